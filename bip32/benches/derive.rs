@@ -0,0 +1,64 @@
+use coins_bip32::{
+    cache::DerivationCache,
+    enc::{MainnetEncoder, XKeyEncoder},
+    xkeys::Parent,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const XPUB: &str = "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y";
+const BATCH: u32 = 100;
+
+fn derive_children(c: &mut Criterion) {
+    let xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+    let mut group = c.benchmark_group("derive 100 children");
+
+    group.bench_function("derive_child", |b| {
+        b.iter(|| {
+            (0..BATCH)
+                .map(|i| xpub.derive_child(black_box(i)).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("derive_range", |b| {
+        b.iter(|| xpub.derive_range(black_box(0..BATCH)).unwrap())
+    });
+    group.finish();
+}
+
+fn derive_paths(c: &mut Criterion) {
+    let xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+    let paths: Vec<String> = (0..BATCH).map(|i| format!("m/0/1/2/{}", i)).collect();
+    let mut group = c.benchmark_group("derive 100 paths under m/0/1/2");
+
+    group.bench_function("derive_path", |b| {
+        b.iter(|| {
+            paths
+                .iter()
+                .map(|p| xpub.derive_path(p.as_str()).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("DerivationCache", |b| {
+        b.iter(|| {
+            let mut cache = DerivationCache::new(xpub);
+            paths
+                .iter()
+                .map(|p| cache.derive_path(p.as_str()).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("DerivationCache + derive_range", |b| {
+        b.iter(|| {
+            let mut cache = DerivationCache::new(xpub);
+            cache
+                .node("m/0/1/2")
+                .unwrap()
+                .derive_range(0..BATCH)
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, derive_children, derive_paths);
+criterion_main!(benches);
@@ -0,0 +1,397 @@
+use alloc::{vec, vec::Vec};
+use k256::ecdsa;
+
+use crate::{
+    derived::{DerivedKey, DerivedXPriv, DerivedXPub},
+    path::KeyDerivation,
+    primitives::XKeyInfo,
+    xkeys::{Parent, XPub},
+    Bip32Error, BIP32_HARDEN,
+};
+
+/// A reason an ancestry proof failed. Steps count from 0 at the first index of
+/// the path from the ancestor to the descendant.
+#[derive(Debug, thiserror::Error)]
+pub enum AncestryError {
+    /// The descendant's derivation does not extend the ancestor's
+    #[error("Key is not a descendant: root or path prefix mismatch")]
+    NotADescendant,
+
+    /// A hardened step has no intermediate xpub
+    #[error("Step {step} (index {index}) is hardened and needs the intermediate xpub")]
+    MissingIntermediate {
+        /// The failing step
+        step: usize,
+        /// The hardened index
+        index: u32,
+    },
+
+    /// An intermediate xpub's metadata does not claim to link to its parent
+    #[error("Step {step} (index {index}): intermediate xpub does not link to its parent")]
+    IntermediateMismatch {
+        /// The failing step
+        step: usize,
+        /// The index
+        index: u32,
+    },
+
+    /// A rederived key does not match the claimed key
+    #[error("Step {step}: rederived key does not match the claimed key")]
+    KeyMismatch {
+        /// The failing step. Equal to the path length for the descendant itself
+        step: usize,
+    },
+
+    /// Derivation failed
+    #[error(transparent)]
+    Bip32Error(#[from] Bip32Error),
+}
+
+/// A proof that a pubkey descends from an xpub.
+///
+/// Unhardened steps are rederived from the parent xpub. Hardened steps cannot
+/// be checked with public keys at all, so the proof carries the xpub after each
+/// hardened step, and these must be trusted. Their parent fingerprint, depth
+/// and index are compared with the step, but those fields are chosen by
+/// whoever builds the proof, so this only catches honest mistakes.
+/// `AncestryProof::verify` reports the intermediates it had to trust. Only a
+/// proof with no hardened steps is verified from the ancestor alone.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    any(feature = "mainnet", feature = "testnet"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct AncestryProof {
+    /// The ancestor xpub
+    pub ancestor: DerivedXPub,
+    /// The intermediate xpubs, in path order
+    pub intermediates: Vec<DerivedXPub>,
+    /// The descendant's derivation
+    pub descendant: KeyDerivation,
+    /// The descendant's pubkey
+    #[cfg_attr(
+        any(feature = "mainnet", feature = "testnet"),
+        serde(with = "sec1_hex")
+    )]
+    pub descendant_key: ecdsa::VerifyingKey,
+}
+
+#[cfg(any(feature = "mainnet", feature = "testnet"))]
+mod sec1_hex {
+    use k256::ecdsa::VerifyingKey;
+
+    pub(super) fn serialize<S>(key: &VerifyingKey, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&hex::encode(key.to_sec1_bytes()))
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<VerifyingKey, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: &str = serde::Deserialize::deserialize(deserializer)?;
+        let bytes = hex::decode(s).map_err(serde::de::Error::custom)?;
+        VerifyingKey::from_sec1_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+fn key_info(xpub: &DerivedXPub) -> &XKeyInfo {
+    xpub.as_ref()
+}
+
+/// The result of checking an ancestry proof
+#[derive(Debug, Clone, PartialEq)]
+pub struct AncestryVerification {
+    trusted: Vec<DerivedXPub>,
+}
+
+impl AncestryVerification {
+    /// The intermediate xpubs after hardened steps, in path order. Nothing
+    /// public links them to their parent, so the descendant is only proven to
+    /// descend from the ancestor if they are trusted.
+    pub fn trusted_intermediates(&self) -> &[DerivedXPub] {
+        &self.trusted
+    }
+
+    /// `true` if every step was rederived from the ancestor, so that no
+    /// intermediate had to be trusted
+    pub const fn is_verified(&self) -> bool {
+        self.trusted.is_empty()
+    }
+}
+
+impl AncestryProof {
+    /// Prove that `descendant` descends from `ancestor`. `intermediates` must
+    /// contain the xpub after each hardened step, and may contain others. Only
+    /// the intermediates needed are kept in the proof.
+    pub fn prove<K>(
+        ancestor: &DerivedXPub,
+        descendant: &K,
+        intermediates: &[DerivedXPub],
+    ) -> Result<Self, AncestryError>
+    where
+        K: DerivedKey + AsRef<ecdsa::VerifyingKey>,
+    {
+        let path = path_between(ancestor.derivation(), descendant.derivation())?;
+        let mut derivation = ancestor.derivation().clone();
+        let mut needed = vec![];
+        for (step, index) in path.iter().enumerate() {
+            derivation = derivation.extended(*index);
+            if *index >= BIP32_HARDEN {
+                let intermediate = intermediates
+                    .iter()
+                    .find(|x| x.derivation() == &derivation)
+                    .ok_or(AncestryError::MissingIntermediate {
+                        step,
+                        index: *index,
+                    })?;
+                needed.push(intermediate.clone());
+            }
+        }
+
+        let proof = Self {
+            ancestor: ancestor.clone(),
+            intermediates: needed,
+            descendant: descendant.derivation().clone(),
+            descendant_key: *descendant.as_ref(),
+        };
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    /// Prove ancestry from a private ancestor. The intermediates are derived
+    /// as needed, and the proof is over the ancestor's xpub.
+    pub fn prove_private<K>(ancestor: &DerivedXPriv, descendant: &K) -> Result<Self, AncestryError>
+    where
+        K: DerivedKey + AsRef<ecdsa::VerifyingKey>,
+    {
+        let path = path_between(ancestor.derivation(), descendant.derivation())?;
+        let mut current = ancestor.clone();
+        let mut intermediates = vec![];
+        for index in path.iter() {
+            current = current.derive_child(*index)?;
+            if *index >= BIP32_HARDEN {
+                intermediates.push(current.verify_key());
+            }
+        }
+        Self::prove(&ancestor.verify_key(), descendant, &intermediates)
+    }
+
+    /// Check the proof step by step, reporting the first step that fails. On
+    /// success, returns the intermediates that had to be trusted. The proof is
+    /// only conclusive if `AncestryVerification::is_verified` is `true`.
+    pub fn verify(&self) -> Result<AncestryVerification, AncestryError> {
+        let path = path_between(self.ancestor.derivation(), &self.descendant)?;
+
+        let mut trusted = vec![];
+        let mut current = self.ancestor.clone();
+        for (step, index) in path.iter().enumerate() {
+            let derivation = current.derivation().extended(*index);
+            let intermediate = self
+                .intermediates
+                .iter()
+                .find(|x| x.derivation() == &derivation);
+
+            current = match (*index >= BIP32_HARDEN, intermediate) {
+                (true, None) => {
+                    return Err(AncestryError::MissingIntermediate {
+                        step,
+                        index: *index,
+                    })
+                }
+                (true, Some(next)) => {
+                    let parent = key_info(&current);
+                    let info = key_info(next);
+                    let linked = info.parent == AsRef::<XPub>::as_ref(&current).fingerprint()
+                        && info.index == *index
+                        && u32::from(info.depth) == u32::from(parent.depth) + 1;
+                    if !linked {
+                        return Err(AncestryError::IntermediateMismatch {
+                            step,
+                            index: *index,
+                        });
+                    }
+                    trusted.push(next.clone());
+                    next.clone()
+                }
+                (false, intermediate) => {
+                    let next = current.derive_child(*index)?;
+                    if intermediate.is_some_and(|x| x != &next) {
+                        return Err(AncestryError::KeyMismatch { step });
+                    }
+                    next
+                }
+            };
+        }
+
+        if AsRef::<ecdsa::VerifyingKey>::as_ref(&current) != &self.descendant_key {
+            return Err(AncestryError::KeyMismatch { step: path.len() });
+        }
+        Ok(AncestryVerification { trusted })
+    }
+}
+
+fn path_between(
+    ancestor: &KeyDerivation,
+    descendant: &KeyDerivation,
+) -> Result<crate::path::DerivationPath, AncestryError> {
+    if !ancestor.is_possible_ancestor_of(descendant) {
+        return Err(AncestryError::NotADescendant);
+    }
+    ancestor
+        .path_to_descendant(descendant)
+        .ok_or(AncestryError::NotADescendant)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{derived::DerivedPubkey, primitives::Hint};
+
+    fn root() -> DerivedXPriv {
+        DerivedXPriv::root_from_seed(&[3u8; 32], Some(Hint::SegWit)).unwrap()
+    }
+
+    fn pubkey(xpub: &DerivedXPub) -> DerivedPubkey {
+        DerivedPubkey::new(*xpub.as_ref(), xpub.derivation().clone())
+    }
+
+    #[test]
+    fn it_proves_unhardened_ancestry() {
+        let account = root().derive_path("m/84'/0'/0'").unwrap().verify_key();
+        let child = account.derive_path("m/1/5").unwrap();
+
+        let proof = AncestryProof::prove(&account, &pubkey(&child), &[]).unwrap();
+        assert!(proof.intermediates.is_empty());
+        assert!(proof.verify().unwrap().is_verified());
+
+        // a forged key with the right derivation fails at the last step
+        let other = account.derive_path("m/1/6").unwrap();
+        let forged = DerivedPubkey::new(*other.as_ref(), child.derivation().clone());
+        assert!(matches!(
+            AncestryProof::prove(&account, &forged, &[]),
+            Err(AncestryError::KeyMismatch { step: 2 })
+        ));
+    }
+
+    #[test]
+    fn it_requires_hardened_intermediates() {
+        let root = root();
+        let root_xpub = root.verify_key();
+        let hardened = root.derive_path("m/84'/0'").unwrap().verify_key();
+        let child = hardened.derive_path("m/0/0").unwrap();
+
+        assert!(matches!(
+            AncestryProof::prove(&root_xpub, &pubkey(&child), &[]),
+            Err(AncestryError::MissingIntermediate { step: 0, .. })
+        ));
+
+        let first = root.derive_path("m/84'").unwrap().verify_key();
+        let proof =
+            AncestryProof::prove(&root_xpub, &pubkey(&child), &[first, hardened.clone()]).unwrap();
+        assert_eq!(proof.intermediates.len(), 2);
+
+        let private = AncestryProof::prove_private(&root, &pubkey(&child)).unwrap();
+        assert_eq!(private, proof);
+
+        // hardened steps are only linked by trusting their xpubs
+        let verification = proof.verify().unwrap();
+        assert!(!verification.is_verified());
+        assert_eq!(
+            verification.trusted_intermediates(),
+            &proof.intermediates[..]
+        );
+    }
+
+    #[test]
+    fn it_does_not_verify_forged_hardened_steps() {
+        let root = root();
+        let root_xpub = root.verify_key();
+
+        // an unrelated xpub that copies the metadata of m/84'
+        let unrelated = DerivedXPriv::root_from_seed(&[5u8; 32], Some(Hint::SegWit))
+            .unwrap()
+            .verify_key();
+        let genuine = root.derive_path("m/84'").unwrap().verify_key();
+        let info = XKeyInfo {
+            parent: AsRef::<XPub>::as_ref(&root_xpub).fingerprint(),
+            ..*key_info(&genuine)
+        };
+        let forged = DerivedXPub::new(
+            XPub::new(*AsRef::<ecdsa::VerifyingKey>::as_ref(&unrelated), info),
+            genuine.derivation().clone(),
+        );
+        assert_ne!(forged, genuine);
+        let child = forged.derive_path("m/0/1").unwrap();
+
+        // the proof is consistent, but rests on the forged xpub
+        let proof =
+            AncestryProof::prove(&root_xpub, &pubkey(&child), core::slice::from_ref(&forged))
+                .unwrap();
+        let verification = proof.verify().unwrap();
+        assert!(!verification.is_verified());
+        assert_eq!(verification.trusted_intermediates(), &[forged]);
+    }
+
+    #[test]
+    fn it_reports_the_failing_step() {
+        let root = root();
+        let child = root.derive_path("m/84'/0'/2").unwrap().verify_key();
+        let mut proof = AncestryProof::prove_private(&root, &child).unwrap();
+        assert_eq!(proof.verify().unwrap().trusted_intermediates().len(), 2);
+
+        // swap in an xpub from another branch with the claimed derivation
+        let imposter = root.derive_path("m/49'/0'").unwrap().verify_key();
+        proof.intermediates[1] = DerivedXPub::new(
+            *AsRef::<XPub>::as_ref(&imposter),
+            proof.intermediates[1].derivation().clone(),
+        );
+        assert!(matches!(
+            proof.verify(),
+            Err(AncestryError::IntermediateMismatch {
+                step: 1,
+                index: 0x8000_0000
+            })
+        ));
+
+        let unrelated = DerivedXPriv::root_from_seed(&[4u8; 32], None)
+            .unwrap()
+            .verify_key();
+        assert!(matches!(
+            AncestryProof::prove(&unrelated, &child, &[]),
+            Err(AncestryError::NotADescendant)
+        ));
+    }
+
+    #[test]
+    fn it_checks_supplied_unhardened_intermediates() {
+        let account = root().derive_path("m/84'/0'/0'").unwrap().verify_key();
+        let child = account.derive_path("m/0/3").unwrap();
+        let mut proof = AncestryProof::prove(&account, &child, &[]).unwrap();
+
+        let wrong = account.derive_child(1).unwrap();
+        proof.intermediates.push(DerivedXPub::new(
+            *AsRef::<XPub>::as_ref(&wrong),
+            account.derivation().extended(0),
+        ));
+        assert!(matches!(
+            proof.verify(),
+            Err(AncestryError::KeyMismatch { step: 0 })
+        ));
+    }
+
+    #[cfg(feature = "mainnet")]
+    #[test]
+    fn it_serializes_proofs() {
+        let root = root();
+        let child = root.derive_path("m/84'/0'/0'/0/1").unwrap().verify_key();
+        let proof = AncestryProof::prove_private(&root, &pubkey(&child)).unwrap();
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let parsed: AncestryProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, proof);
+        parsed.verify().unwrap();
+    }
+}
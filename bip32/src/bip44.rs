@@ -0,0 +1,667 @@
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::convert::TryFrom;
+
+use crate::{
+    derived::DerivedKey,
+    path::{harden_index, DerivationPath},
+    primitives::{Hint, XKeyInfo},
+    Bip32Error, BIP32_HARDEN,
+};
+
+/// A BIP44-family purpose, the first level of an account path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Purpose {
+    /// BIP44 legacy P2PKH accounts
+    Bip44,
+    /// BIP49 P2SH-P2WPKH accounts
+    Bip49,
+    /// BIP84 native P2WPKH accounts
+    Bip84,
+    /// BIP86 single-key P2TR accounts
+    Bip86,
+}
+
+impl Purpose {
+    /// All purposes
+    pub const ALL: [Purpose; 4] = [
+        Purpose::Bip44,
+        Purpose::Bip49,
+        Purpose::Bip84,
+        Purpose::Bip86,
+    ];
+
+    /// The unhardened purpose index
+    pub const fn index(&self) -> u32 {
+        match self {
+            Purpose::Bip44 => 44,
+            Purpose::Bip49 => 49,
+            Purpose::Bip84 => 84,
+            Purpose::Bip86 => 86,
+        }
+    }
+
+    /// The purpose with some unhardened index, if any
+    pub fn from_index(index: u32) -> Option<Self> {
+        Self::ALL.iter().copied().find(|p| p.index() == index)
+    }
+
+    /// The output type hint matching the purpose
+    pub const fn hint(&self) -> Hint {
+        match self {
+            Purpose::Bip44 => Hint::Legacy,
+            Purpose::Bip49 => Hint::Compatibility,
+            Purpose::Bip84 => Hint::SegWit,
+            Purpose::Bip86 => Hint::Taproot,
+        }
+    }
+
+    /// The purpose matching an output type hint. Multisig hints have no
+    /// BIP44-family purpose
+    pub fn from_hint(hint: Hint) -> Option<Self> {
+        Self::ALL.iter().copied().find(|p| p.hint() == hint)
+    }
+}
+
+/// A registered SLIP-44 coin type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoinType {
+    /// The unhardened coin type index
+    pub index: u32,
+    /// The ticker symbol
+    pub symbol: &'static str,
+    /// The coin name
+    pub name: &'static str,
+}
+
+macro_rules! coin_types {
+    ($($(#[$outer:meta])* $const_name:ident: $index:literal, $symbol:literal, $name:literal;)*) => {
+        $(
+            $(#[$outer])*
+            pub const $const_name: CoinType = CoinType {
+                index: $index,
+                symbol: $symbol,
+                name: $name,
+            };
+        )*
+
+        /// The SLIP-44 coin types known to this crate
+        pub const COIN_TYPES: &[CoinType] = &[$($const_name,)*];
+    };
+}
+
+coin_types! {
+    /// Bitcoin
+    BITCOIN: 0, "BTC", "Bitcoin";
+    /// The coin type shared by all testnets
+    TESTNET: 1, "", "Testnet (all coins)";
+    /// Litecoin
+    LITECOIN: 2, "LTC", "Litecoin";
+    /// Dogecoin
+    DOGECOIN: 3, "DOGE", "Dogecoin";
+    /// Dash
+    DASH: 5, "DASH", "Dash";
+    /// Ether
+    ETHER: 60, "ETH", "Ether";
+    /// Ether Classic
+    ETHER_CLASSIC: 61, "ETC", "Ether Classic";
+    /// Zcash
+    ZCASH: 133, "ZEC", "Zcash";
+    /// Bitcoin Cash
+    BITCOIN_CASH: 145, "BCH", "Bitcoin Cash";
+    /// Handshake
+    HANDSHAKE: 5353, "HNS", "Handshake";
+}
+
+/// Look up a known coin type by its unhardened index
+pub fn coin_type(index: u32) -> Option<CoinType> {
+    COIN_TYPES.iter().copied().find(|c| c.index == index)
+}
+
+/// Look up a known coin type by its ticker symbol, ignoring case
+pub fn coin_type_by_symbol(symbol: &str) -> Option<CoinType> {
+    COIN_TYPES
+        .iter()
+        .copied()
+        .find(|c| !c.symbol.is_empty() && c.symbol.eq_ignore_ascii_case(symbol))
+}
+
+/// The chain, or change level, of an account path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Chain {
+    /// The external chain, used for receiving. Index 0
+    External,
+    /// The internal chain, used for change. Index 1
+    Internal,
+}
+
+impl Chain {
+    /// The chain index
+    pub const fn index(&self) -> u32 {
+        match self {
+            Chain::External => 0,
+            Chain::Internal => 1,
+        }
+    }
+
+    /// The chain with some index, if any
+    pub const fn from_index(index: u32) -> Option<Self> {
+        match index {
+            0 => Some(Chain::External),
+            1 => Some(Chain::Internal),
+            _ => None,
+        }
+    }
+}
+
+/// A typed BIP44-family path, `m/purpose'/coin_type'/account'/change/index`.
+/// The change and index levels are optional, so that account-level and
+/// chain-level paths may also be represented.
+///
+/// `Display` never fails, even for paths that `to_path` rejects: hardened
+/// indices are printed as set, and an address index without a chain follows
+/// a `?` chain level.
+///
+/// ```
+/// use coins_bip32::bip44::{Bip44Path, Chain, Purpose, BITCOIN};
+/// # fn main() -> Result<(), coins_bip32::Bip32Error> {
+/// let path = Bip44Path::new(Purpose::Bip84, BITCOIN.index)
+///     .account(0)
+///     .chain(Chain::Internal)
+///     .index(7)
+///     .to_path()?;
+/// assert_eq!(path.derivation_string(), "m/84'/0'/0'/1/7");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bip44Path {
+    /// The purpose
+    pub purpose: Purpose,
+    /// The unhardened coin type index
+    pub coin_type: u32,
+    /// The unhardened account index
+    pub account: u32,
+    /// The chain, if this is a chain or address path
+    pub chain: Option<Chain>,
+    /// The address index, if this is an address path
+    pub index: Option<u32>,
+}
+
+impl Bip44Path {
+    /// Start building a path for account 0 of a coin type
+    pub const fn new(purpose: Purpose, coin_type: u32) -> Self {
+        Self {
+            purpose,
+            coin_type,
+            account: 0,
+            chain: None,
+            index: None,
+        }
+    }
+
+    /// Set the account index
+    pub const fn account(mut self, account: u32) -> Self {
+        self.account = account;
+        self
+    }
+
+    /// Set the chain
+    pub const fn chain(mut self, chain: Chain) -> Self {
+        self.chain = Some(chain);
+        self
+    }
+
+    /// Set the address index
+    pub const fn index(mut self, index: u32) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// The path of the account, without chain or index
+    pub const fn account_level(&self) -> Self {
+        Self {
+            chain: None,
+            index: None,
+            ..*self
+        }
+    }
+
+    /// The output type hint matching the purpose
+    pub const fn hint(&self) -> Hint {
+        self.purpose.hint()
+    }
+
+    /// The registered coin type, if known
+    pub fn coin(&self) -> Option<CoinType> {
+        coin_type(self.coin_type)
+    }
+
+    /// Build the derivation path. Fails if any index is already hardened, or
+    /// if an address index is set without a chain.
+    pub fn to_path(&self) -> Result<DerivationPath, Bip32Error> {
+        let check = |name: &str, index: u32| match index < BIP32_HARDEN {
+            true => Ok(index),
+            false => Err(Bip32Error::MalformattedDerivation(format!(
+                "{} index {} must be unhardened",
+                name, index
+            ))),
+        };
+        let mut path = vec![
+            harden_index(self.purpose.index()),
+            harden_index(check("coin type", self.coin_type)?),
+            harden_index(check("account", self.account)?),
+        ];
+        match (self.chain, self.index) {
+            (None, None) => {}
+            (Some(chain), None) => path.push(chain.index()),
+            (Some(chain), Some(index)) => {
+                path.push(chain.index());
+                path.push(check("address", index)?);
+            }
+            (None, Some(_)) => {
+                return Err(Bip32Error::MalformattedDerivation(
+                    "an address index requires a chain".to_owned(),
+                ))
+            }
+        }
+        Ok(path.into())
+    }
+}
+
+impl core::fmt::Display for Bip44Path {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "m/{}'/{}'/{}'",
+            self.purpose.index(),
+            self.coin_type,
+            self.account
+        )?;
+        match (self.chain, self.index) {
+            (None, None) => Ok(()),
+            (Some(chain), None) => write!(f, "/{}", chain.index()),
+            (Some(chain), Some(index)) => write!(f, "/{}/{}", chain.index(), index),
+            (None, Some(index)) => write!(f, "/?/{}", index),
+        }
+    }
+}
+
+impl TryFrom<Bip44Path> for DerivationPath {
+    type Error = Bip32Error;
+
+    fn try_from(path: Bip44Path) -> Result<Self, Self::Error> {
+        path.to_path()
+    }
+}
+
+impl TryFrom<&DerivationPath> for Bip44Path {
+    type Error = Bip32Error;
+
+    /// Parse a path that satisfies the default `PathPolicy`
+    fn try_from(path: &DerivationPath) -> Result<Self, Self::Error> {
+        PathPolicy::default().validate(path)
+    }
+}
+
+/// A reason a path violates BIP44-family conventions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// The path is not 3 (account), 4 (chain) or 5 (address) levels deep
+    WrongDepth(usize),
+    /// The purpose, coin type or account level is not hardened
+    Unhardened {
+        /// The level name
+        level: &'static str,
+        /// The index found
+        index: u32,
+    },
+    /// The chain or address level is hardened
+    Hardened {
+        /// The level name
+        level: &'static str,
+        /// The index found
+        index: u32,
+    },
+    /// The purpose is not 44, 49, 84 or 86
+    UnknownPurpose(u32),
+    /// The purpose does not match the expected output type hint
+    PurposeHintMismatch {
+        /// The purpose found
+        purpose: Purpose,
+        /// The hint expected
+        hint: Hint,
+    },
+    /// The coin type is not in the SLIP-44 registry
+    UnknownCoinType(u32),
+    /// The coin type is not the expected coin type
+    CoinTypeMismatch {
+        /// The coin type expected
+        expected: u32,
+        /// The coin type found
+        found: u32,
+    },
+    /// The chain index is not 0 or 1
+    InvalidChain(u32),
+    /// The account index exceeds the policy's maximum
+    AccountTooHigh {
+        /// The maximum account index
+        max: u32,
+        /// The account index found
+        found: u32,
+    },
+}
+
+impl core::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::WrongDepth(depth) => write!(
+                f,
+                "path has {} levels, expected 3 (account), 4 (chain) or 5 (address)",
+                depth
+            ),
+            Self::Unhardened { level, index } => {
+                write!(f, "{} level {} must be hardened", level, index)
+            }
+            Self::Hardened { level, index } => write!(
+                f,
+                "{} level {}' must not be hardened",
+                level,
+                index - BIP32_HARDEN
+            ),
+            Self::UnknownPurpose(purpose) => {
+                write!(f, "purpose {} is not one of 44, 49, 84 or 86", purpose)
+            }
+            Self::PurposeHintMismatch { purpose, hint } => write!(
+                f,
+                "purpose {}' does not match hint {:?}",
+                purpose.index(),
+                hint
+            ),
+            Self::UnknownCoinType(coin_type) => {
+                write!(
+                    f,
+                    "coin type {} is not a known SLIP-44 coin type",
+                    coin_type
+                )
+            }
+            Self::CoinTypeMismatch { expected, found } => {
+                write!(f, "coin type {} is not the expected {}", found, expected)
+            }
+            Self::InvalidChain(chain) => write!(f, "chain {} is not 0 or 1", chain),
+            Self::AccountTooHigh { max, found } => {
+                write!(f, "account {} exceeds the maximum {}", found, max)
+            }
+        }
+    }
+}
+
+pub(crate) fn display_violations(violations: &[PolicyViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// A policy for BIP44-family paths. The default policy checks only the
+/// structure of the path: its depth, hardening, purpose and chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathPolicy {
+    /// If set, the purpose must match this output type hint
+    pub hint: Option<Hint>,
+    /// If set, the coin type must be this unhardened index
+    pub coin_type: Option<u32>,
+    /// If `true`, the coin type must be in the SLIP-44 registry
+    pub require_known_coin: bool,
+    /// If set, the account index may not exceed this
+    pub max_account: Option<u32>,
+}
+
+impl PathPolicy {
+    /// A policy requiring a purpose and coin type
+    pub const fn new(purpose: Purpose, coin_type: u32) -> Self {
+        Self {
+            hint: Some(purpose.hint()),
+            coin_type: Some(coin_type),
+            require_known_coin: false,
+            max_account: None,
+        }
+    }
+
+    /// List every way a path violates the policy. Empty if the path is
+    /// acceptable.
+    pub fn check(&self, path: &DerivationPath) -> Vec<PolicyViolation> {
+        let mut violations = vec![];
+        let indices: Vec<u32> = path.iter().copied().collect();
+        if !(3..=5).contains(&indices.len()) {
+            violations.push(PolicyViolation::WrongDepth(indices.len()));
+        }
+
+        let levels = ["purpose", "coin type", "account", "chain", "address"];
+        for (i, (level, index)) in levels.iter().zip(indices.iter()).enumerate() {
+            let hardened = *index >= BIP32_HARDEN;
+            if i < 3 && !hardened {
+                violations.push(PolicyViolation::Unhardened {
+                    level,
+                    index: *index,
+                });
+            }
+            if i >= 3 && hardened {
+                violations.push(PolicyViolation::Hardened {
+                    level,
+                    index: *index,
+                });
+            }
+        }
+
+        let unharden = |index: u32| index & !BIP32_HARDEN;
+        if let Some(purpose) = indices.first().map(|p| unharden(*p)) {
+            match Purpose::from_index(purpose) {
+                None => violations.push(PolicyViolation::UnknownPurpose(purpose)),
+                Some(purpose) => match self.hint {
+                    Some(hint) if hint != purpose.hint() => {
+                        violations.push(PolicyViolation::PurposeHintMismatch { purpose, hint })
+                    }
+                    _ => {}
+                },
+            }
+        }
+        if let Some(found) = indices.get(1).map(|c| unharden(*c)) {
+            match self.coin_type {
+                Some(expected) if expected != found => {
+                    violations.push(PolicyViolation::CoinTypeMismatch { expected, found })
+                }
+                _ => {}
+            }
+            if self.require_known_coin && coin_type(found).is_none() {
+                violations.push(PolicyViolation::UnknownCoinType(found));
+            }
+        }
+        if let (Some(max), Some(found)) = (self.max_account, indices.get(2)) {
+            let found = unharden(*found);
+            if found > max {
+                violations.push(PolicyViolation::AccountTooHigh { max, found });
+            }
+        }
+        if let Some(chain) = indices.get(3).map(|c| unharden(*c)) {
+            if Chain::from_index(chain).is_none() {
+                violations.push(PolicyViolation::InvalidChain(chain));
+            }
+        }
+        violations
+    }
+
+    /// Parse a path into a `Bip44Path`, or explain why it violates the
+    /// policy
+    pub fn validate(&self, path: &DerivationPath) -> Result<Bip44Path, Bip32Error> {
+        let violations = self.check(path);
+        if !violations.is_empty() {
+            return Err(Bip32Error::PathPolicyViolation(violations));
+        }
+        let indices: Vec<u32> = path.iter().map(|i| i & !BIP32_HARDEN).collect();
+        Ok(Bip44Path {
+            purpose: Purpose::from_index(indices[0]).expect("checked"),
+            coin_type: indices[1],
+            account: indices[2],
+            chain: indices
+                .get(3)
+                .map(|c| Chain::from_index(*c).expect("checked")),
+            index: indices.get(4).copied(),
+        })
+    }
+
+    /// Check a derived key's path. Unless the policy sets a hint, the purpose
+    /// must match the key's own hint.
+    pub fn check_key<K>(&self, key: &K) -> Vec<PolicyViolation>
+    where
+        K: DerivedKey + AsRef<XKeyInfo>,
+    {
+        let policy = Self {
+            hint: self.hint.or(Some(key.as_ref().hint)),
+            ..*self
+        };
+        policy.check(&key.derivation().path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        derived::DerivedXPriv,
+        enc::{MainnetEncoder, XKeyEncoder},
+        path::KeyDerivation,
+        xkeys::Parent,
+    };
+
+    #[test]
+    fn it_builds_paths() {
+        let account = Bip44Path::new(Purpose::Bip86, BITCOIN.index).account(3);
+        assert_eq!(account.to_string(), "m/86'/0'/3'");
+        assert_eq!(account.chain(Chain::External).to_string(), "m/86'/0'/3'/0");
+        let address = account.chain(Chain::Internal).index(9);
+        assert_eq!(address.to_string(), "m/86'/0'/3'/1/9");
+        assert_eq!(address.account_level(), account);
+        assert_eq!(address.hint(), Hint::Taproot);
+        assert_eq!(address.coin(), Some(BITCOIN));
+
+        let path = DerivationPath::try_from(address).unwrap();
+        assert_eq!(Bip44Path::try_from(&path).unwrap(), address);
+
+        // invalid paths fail to build, but still display
+        let hardened = Bip44Path::new(Purpose::Bip44, BIP32_HARDEN);
+        assert!(hardened.to_path().is_err());
+        assert_eq!(hardened.to_string(), "m/44'/2147483648'/0'");
+        let chainless = Bip44Path::new(Purpose::Bip44, 0).index(1);
+        assert!(chainless.to_path().is_err());
+        assert_eq!(chainless.to_string(), "m/44'/0'/0'/?/1");
+    }
+
+    #[test]
+    fn it_looks_up_coin_types() {
+        assert_eq!(coin_type(2), Some(LITECOIN));
+        assert_eq!(coin_type_by_symbol("eth"), Some(ETHER));
+        assert_eq!(coin_type(7), None);
+        assert_eq!(coin_type_by_symbol(""), None);
+        assert_eq!(
+            Purpose::from_hint(Hint::Compatibility),
+            Some(Purpose::Bip49)
+        );
+        assert_eq!(Purpose::from_hint(Hint::SegWitMultisig), None);
+    }
+
+    #[test]
+    fn it_explains_violations() {
+        let policy = PathPolicy::new(Purpose::Bip84, BITCOIN.index);
+        let path: DerivationPath = "m/84'/0'/0'/0/0".parse().unwrap();
+        assert!(policy.check(&path).is_empty());
+
+        let path: DerivationPath = "m/84'/0'/0/0/0".parse().unwrap();
+        assert_eq!(
+            policy.check(&path),
+            vec![PolicyViolation::Unhardened {
+                level: "account",
+                index: 0
+            }]
+        );
+
+        let path: DerivationPath = "m/44'/2'/0'/2/0'".parse().unwrap();
+        assert_eq!(
+            policy.check(&path),
+            vec![
+                PolicyViolation::Hardened {
+                    level: "address",
+                    index: BIP32_HARDEN
+                },
+                PolicyViolation::PurposeHintMismatch {
+                    purpose: Purpose::Bip44,
+                    hint: Hint::SegWit
+                },
+                PolicyViolation::CoinTypeMismatch {
+                    expected: 0,
+                    found: 2
+                },
+                PolicyViolation::InvalidChain(2),
+            ]
+        );
+
+        let path: DerivationPath = "m/48'/9999'".parse().unwrap();
+        let strict = PathPolicy {
+            require_known_coin: true,
+            max_account: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            strict.check(&path),
+            vec![
+                PolicyViolation::WrongDepth(2),
+                PolicyViolation::UnknownPurpose(48),
+                PolicyViolation::UnknownCoinType(9999),
+            ]
+        );
+        let path: DerivationPath = "m/44'/0'/11'".parse().unwrap();
+        assert_eq!(
+            strict.check(&path),
+            vec![PolicyViolation::AccountTooHigh { max: 10, found: 11 }]
+        );
+
+        match PathPolicy::default().validate(&"m/84'/0'/0/0/0".parse().unwrap()) {
+            Err(Bip32Error::PathPolicyViolation(v)) => {
+                assert_eq!(display_violations(&v), "account level 0 must be hardened")
+            }
+            _ => panic!("expected a violation"),
+        }
+    }
+
+    #[test]
+    fn it_checks_key_hints() {
+        let mut xpriv = MainnetEncoder::xpriv_from_base58("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi").unwrap();
+        xpriv.xkey_info.hint = Hint::SegWit;
+        let root = DerivedXPriv::new(
+            xpriv,
+            KeyDerivation {
+                root: [0u8; 4].into(),
+                path: vec![].into(),
+            },
+        );
+        let policy = PathPolicy::default();
+
+        let key = root.derive_path("m/84'/0'/0'").unwrap();
+        assert!(policy.check_key(&key).is_empty());
+
+        let key = root.derive_path("m/49'/0'/0'").unwrap();
+        assert_eq!(
+            policy.check_key(&key),
+            vec![PolicyViolation::PurposeHintMismatch {
+                purpose: Purpose::Bip49,
+                hint: Hint::SegWit
+            }]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use alloc::{format, string::String, vec::Vec};
+use core::convert::TryInto;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use k256::ecdsa;
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+use crate::{
+    path::{harden_index, DerivationPath},
+    primitives::{ChainCode, Hint, KeyFingerprint, XKeyInfo},
+    wif::Privkey,
+    xkeys::XPriv,
+    Bip32Error, BIP32_HARDEN,
+};
+
+/// The BIP85 purpose index, `83696968'`
+pub const BIP85_PURPOSE: u32 = 83696968;
+/// The HMAC key used to turn a derived private key into entropy
+pub const BIP85_HMAC_KEY: &[u8] = b"bip-entropy-from-k";
+
+/// The BIP39 mnemonic application number
+pub const APP_BIP39: u32 = 39;
+/// The HD-Seed WIF application number
+pub const APP_WIF: u32 = 2;
+/// The XPRV application number
+pub const APP_XPRV: u32 = 32;
+/// The HEX application number
+pub const APP_HEX: u32 = 128169;
+/// The base64 password application number
+pub const APP_PWD_BASE64: u32 = 707764;
+/// The base85 password application number
+pub const APP_PWD_BASE85: u32 = 707785;
+
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// 64 bytes of BIP85 entropy. Zeroized on drop.
+pub type Bip85Entropy = Zeroizing<[u8; 64]>;
+
+fn invalid(msg: impl Into<String>) -> Bip32Error {
+    Bip32Error::InvalidBip85Request(msg.into())
+}
+
+/// Check that an application parameter is in range
+fn check_range(
+    name: &str,
+    value: u32,
+    range: core::ops::RangeInclusive<u32>,
+) -> Result<(), Bip32Error> {
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "{} must be in {}..={}, got {}",
+            name,
+            range.start(),
+            range.end(),
+            value
+        )))
+    }
+}
+
+/// Build the path `m/83696968'/{app}'/...` from unhardened application
+/// indices.
+pub fn app_path(indices: &[u32]) -> Result<DerivationPath, Bip32Error> {
+    core::iter::once(BIP85_PURPOSE)
+        .chain(indices.iter().copied())
+        .map(|index| match index {
+            i if i < BIP32_HARDEN => Ok(harden_index(i)),
+            _ => Err(invalid(format!("index {} is already hardened", index))),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Into::into)
+}
+
+/// Derive 64 bytes of entropy at a BIP85 path. The path must start at
+/// `83696968'` and be fully hardened.
+pub fn derive_entropy<E, P>(root: &XPriv, path: P) -> Result<Bip85Entropy, Bip32Error>
+where
+    E: Into<Bip32Error>,
+    P: TryInto<DerivationPath, Error = E>,
+{
+    let path: DerivationPath = path.try_into().map_err(Into::into)?;
+    if path.iter().next() != Some(&harden_index(BIP85_PURPOSE)) {
+        return Err(invalid("path must start at 83696968'"));
+    }
+    if path.iter().any(|index| *index < BIP32_HARDEN) {
+        return Err(invalid("path must be fully hardened"));
+    }
+
+    let child = root.derive_path(&path)?;
+    let k = Zeroizing::new(AsRef::<ecdsa::SigningKey>::as_ref(&child).to_bytes());
+
+    let mut mac = Hmac::<Sha512>::new_from_slice(BIP85_HMAC_KEY).expect("key length is ok");
+    mac.update(&k);
+    let mut entropy = Zeroizing::new([0u8; 64]);
+    entropy.copy_from_slice(&mac.finalize().into_bytes());
+    Ok(entropy)
+}
+
+/// Derive entropy for an application, at `m/83696968'/{app}'/...`
+pub fn app_entropy(root: &XPriv, indices: &[u32]) -> Result<Bip85Entropy, Bip32Error> {
+    derive_entropy(root, app_path(indices)?)
+}
+
+/// Derive a private key for the HD-Seed WIF application, at
+/// `m/83696968'/2'/{index}'`. The key has a compressed pubkey.
+pub fn wif(root: &XPriv, index: u32) -> Result<Privkey, Bip32Error> {
+    let entropy = app_entropy(root, &[APP_WIF, index])?;
+    Privkey::from_bytes(&entropy[..32])
+}
+
+/// Derive a root extended private key for the XPRV application, at
+/// `m/83696968'/32'/{index}'`. The first 32 bytes of entropy are the chain
+/// code, the last 32 are the private key.
+pub fn xpriv(root: &XPriv, index: u32) -> Result<XPriv, Bip32Error> {
+    let entropy = app_entropy(root, &[APP_XPRV, index])?;
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&entropy[..32]);
+    Ok(XPriv::new(
+        ecdsa::SigningKey::from_slice(&entropy[32..])?,
+        XKeyInfo {
+            depth: 0,
+            parent: KeyFingerprint([0u8; 4]),
+            index: 0,
+            chain_code: ChainCode(chain_code),
+            hint: Hint::Legacy,
+        },
+    ))
+}
+
+/// Derive `num_bytes` of entropy for the HEX application, at
+/// `m/83696968'/128169'/{num_bytes}'/{index}'`. `num_bytes` must be in
+/// `16..=64`.
+pub fn hex(root: &XPriv, num_bytes: u32, index: u32) -> Result<Zeroizing<Vec<u8>>, Bip32Error> {
+    check_range("num_bytes", num_bytes, 16..=64)?;
+    let entropy = app_entropy(root, &[APP_HEX, num_bytes, index])?;
+    Ok(Zeroizing::new(entropy[..num_bytes as usize].to_vec()))
+}
+
+/// Derive a base64 password for the PWD BASE64 application, at
+/// `m/83696968'/707764'/{pwd_len}'/{index}'`. `pwd_len` must be in `20..=86`.
+pub fn password_base64(
+    root: &XPriv,
+    pwd_len: u32,
+    index: u32,
+) -> Result<Zeroizing<String>, Bip32Error> {
+    check_range("pwd_len", pwd_len, 20..=86)?;
+    let entropy = app_entropy(root, &[APP_PWD_BASE64, pwd_len, index])?;
+
+    let mut pwd = Zeroizing::new(STANDARD.encode(&entropy[..]));
+    pwd.truncate(pwd_len as usize);
+    Ok(pwd)
+}
+
+/// Derive a base85 password for the PWD BASE85 application, at
+/// `m/83696968'/707785'/{pwd_len}'/{index}'`. `pwd_len` must be in `10..=80`.
+pub fn password_base85(
+    root: &XPriv,
+    pwd_len: u32,
+    index: u32,
+) -> Result<Zeroizing<String>, Bip32Error> {
+    check_range("pwd_len", pwd_len, 10..=80)?;
+    let entropy = app_entropy(root, &[APP_PWD_BASE85, pwd_len, index])?;
+
+    let mut pwd = Zeroizing::new(String::with_capacity(80));
+    for chunk in entropy.chunks(4) {
+        let mut n = u32::from_be_bytes(chunk.try_into().expect("64 is a multiple of 4"));
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = BASE85_ALPHABET[(n % 85) as usize];
+            n /= 85;
+        }
+        digits.iter().for_each(|d| pwd.push(*d as char));
+    }
+    pwd.truncate(pwd_len as usize);
+    Ok(pwd)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::enc::{MainnetEncoder, XKeyEncoder};
+
+    // https://github.com/bitcoin/bips/blob/master/bip-0085.mediawiki
+    const ROOT: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+    fn root() -> XPriv {
+        MainnetEncoder::xpriv_from_base58(ROOT).unwrap()
+    }
+
+    #[test]
+    fn it_derives_entropy() {
+        let cases = [
+            (
+                "m/83696968'/0'/0'",
+                "efecfbccffea313214232d29e71563d941229afb4338c21f9517c41aaa0d16f00b83d2a09ef747e7a64e8e2bd5a14869e693da66ce94ac2da570ab7ee48618f7",
+            ),
+            (
+                "m/83696968'/0'/1'",
+                "70c6e3e8ebee8dc4c0dbba66076819bb8c09672527c4277ca8729532ad711872218f826919f6b67218adde99018a6df9095ab2b58d803b5b93ec9802085a690e",
+            ),
+        ];
+        for (path, expected) in cases.iter() {
+            let entropy = derive_entropy(&root(), *path).unwrap();
+            assert_eq!(hex::encode(&entropy[..]), *expected);
+        }
+        assert_eq!(
+            app_entropy(&root(), &[0, 1]).unwrap(),
+            derive_entropy(&root(), "m/83696968'/0'/1'").unwrap()
+        );
+    }
+
+    #[test]
+    fn it_rejects_bad_paths() {
+        assert!(matches!(
+            derive_entropy(&root(), "m/44'/0'/0'"),
+            Err(Bip32Error::InvalidBip85Request(_))
+        ));
+        assert!(matches!(
+            derive_entropy(&root(), "m/83696968'/0'/0"),
+            Err(Bip32Error::InvalidBip85Request(_))
+        ));
+        assert!(matches!(
+            app_path(&[APP_WIF, BIP32_HARDEN]),
+            Err(Bip32Error::InvalidBip85Request(_))
+        ));
+        assert!(matches!(
+            hex(&root(), 15, 0),
+            Err(Bip32Error::InvalidBip85Request(_))
+        ));
+        assert!(matches!(
+            password_base64(&root(), 87, 0),
+            Err(Bip32Error::InvalidBip85Request(_))
+        ));
+        assert!(matches!(
+            password_base85(&root(), 9, 0),
+            Err(Bip32Error::InvalidBip85Request(_))
+        ));
+    }
+
+    #[test]
+    fn it_derives_applications() {
+        let root = root();
+        assert_eq!(
+            wif(&root, 0).unwrap().to_wif::<crate::enc::Main>().as_str(),
+            "Kzyv4uF39d4Jrw2W7UryTHwZr1zQVNk4dAFyqE6BuMrMh1Za7uhp"
+        );
+        assert_eq!(
+            MainnetEncoder::xpriv_to_base58(&xpriv(&root, 0).unwrap()).unwrap(),
+            "xprv9s21ZrQH143K2srSbCSg4m4kLvPMzcWydgmKEnMmoZUurYuBuYG46c6P71UGXMzmriLzCCBvKQWBUv3vPB3m1SATMhp3uEjXHJ42jFg7myX"
+        );
+        assert_eq!(
+            hex::encode(&hex(&root, 64, 0).unwrap()[..]),
+            "492db4698cf3b73a5a24998aa3e9d7fa96275d85724a91e71aa2d645442f878555d078fd1f1f67e368976f04137b1f7a0d19232136ca50c44614af72b5582a5c"
+        );
+        assert_eq!(
+            password_base64(&root, 21, 0).unwrap().as_str(),
+            "dKLoepugzdVJvdL56ogNV"
+        );
+        assert_eq!(
+            password_base85(&root, 12, 0).unwrap().as_str(),
+            "_s`{TW89)i4`"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use alloc::collections::BTreeMap;
+use core::convert::TryInto;
+
+use crate::{path::DerivationPath, xkeys::Parent, Bip32Error};
+
+/// A cache of intermediate nodes in a derivation tree, keyed by their path
+/// from the root. Deriving many keys under a common prefix (e.g.
+/// `m/84'/0'/0'/0/i`) derives the prefix once, rather than once per key.
+///
+/// ```
+/// use coins_bip32::{cache::DerivationCache, enc::{MainnetEncoder, XKeyEncoder}};
+/// # fn main() -> Result<(), coins_bip32::Bip32Error> {
+/// let xpub = MainnetEncoder::xpub_from_base58("xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y")?;
+/// let mut cache = DerivationCache::new(xpub);
+///
+/// let receive = cache.node("m/0")?.derive_range(0..20)?;
+/// let change = cache.node("m/1")?.derive_range(0..20)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DerivationCache<K> {
+    root: K,
+    nodes: BTreeMap<DerivationPath, K>,
+}
+
+impl<K: Parent> DerivationCache<K> {
+    /// Instantiate an empty cache over a root key
+    pub const fn new(root: K) -> Self {
+        Self {
+            root,
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// The root key
+    pub const fn root(&self) -> &K {
+        &self.root
+    }
+
+    /// The number of cached nodes, excluding the root
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// `true` if no nodes other than the root are cached
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Remove all cached nodes
+    pub fn clear(&mut self) {
+        self.nodes.clear()
+    }
+
+    /// Get the node at `path`, deriving it from its longest cached ancestor.
+    /// The node and every node between it and that ancestor are cached.
+    pub fn node<E, P>(&mut self, path: P) -> Result<&K, Bip32Error>
+    where
+        E: Into<Bip32Error>,
+        P: TryInto<DerivationPath, Error = E>,
+    {
+        let path: DerivationPath = path.try_into().map_err(Into::into)?;
+        if path.is_empty() {
+            return Ok(&self.root);
+        }
+
+        let mut depth = path.len();
+        while depth > 0 && !self.nodes.contains_key(&path.resized(depth, 0)) {
+            depth -= 1;
+        }
+
+        let mut current = match depth {
+            0 => self.root.clone(),
+            _ => self.nodes[&path.resized(depth, 0)].clone(),
+        };
+        for (i, index) in path.iter().enumerate().skip(depth) {
+            current = current.derive_child(*index)?;
+            self.nodes.insert(path.resized(i + 1, 0), current.clone());
+        }
+
+        Ok(&self.nodes[&path])
+    }
+
+    /// Derive the key at `path`. Its ancestors are cached, but the key itself
+    /// is not, so scanning many leaves does not grow the cache.
+    pub fn derive_path<E, P>(&mut self, path: P) -> Result<K, Bip32Error>
+    where
+        E: Into<Bip32Error>,
+        P: TryInto<DerivationPath, Error = E>,
+    {
+        let path: DerivationPath = path.try_into().map_err(Into::into)?;
+        match path.last() {
+            None => Ok(self.root.clone()),
+            Some(index) => {
+                let index = *index;
+                let parent = path.resized(path.len() - 1, 0);
+                self.node(parent)?.derive_child(index)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        derived::{DerivedKey, DerivedXPriv},
+        enc::{MainnetEncoder, XKeyEncoder},
+        path::{harden_index, KeyDerivation},
+        primitives::XKeyInfo,
+        BIP32_HARDEN,
+    };
+
+    const XPRV: &str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+
+    #[test]
+    fn it_caches_prefixes() {
+        let xpriv = MainnetEncoder::xpriv_from_base58(XPRV).unwrap();
+        let root = DerivedXPriv::new(
+            xpriv,
+            KeyDerivation {
+                root: [0u8; 4].into(),
+                path: vec![].into(),
+            },
+        );
+        let mut cache = DerivationCache::new(root.clone());
+        assert!(cache.is_empty());
+
+        for i in 0..5 {
+            let path: DerivationPath =
+                vec![harden_index(84), harden_index(0), harden_index(0), 1, i].into();
+            let cached = cache.derive_path(&path).unwrap();
+            let expected = root.derive_path(&path).unwrap();
+            assert_eq!(cached.verify_key(), expected.verify_key());
+        }
+        // only the 4 ancestors are cached
+        assert_eq!(cache.len(), 4);
+
+        let account = cache.node("m/84'/0'/0'").unwrap().clone();
+        assert_eq!(
+            account.verify_key(),
+            root.derive_path("m/84'/0'/0'").unwrap().verify_key()
+        );
+        assert_eq!(cache.len(), 4);
+
+        cache.node("m/84'/0'/0'/0").unwrap();
+        assert_eq!(cache.len(), 5);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.node("m").unwrap().verify_key(), root.verify_key());
+    }
+
+    #[test]
+    fn it_derives_ranges() {
+        let xpriv = MainnetEncoder::xpriv_from_base58(XPRV).unwrap();
+        let root = DerivedXPriv::new(
+            xpriv,
+            KeyDerivation {
+                root: [0u8; 4].into(),
+                path: vec![].into(),
+            },
+        );
+        let account = root.derive_path("m/84'/0'/0'/0").unwrap().verify_key();
+
+        let children = account.derive_range(10..60).unwrap();
+        assert_eq!(children.len(), 50);
+        for (child, index) in children.iter().zip(10..) {
+            let expected = account.derive_child(index).unwrap();
+            assert_eq!(child, &expected);
+            assert_eq!(child.derivation(), expected.derivation());
+            let info: &XKeyInfo = child.as_ref();
+            let expected_info: &XKeyInfo = expected.as_ref();
+            assert_eq!(info, expected_info);
+            assert_eq!(info.index, index);
+        }
+
+        assert!(account.derive_range(0..0).unwrap().is_empty());
+        assert!(matches!(
+            account.derive_range(0..BIP32_HARDEN + 1),
+            Err(Bip32Error::HardenedDerivationFailed)
+        ));
+    }
+}
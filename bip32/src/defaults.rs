@@ -1,4 +1,5 @@
 use crate::enc::XKeyEncoder;
+use alloc::string::ToString;
 
 /// The default encoder, selected by feature flag
 #[cfg(feature = "mainnet")]
@@ -8,7 +9,7 @@ pub type Encoder = crate::enc::MainnetEncoder;
 #[cfg(feature = "testnet")]
 pub type Encoder = crate::enc::TestnetEncoder;
 
-impl std::str::FromStr for crate::xkeys::XPriv {
+impl core::str::FromStr for crate::xkeys::XPriv {
     type Err = crate::Bip32Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -16,7 +17,7 @@ impl std::str::FromStr for crate::xkeys::XPriv {
     }
 }
 
-impl std::str::FromStr for crate::xkeys::XPub {
+impl core::str::FromStr for crate::xkeys::XPub {
     type Err = crate::Bip32Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
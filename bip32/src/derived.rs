@@ -1,3 +1,4 @@
+use alloc::{vec, vec::Vec};
 use k256::ecdsa;
 
 use coins_core::prelude::{Hash160, Hash160Digest, MarkedDigest, MarkedDigestOutput};
@@ -27,7 +28,8 @@ pub trait DerivedKey {
     /// (which may collide) and checks that `self.path` is a prefix of `other.path`. This may be
     /// deliberately foold by an attacker. For a precise check, use
     /// `DerivedXPriv::is_private_ancestor_of()` or
-    /// `DerivedXPub::is_public_ancestor_of()`
+    /// `DerivedXPub::is_public_ancestor_of()`. For a proof that can be stored
+    /// and checked later, use `ancestry::AncestryProof`
     fn is_possible_ancestor_of<K: DerivedKey>(&self, other: &K) -> bool {
         self.derivation()
             .is_possible_ancestor_of(other.derivation())
@@ -55,6 +57,9 @@ pub struct DerivedXPriv {
 
 inherit_signer!(DerivedXPriv.xpriv);
 
+// The inner `XPriv` zeroizes its key and chain code on drop
+impl zeroize::ZeroizeOnDrop for DerivedXPriv {}
+
 impl AsRef<XPriv> for DerivedXPriv {
     fn as_ref(&self) -> &XPriv {
         &self.xpriv
@@ -91,8 +96,6 @@ impl DerivedXPriv {
     pub fn is_private_ancestor_of(&self, other: &DerivedXPub) -> Result<bool, Bip32Error> {
         if let Some(path) = self.path_to_descendant(other) {
             let descendant = self.derive_path(path)?;
-            dbg!(descendant.verify_key());
-            dbg!(&other);
             Ok(descendant.verify_key() == *other)
         } else {
             Ok(false)
@@ -218,6 +221,21 @@ impl DerivedXPub {
         Self { xpub, derivation }
     }
 
+    /// Derive the unhardened children at each index in `range`. See
+    /// `XPub::derive_range`
+    pub fn derive_range(&self, range: core::ops::Range<u32>) -> Result<Vec<Self>, Bip32Error> {
+        Ok(self
+            .xpub
+            .derive_range(range.clone())?
+            .into_iter()
+            .zip(range)
+            .map(|(xpub, index)| Self {
+                xpub,
+                derivation: self.derivation.extended(index),
+            })
+            .collect())
+    }
+
     /// Check if this XPriv is the private ancestor of some other derived key
     pub fn is_public_ancestor_of(&self, other: &DerivedXPub) -> Result<bool, Bip32Error> {
         if let Some(path) = self.path_to_descendant(other) {
@@ -235,8 +253,8 @@ pub struct DerivedPubkey {
     derivation: KeyDerivation,
 }
 
-impl std::fmt::Debug for DerivedPubkey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for DerivedPubkey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("DerivedPubkey")
             .field("public key", &self.key.to_sec1_bytes())
             .field("key fingerprint", &self.fingerprint())
@@ -0,0 +1,506 @@
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, ops::Range, str::FromStr};
+
+use crate::{
+    derived::{DerivedKey, DerivedPubkey, DerivedXPriv, DerivedXPub},
+    network::Network,
+    path::{encode_index, DerivationPath, KeyDerivation},
+    primitives::{KeyFingerprint, XKeyInfo},
+    xkeys::{Parent, XPriv, XPub},
+    Bip32Error, BIP32_HARDEN,
+};
+
+/// The trailing wildcard of a ranged descriptor key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wildcard {
+    /// The key is not ranged
+    None,
+    /// `/*`: unhardened children
+    Unhardened,
+    /// `/*'` or `/*h`: hardened children. Requires a private key
+    Hardened,
+}
+
+/// A derivation step following the extended key in a descriptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathStep {
+    /// A single index
+    Index(u32),
+    /// A BIP389 multipath step, e.g. `<0;1>`
+    Multipath(Vec<u32>),
+}
+
+/// An extended key that may appear in a descriptor key expression
+pub trait DescriptorXKeyType: Parent + DerivedKey + AsRef<XKeyInfo> {
+    /// `true` if hardened steps may follow the key
+    const HARDENED_STEPS: bool;
+
+    /// Parse the base58 key, attaching the origin. If there is no origin,
+    /// the key is treated as its own root.
+    fn parse_xkey(s: &str, origin: Option<KeyDerivation>) -> Result<(Self, Network), Bip32Error>;
+
+    /// Serialize the key to base58 for the network
+    fn format_xkey(&self, network: &Network) -> Result<String, Bip32Error>;
+
+    /// Get the public key with its derivation
+    fn to_derived_pubkey(&self) -> DerivedPubkey;
+}
+
+impl DescriptorXKeyType for DerivedXPub {
+    const HARDENED_STEPS: bool = false;
+
+    fn parse_xkey(s: &str, origin: Option<KeyDerivation>) -> Result<(Self, Network), Bip32Error> {
+        let detected = XPub::from_str_detect(s)?;
+        let origin = origin.unwrap_or_else(|| KeyDerivation {
+            root: detected.key.fingerprint(),
+            path: vec![].into(),
+        });
+        Ok((DerivedXPub::new(detected.key, origin), detected.network))
+    }
+
+    fn format_xkey(&self, network: &Network) -> Result<String, Bip32Error> {
+        network.xpub_to_base58(self)
+    }
+
+    fn to_derived_pubkey(&self) -> DerivedPubkey {
+        DerivedPubkey::new(AsRef::<XPub>::as_ref(self).key, self.derivation().clone())
+    }
+}
+
+impl DescriptorXKeyType for DerivedXPriv {
+    const HARDENED_STEPS: bool = true;
+
+    fn parse_xkey(s: &str, origin: Option<KeyDerivation>) -> Result<(Self, Network), Bip32Error> {
+        let detected = XPriv::from_str_detect(s)?;
+        let origin = origin.unwrap_or_else(|| KeyDerivation {
+            root: detected.key.fingerprint(),
+            path: vec![].into(),
+        });
+        Ok((DerivedXPriv::new(detected.key, origin), detected.network))
+    }
+
+    fn format_xkey(&self, network: &Network) -> Result<String, Bip32Error> {
+        network.xpriv_to_base58(self)
+    }
+
+    fn to_derived_pubkey(&self) -> DerivedPubkey {
+        self.verify_key().to_derived_pubkey()
+    }
+}
+
+/// A descriptor key expression built on an extended key, e.g.
+/// `[d34db33f/84'/0'/0']xpub.../<0;1>/*`. Supports key origins, BIP389
+/// multipath steps and ranged wildcards.
+#[derive(Debug, Clone)]
+pub struct DescriptorXKey<K> {
+    key: K,
+    network: Network,
+    has_origin: bool,
+    steps: Vec<PathStep>,
+    wildcard: Wildcard,
+}
+
+/// A descriptor key expression over an xpub
+pub type DescriptorXPub = DescriptorXKey<DerivedXPub>;
+
+/// A descriptor key expression over an xpriv
+pub type DescriptorXPriv = DescriptorXKey<DerivedXPriv>;
+
+fn malformatted(s: &str) -> Bip32Error {
+    Bip32Error::MalformattedDescriptor(s.to_owned())
+}
+
+fn parse_step_index(s: &str) -> Result<u32, Bip32Error> {
+    let path: DerivationPath = s.parse()?;
+    match (path.len(), path.last()) {
+        (1, Some(index)) => Ok(*index),
+        _ => Err(Bip32Error::MalformattedDerivation(s.to_owned())),
+    }
+}
+
+fn parse_step(s: &str) -> Result<PathStep, Bip32Error> {
+    if let Some(inner) = s.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+        let indices = inner
+            .split(';')
+            .map(parse_step_index)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut sorted = indices.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if indices.len() < 2 || sorted.len() != indices.len() {
+            return Err(malformatted(s));
+        }
+        Ok(PathStep::Multipath(indices))
+    } else {
+        parse_step_index(s).map(PathStep::Index)
+    }
+}
+
+fn parse_origin(s: &str) -> Result<KeyDerivation, Bip32Error> {
+    let mut parts = s.splitn(2, '/');
+    let fingerprint = parts.next().unwrap_or_default();
+    // `from_str_radix` alone would accept a sign
+    if fingerprint.len() != 8 || !fingerprint.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(malformatted(s));
+    }
+    let root = u32::from_str_radix(fingerprint, 16).map_err(|_| malformatted(s))?;
+    let path = match parts.next() {
+        Some(path) => path.parse()?,
+        None => DerivationPath::default(),
+    };
+    Ok(KeyDerivation {
+        root: KeyFingerprint(root.to_be_bytes()),
+        path,
+    })
+}
+
+impl<K: DescriptorXKeyType> DescriptorXKey<K> {
+    /// Instantiate a descriptor key. The key's derivation is used as its origin
+    pub const fn new(key: K, network: Network, steps: Vec<PathStep>, wildcard: Wildcard) -> Self {
+        Self {
+            key,
+            network,
+            has_origin: true,
+            steps,
+            wildcard,
+        }
+    }
+
+    /// The extended key, with its origin as its derivation
+    pub const fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// The network whose version bytes the key uses
+    pub const fn network(&self) -> &Network {
+        &self.network
+    }
+
+    /// The derivation steps following the key
+    pub fn steps(&self) -> &[PathStep] {
+        &self.steps
+    }
+
+    /// The trailing wildcard
+    pub const fn wildcard(&self) -> Wildcard {
+        self.wildcard
+    }
+
+    /// `true` if the key expression ends in a wildcard
+    pub fn is_ranged(&self) -> bool {
+        self.wildcard != Wildcard::None
+    }
+
+    /// The number of paths described. 1 unless there is a multipath step
+    pub fn multipath_len(&self) -> usize {
+        self.steps
+            .iter()
+            .find_map(|step| match step {
+                PathStep::Multipath(indices) => Some(indices.len()),
+                PathStep::Index(_) => None,
+            })
+            .unwrap_or(1)
+    }
+
+    /// The concrete steps of each path, in multipath order
+    pub fn branch_paths(&self) -> Vec<DerivationPath> {
+        (0..self.multipath_len())
+            .map(|branch| {
+                self.steps
+                    .iter()
+                    .map(|step| match step {
+                        PathStep::Index(index) => *index,
+                        PathStep::Multipath(indices) => indices[branch],
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Split a multipath key expression into one key expression per path
+    pub fn into_single_paths(self) -> Vec<Self> {
+        self.branch_paths()
+            .into_iter()
+            .map(|path| Self {
+                key: self.key.clone(),
+                network: self.network.clone(),
+                has_origin: self.has_origin,
+                steps: path.iter().copied().map(PathStep::Index).collect(),
+                wildcard: self.wildcard,
+            })
+            .collect()
+    }
+
+    /// Derive the concrete public keys for a range of wildcard indices. Keys
+    /// are ordered by multipath branch, then by index. If the expression is
+    /// not ranged, the range is ignored and one key per branch is returned.
+    pub fn derive_pubkeys(&self, range: Range<u32>) -> Result<Vec<DerivedPubkey>, Bip32Error> {
+        let mut keys = vec![];
+        for path in self.branch_paths() {
+            let branch = self.key.derive_path(&path)?;
+            match self.wildcard {
+                Wildcard::None => keys.push(branch.to_derived_pubkey()),
+                Wildcard::Unhardened | Wildcard::Hardened => {
+                    for index in range.clone() {
+                        if index >= BIP32_HARDEN {
+                            return Err(Bip32Error::MalformattedDerivation(index.to_string()));
+                        }
+                        let index = if self.wildcard == Wildcard::Hardened {
+                            index + BIP32_HARDEN
+                        } else {
+                            index
+                        };
+                        keys.push(branch.derive_child(index)?.to_derived_pubkey());
+                    }
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Serialize the key expression
+    pub fn to_descriptor_string(&self) -> Result<String, Bip32Error> {
+        let mut s = String::new();
+        if self.has_origin {
+            let origin = self.key.derivation();
+            let root = format!("{:08x}", u32::from_be_bytes(origin.root.0));
+            s.push('[');
+            s.push_str(&origin.path.custom_string(&root, '/', '\''));
+            s.push(']');
+        }
+        s.push_str(&self.key.format_xkey(&self.network)?);
+        for step in self.steps.iter() {
+            s.push('/');
+            match step {
+                PathStep::Index(index) => s.push_str(&encode_index(*index, '\'')),
+                PathStep::Multipath(indices) => {
+                    let indices: Vec<_> = indices.iter().map(|i| encode_index(*i, '\'')).collect();
+                    s.push('<');
+                    s.push_str(&indices.join(";"));
+                    s.push('>');
+                }
+            }
+        }
+        match self.wildcard {
+            Wildcard::None => {}
+            Wildcard::Unhardened => s.push_str("/*"),
+            Wildcard::Hardened => s.push_str("/*'"),
+        }
+        Ok(s)
+    }
+}
+
+impl<K: DescriptorXKeyType> FromStr for DescriptorXKey<K> {
+    type Err = Bip32Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (origin, rest) = match s.strip_prefix('[') {
+            Some(rest) => {
+                let end = rest.find(']').ok_or_else(|| malformatted(s))?;
+                (Some(parse_origin(&rest[..end])?), &rest[end + 1..])
+            }
+            None => (None, s),
+        };
+        let has_origin = origin.is_some();
+
+        let mut parts = rest.split('/').peekable();
+        let (key, network) = K::parse_xkey(parts.next().unwrap_or_default(), origin)?;
+        // the origin path leads from the root to the key
+        if has_origin && key.derivation().path.len() != key.as_ref().depth as usize {
+            return Err(malformatted(s));
+        }
+
+        let mut steps = vec![];
+        let mut wildcard = Wildcard::None;
+        while let Some(part) = parts.next() {
+            wildcard = match part {
+                "*" => Wildcard::Unhardened,
+                "*'" | "*h" => Wildcard::Hardened,
+                _ => {
+                    steps.push(parse_step(part)?);
+                    continue;
+                }
+            };
+            // the wildcard must be the final step
+            if parts.peek().is_some() {
+                return Err(malformatted(s));
+            }
+        }
+
+        let multipaths = steps
+            .iter()
+            .filter(|step| matches!(step, PathStep::Multipath(_)))
+            .count();
+        if multipaths > 1 {
+            return Err(malformatted(s));
+        }
+
+        let hardened = wildcard == Wildcard::Hardened
+            || steps.iter().any(|step| match step {
+                PathStep::Index(index) => *index >= BIP32_HARDEN,
+                PathStep::Multipath(indices) => indices.iter().any(|i| *i >= BIP32_HARDEN),
+            });
+        if hardened && !K::HARDENED_STEPS {
+            return Err(Bip32Error::HardenedDerivationFailed);
+        }
+
+        Ok(Self {
+            key,
+            network,
+            has_origin,
+            steps,
+            wildcard,
+        })
+    }
+}
+
+impl<K: DescriptorXKeyType> fmt::Display for DescriptorXKey<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = self.to_descriptor_string().map_err(|_| fmt::Error)?;
+        f.write_str(&s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::path::harden_index;
+
+    // BIP32 test vector 1
+    const XPUB: &str = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    const XPRV: &str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+    // m/0H
+    const CHILD_XPRV: &str = "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7";
+    // m/84H/0H/0H
+    const ACCOUNT_XPUB: &str = "xpub6C1HVMz946r433QEjZGpYYWYcspxXXBPys5PBGkmQboRXE6RLfFiStEkKbWKCZaPgDrzZh9nUEunxuiuy6MNdw23du2Ek7GoKYMJVH8eK5E";
+    // m/48H/0H/0H/2H
+    const MULTISIG_XPUB: &str = "xpub6E64WfdQwBGz85XhbZryr9gUGUPBgoSu5WV6tJWpzAvgAmpVpdPHkT3XYm9R5J6MeWzvLQoz4q845taC9Q28XutbptxAmg7q8QPkjvTL4oi";
+
+    #[test]
+    fn it_round_trips_key_expressions() {
+        let cases = [
+            XPUB.to_owned(),
+            format!("{}/1/2", XPUB),
+            format!("[d34db33f/84'/0'/0']{}/0/*", ACCOUNT_XPUB),
+            format!("[d34db33f]{}/<0;1>/*", XPUB),
+            format!("[d34db33f/48'/0'/0'/2']{}/<0;1;7>/3", MULTISIG_XPUB),
+        ];
+        for case in cases.iter() {
+            let key: DescriptorXPub = case.parse().unwrap();
+            assert_eq!(&key.to_string(), case);
+        }
+
+        let cases = [
+            format!("{}/0'/*'", XPRV),
+            format!("[3442193e/0']{}/<0';1'>/*", CHILD_XPRV),
+        ];
+        for case in cases.iter() {
+            let key: DescriptorXPriv = case.parse().unwrap();
+            assert_eq!(&key.to_string(), case);
+        }
+
+        // `h` hardening is accepted and normalized
+        let key: DescriptorXPub = format!("[d34db33f/84h/0h/0h]{}/0/*", ACCOUNT_XPUB)
+            .parse()
+            .unwrap();
+        assert_eq!(
+            key.to_string(),
+            format!("[d34db33f/84'/0'/0']{}/0/*", ACCOUNT_XPUB)
+        );
+    }
+
+    #[test]
+    fn it_rejects_malformatted_key_expressions() {
+        let cases = [
+            format!("[d34db33]{}", XPUB),
+            format!("[d34db33f{}", XPUB),
+            format!("[nothexxx]{}", XPUB),
+            format!("[+d34db33]{}", XPUB),
+            format!("[-d34db33]{}", XPUB),
+            // the origin path must match the key depth
+            format!("[d34db33f/84'/0'/0']{}/0/*", XPUB),
+            format!("[d34db33f]{}/0/*", ACCOUNT_XPUB),
+            format!("[d34db33f/84'/0']{}/0/*", ACCOUNT_XPUB),
+            format!("{}/*/0", XPUB),
+            format!("{}/<0>/*", XPUB),
+            format!("{}/<0;0>/*", XPUB),
+            format!("{}/<0;1>/<2;3>/*", XPUB),
+            format!("{}/toast", XPUB),
+            format!("{}/*'", XPUB),
+            format!("{}/0'/*", XPUB),
+            "xpubnope/0/*".to_owned(),
+        ];
+        for case in cases.iter() {
+            assert!(case.parse::<DescriptorXPub>().is_err(), "{}", case);
+        }
+    }
+
+    #[test]
+    fn it_expands_wildcards_and_multipaths() {
+        let key: DescriptorXPub = format!("[d34db33f/84'/0'/0']{}/<0;1>/*", ACCOUNT_XPUB)
+            .parse()
+            .unwrap();
+        assert_eq!(key.multipath_len(), 2);
+        let xpub = XPub::from_str_detect(ACCOUNT_XPUB).unwrap().key;
+
+        let pubkeys = key.derive_pubkeys(5..8).unwrap();
+        assert_eq!(pubkeys.len(), 6);
+        for (i, pubkey) in pubkeys.iter().enumerate() {
+            let branch = (i / 3) as u32;
+            let index = 5 + (i % 3) as u32;
+            let expected = xpub.derive_path(vec![branch, index]).unwrap();
+            assert_eq!(pubkey.to_sec1_bytes(), expected.to_sec1_bytes());
+
+            let derivation = pubkey.derivation();
+            assert_eq!(derivation.root, KeyFingerprint([0xd3, 0x4d, 0xb3, 0x3f]));
+            assert_eq!(
+                derivation.path,
+                vec![
+                    harden_index(84),
+                    harden_index(0),
+                    harden_index(0),
+                    branch,
+                    index
+                ]
+                .into()
+            );
+        }
+
+        let single = key.into_single_paths();
+        assert_eq!(single.len(), 2);
+        assert_eq!(
+            single[1].to_string(),
+            format!("[d34db33f/84'/0'/0']{}/1/*", ACCOUNT_XPUB)
+        );
+    }
+
+    #[test]
+    fn it_expands_private_key_expressions() {
+        // BIP32 test vector 1, chain m/0H/1
+        let key: DescriptorXPriv = format!("{}/0'/*", XPRV).parse().unwrap();
+        let pubkeys = key.derive_pubkeys(1..2).unwrap();
+        assert_eq!(pubkeys.len(), 1);
+        assert_eq!(
+            pubkeys[0].to_sec1_bytes().to_vec(),
+            hex::decode("03501e454bf00751f24b1b489aa925215d66af2234e3891c3b21a52bedb3cd711c")
+                .unwrap()
+        );
+        assert_eq!(
+            pubkeys[0].derivation(),
+            &KeyDerivation {
+                root: KeyFingerprint([0x34, 0x42, 0x19, 0x3e]),
+                path: vec![harden_index(0), 1].into(),
+            }
+        );
+
+        // unranged keys ignore the range
+        let key: DescriptorXPub = format!("{}/<0;1>", XPUB).parse().unwrap();
+        assert_eq!(key.derive_pubkeys(0..100).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,447 @@
+use std::{
+    collections::HashSet,
+    iter::FromIterator,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use async_trait::async_trait;
+use coins_core::hashes::{Digest, Hash160};
+use k256::ecdsa;
+
+use crate::{
+    bip44::{Bip44Path, Chain, Purpose},
+    derived::{DerivedKey, DerivedPubkey, DerivedXPriv, DerivedXPub},
+    primitives::{Hint, XKeyInfo},
+    xkeys::Parent,
+    Bip32Error, BIP32_HARDEN,
+};
+
+/// The BIP44 default gap limit
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// Build the single-key output script for a pubkey. `Legacy` is P2PKH,
+/// `Compatibility` is P2SH-P2WPKH, `SegWit` is P2WPKH and `Taproot` is a
+/// BIP86 P2TR key-path output. Multisig hints have no single-key script.
+pub fn script_pubkey(key: &ecdsa::VerifyingKey, hint: Hint) -> Result<Vec<u8>, Bip32Error> {
+    let key_hash = Hash160::digest(key.to_sec1_bytes());
+    let script = match hint {
+        Hint::Legacy => [&[0x76, 0xa9, 0x14][..], &key_hash, &[0x88, 0xac]].concat(),
+        Hint::SegWit => [&[0x00, 0x14][..], &key_hash].concat(),
+        Hint::Compatibility => {
+            let redeem_script = [&[0x00, 0x14][..], &key_hash].concat();
+            let script_hash = Hash160::digest(&redeem_script);
+            [&[0xa9, 0x14][..], &script_hash, &[0x87]].concat()
+        }
+        Hint::Taproot => {
+            let output_key = crate::schnorr::tweak_pubkey(&crate::schnorr::x_only(key), None)?.0;
+            [&[0x51, 0x20][..], &output_key.to_bytes()].concat()
+        }
+        Hint::CompatibilityMultisig | Hint::SegWitMultisig => {
+            return Err(Bip32Error::NoSingleKeyScript(hint))
+        }
+    };
+    Ok(script)
+}
+
+/// An address that account discovery asks the oracle about
+#[derive(Debug)]
+pub struct AddressQuery {
+    /// The unhardened account index
+    pub account: u32,
+    /// The chain
+    pub chain: Chain,
+    /// The address index
+    pub index: u32,
+    /// The output type hint used to build the script
+    pub hint: Hint,
+    /// The pubkey at the address, with its derivation
+    pub pubkey: DerivedPubkey,
+    /// The output script of the address
+    pub script_pubkey: Vec<u8>,
+}
+
+/// A source of truth about which addresses have been used, typically backed
+/// by a chain indexer.
+///
+/// Oracles must be `Sync` and return `Send` futures, so that discovery can be
+/// spawned on a multi-threaded runtime. On wasm32, where HTTP clients are
+/// usually `!Send`, the futures need not be `Send`.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait UsageOracle {
+    /// The error returned by the oracle
+    type Error: std::error::Error;
+
+    /// Return true if the address has ever received funds
+    async fn is_used(&self, query: &AddressQuery) -> Result<bool, Self::Error>;
+}
+
+/// An error during account discovery
+#[derive(Debug, thiserror::Error)]
+pub enum DiscoveryError<E: std::error::Error> {
+    /// Key derivation failed
+    #[error(transparent)]
+    Bip32Error(#[from] Bip32Error),
+
+    /// The oracle failed
+    #[error("Usage oracle error: {0}")]
+    OracleError(E),
+}
+
+/// An in-memory oracle over a set of used output scripts
+#[derive(Debug, Default)]
+pub struct InMemoryOracle {
+    used: HashSet<Vec<u8>>,
+    queries: AtomicUsize,
+}
+
+impl InMemoryOracle {
+    /// Instantiate an empty oracle
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Mark an output script as used
+    pub fn mark_used(&mut self, script_pubkey: impl Into<Vec<u8>>) {
+        self.used.insert(script_pubkey.into());
+    }
+
+    /// The number of queries answered so far
+    pub fn queries(&self) -> usize {
+        self.queries.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Into<Vec<u8>>> FromIterator<T> for InMemoryOracle {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            used: iter.into_iter().map(Into::into).collect(),
+            queries: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl UsageOracle for InMemoryOracle {
+    type Error = std::convert::Infallible;
+
+    async fn is_used(&self, query: &AddressQuery) -> Result<bool, Self::Error> {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        Ok(self.used.contains(&query.script_pubkey))
+    }
+}
+
+/// An account found by discovery
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredAccount {
+    /// The unhardened account index
+    pub account: u32,
+    /// The account xpub
+    pub xpub: DerivedXPub,
+    /// The highest used index on the external chain
+    pub last_external: Option<u32>,
+    /// The highest used index on the internal chain
+    pub last_internal: Option<u32>,
+}
+
+impl DiscoveredAccount {
+    /// True if any address in the account has been used
+    pub const fn is_used(&self) -> bool {
+        self.last_external.is_some() || self.last_internal.is_some()
+    }
+
+    /// The highest used index on a chain
+    pub const fn last_used(&self, chain: Chain) -> Option<u32> {
+        match chain {
+            Chain::External => self.last_external,
+            Chain::Internal => self.last_internal,
+        }
+    }
+
+    /// The first index after the highest used index on a chain
+    pub fn next_unused(&self, chain: Chain) -> u32 {
+        self.last_used(chain).map_or(0, |i| i + 1)
+    }
+}
+
+/// BIP44 account discovery. Accounts are scanned in order, and each chain is
+/// scanned until `gap_limit` consecutive addresses are unused. Discovery
+/// stops at the first account with no used external addresses.
+///
+/// The purpose and output script type follow the hint of the key being
+/// scanned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountDiscovery {
+    /// The unhardened SLIP-44 coin type
+    pub coin_type: u32,
+    /// The number of consecutive unused addresses that ends a chain
+    pub gap_limit: u32,
+    /// The maximum number of accounts to scan
+    pub max_accounts: u32,
+}
+
+impl AccountDiscovery {
+    /// Instantiate discovery for a coin type with the default gap limit
+    pub const fn new(coin_type: u32) -> Self {
+        Self {
+            coin_type,
+            gap_limit: DEFAULT_GAP_LIMIT,
+            max_accounts: BIP32_HARDEN,
+        }
+    }
+
+    /// Set the gap limit
+    pub const fn gap_limit(mut self, gap_limit: u32) -> Self {
+        self.gap_limit = gap_limit;
+        self
+    }
+
+    /// Set the maximum number of accounts to scan
+    pub const fn max_accounts(mut self, max_accounts: u32) -> Self {
+        self.max_accounts = max_accounts;
+        self
+    }
+
+    /// Discover the used accounts under a root key. The account path is
+    /// chosen from the root's hint, e.g. `m/84'/coin'/account'` for
+    /// `Hint::SegWit`.
+    pub async fn discover<O: UsageOracle>(
+        &self,
+        root: &DerivedXPriv,
+        oracle: &O,
+    ) -> Result<Vec<DiscoveredAccount>, DiscoveryError<O::Error>> {
+        let hint = AsRef::<XKeyInfo>::as_ref(root).hint;
+        let purpose = Purpose::from_hint(hint).ok_or(Bip32Error::NoSingleKeyScript(hint))?;
+
+        let mut accounts = vec![];
+        for account in 0..self.max_accounts {
+            let path = Bip44Path::new(purpose, self.coin_type)
+                .account(account)
+                .to_path()?;
+            let xpub = root.derive_path(&path)?.verify_key();
+            let discovered = self.scan_account(&xpub, oracle).await?;
+            if discovered.last_external.is_none() {
+                break;
+            }
+            accounts.push(discovered);
+        }
+        Ok(accounts)
+    }
+
+    /// Scan both chains of a single account xpub. This needs no private key,
+    /// and can be used by watch-only wallets.
+    pub async fn scan_account<O: UsageOracle>(
+        &self,
+        xpub: &DerivedXPub,
+        oracle: &O,
+    ) -> Result<DiscoveredAccount, DiscoveryError<O::Error>> {
+        let account = AsRef::<XKeyInfo>::as_ref(xpub).index & !BIP32_HARDEN;
+        let last_external = self
+            .scan_chain(xpub, account, Chain::External, oracle)
+            .await?;
+        let last_internal = match last_external {
+            Some(_) => {
+                self.scan_chain(xpub, account, Chain::Internal, oracle)
+                    .await?
+            }
+            None => None,
+        };
+        Ok(DiscoveredAccount {
+            account,
+            xpub: xpub.clone(),
+            last_external,
+            last_internal,
+        })
+    }
+
+    /// Scan one chain of an account until the gap limit, returning the
+    /// highest used index
+    async fn scan_chain<O: UsageOracle>(
+        &self,
+        xpub: &DerivedXPub,
+        account: u32,
+        chain: Chain,
+        oracle: &O,
+    ) -> Result<Option<u32>, DiscoveryError<O::Error>> {
+        let hint = AsRef::<XKeyInfo>::as_ref(xpub).hint;
+        let chain_xpub = xpub.derive_child(chain.index())?;
+
+        let mut last_used = None;
+        let mut gap = 0;
+        let mut index = 0;
+        while gap < self.gap_limit && index < BIP32_HARDEN {
+            let child = chain_xpub.derive_child(index)?;
+            let key: &ecdsa::VerifyingKey = child.as_ref();
+            let query = AddressQuery {
+                account,
+                chain,
+                index,
+                hint,
+                script_pubkey: script_pubkey(key, hint)?,
+                pubkey: DerivedPubkey::new(*key, child.derivation().clone()),
+            };
+            if oracle
+                .is_used(&query)
+                .await
+                .map_err(DiscoveryError::OracleError)?
+            {
+                last_used = Some(index);
+                gap = 0;
+            } else {
+                gap += 1;
+            }
+            index += 1;
+        }
+        Ok(last_used)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::path::DerivationPath;
+
+    fn root(hint: Hint) -> DerivedXPriv {
+        DerivedXPriv::root_from_seed(&[7u8; 32], Some(hint)).unwrap()
+    }
+
+    fn script_at(root: &DerivedXPriv, path: &str) -> Vec<u8> {
+        let path: DerivationPath = path.parse().unwrap();
+        let child = root.derive_path(&path).unwrap();
+        let hint = AsRef::<XKeyInfo>::as_ref(&child).hint;
+        script_pubkey(child.verify_key().as_ref(), hint).unwrap()
+    }
+
+    #[test]
+    fn it_builds_scripts() {
+        let key = root(Hint::Legacy).verify_key();
+        let key: &ecdsa::VerifyingKey = key.as_ref();
+        let hash = Hash160::digest(key.to_sec1_bytes());
+
+        let p2pkh = script_pubkey(key, Hint::Legacy).unwrap();
+        assert_eq!(p2pkh.len(), 25);
+        assert_eq!(&p2pkh[3..23], &hash[..]);
+
+        let p2wpkh = script_pubkey(key, Hint::SegWit).unwrap();
+        assert_eq!(p2wpkh, [&[0x00, 0x14][..], &hash].concat());
+
+        let p2sh = script_pubkey(key, Hint::Compatibility).unwrap();
+        assert_eq!(&p2sh[..2], &[0xa9, 0x14]);
+        assert_eq!(p2sh[22], 0x87);
+
+        let p2tr = script_pubkey(key, Hint::Taproot).unwrap();
+        assert_eq!(&p2tr[..2], &[0x51, 0x20]);
+
+        assert!(matches!(
+            script_pubkey(key, Hint::SegWitMultisig),
+            Err(Bip32Error::NoSingleKeyScript(Hint::SegWitMultisig))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_discovers_segwit_accounts() {
+        let root = root(Hint::SegWit);
+        let oracle: InMemoryOracle = [
+            "m/84'/0'/0'/0/0",
+            "m/84'/0'/0'/0/5",
+            "m/84'/0'/0'/1/2",
+            "m/84'/0'/1'/0/19",
+            // beyond the gap after index 19
+            "m/84'/0'/1'/0/40",
+            // account 2 is unused, so account 3 is never scanned
+            "m/84'/0'/3'/0/0",
+            // wrong purpose
+            "m/44'/0'/2'/0/0",
+        ]
+        .iter()
+        .map(|path| script_at(&root, path))
+        .collect();
+
+        let accounts = AccountDiscovery::new(0)
+            .discover(&root, &oracle)
+            .await
+            .unwrap();
+        assert_eq!(accounts.len(), 2);
+
+        assert_eq!(accounts[0].account, 0);
+        assert_eq!(accounts[0].last_external, Some(5));
+        assert_eq!(accounts[0].last_internal, Some(2));
+        assert_eq!(accounts[0].next_unused(Chain::External), 6);
+        assert_eq!(
+            accounts[0].xpub.derivation().path,
+            "m/84'/0'/0'".parse().unwrap()
+        );
+
+        assert_eq!(accounts[1].account, 1);
+        assert_eq!(accounts[1].last_external, Some(19));
+        assert_eq!(accounts[1].last_internal, None);
+        assert_eq!(accounts[1].next_unused(Chain::Internal), 0);
+    }
+
+    #[tokio::test]
+    async fn it_discovers_legacy_accounts() {
+        let root = root(Hint::Legacy);
+        let oracle: InMemoryOracle = ["m/44'/2'/0'/1/0", "m/44'/2'/0'/0/2"]
+            .iter()
+            .map(|path| script_at(&root, path))
+            .collect();
+
+        let accounts = AccountDiscovery::new(2)
+            .gap_limit(3)
+            .discover(&root, &oracle)
+            .await
+            .unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].last_external, Some(2));
+        assert_eq!(accounts[0].last_internal, Some(0));
+
+        // account 0: 3 + 3 external, 1 + 3 internal. account 1: 3 external
+        assert_eq!(oracle.queries(), 13);
+    }
+
+    #[tokio::test]
+    async fn it_scans_watch_only_accounts() {
+        let root = root(Hint::SegWit);
+        let xpub = root.derive_path("m/84'/1'/4'").unwrap().verify_key();
+        let mut oracle = InMemoryOracle::new();
+        oracle.mark_used(script_at(&root, "m/84'/1'/4'/1/7"));
+
+        let account = AccountDiscovery::new(1)
+            .scan_account(&xpub, &oracle)
+            .await
+            .unwrap();
+        assert_eq!(account.account, 4);
+        assert!(!account.is_used());
+        assert_eq!(oracle.queries(), DEFAULT_GAP_LIMIT as usize);
+
+        oracle.mark_used(script_at(&root, "m/84'/1'/4'/0/0"));
+        let account = AccountDiscovery::new(1)
+            .scan_account(&xpub, &oracle)
+            .await
+            .unwrap();
+        assert_eq!(account.last_internal, Some(7));
+    }
+
+    #[test]
+    fn it_discovers_in_send_futures() {
+        fn assert_send<T: Send>(_: &T) {}
+        let root = root(Hint::SegWit);
+        let oracle = InMemoryOracle::new();
+        let discovery = AccountDiscovery::new(0);
+        assert_send(&discovery.discover(&root, &oracle));
+        assert_send(&discovery.scan_account(&root.verify_key(), &oracle));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_multisig_roots() {
+        let root = root(Hint::SegWitMultisig);
+        let result = AccountDiscovery::new(0)
+            .discover(&root, &InMemoryOracle::new())
+            .await;
+        assert!(matches!(
+            result,
+            Err(DiscoveryError::Bip32Error(Bip32Error::NoSingleKeyScript(_)))
+        ));
+    }
+}
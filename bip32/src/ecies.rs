@@ -0,0 +1,393 @@
+use std::io::Read;
+
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305,
+};
+use coins_core::ser::ByteFormat;
+use hkdf::Hkdf;
+use k256::ecdsa;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::{
+    derived::{DerivedKey, DerivedXPriv},
+    path::KeyDerivation,
+    wif::Privkey,
+    xkeys::{Parent, XPriv},
+    Bip32Error,
+};
+
+/// The serialization version of `EciesCiphertext`
+pub const ECIES_VERSION: u8 = 1;
+/// The HKDF info prefix for ECIES keys. The cipher id is appended
+pub const ECIES_INFO: &[u8] = b"coins-bip32/ecies/v1";
+
+const NONCE_LENGTH: usize = 12;
+
+/// A raw ECDH shared secret: the x coordinate of the shared point. Zeroized
+/// on drop.
+pub type SharedSecret = Zeroizing<[u8; 32]>;
+
+fn encryption_error(msg: impl Into<String>) -> Bip32Error {
+    Bip32Error::EncryptionError(msg.into())
+}
+
+/// Compute the raw ECDH shared secret between a private and a public key
+pub fn shared_secret(secret: &ecdsa::SigningKey, public: &ecdsa::VerifyingKey) -> SharedSecret {
+    let shared = k256::ecdh::diffie_hellman(secret.as_nonzero_scalar(), public.as_affine());
+    let mut out = Zeroizing::new([0u8; 32]);
+    out.copy_from_slice(shared.raw_secret_bytes());
+    out
+}
+
+/// Compute an ECDH shared secret and expand it with HKDF-SHA256 into a
+/// 32-byte key
+pub fn ecdh_key(
+    secret: &ecdsa::SigningKey,
+    public: &ecdsa::VerifyingKey,
+    salt: &[u8],
+    info: &[u8],
+) -> Zeroizing<[u8; 32]> {
+    let shared = shared_secret(secret, public);
+    let mut okm = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(salt), &shared[..])
+        .expand(info, &mut okm[..])
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    okm
+}
+
+/// The AEAD used by ECIES and keystores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Cipher {
+    /// AES-256-GCM
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    /// ChaCha20-Poly1305
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+impl Cipher {
+    /// The cipher id byte used in serialization and key derivation
+    pub const fn id(&self) -> u8 {
+        match self {
+            Cipher::Aes256Gcm => 1,
+            Cipher::ChaCha20Poly1305 => 2,
+        }
+    }
+
+    /// The cipher with some id byte, if any
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Cipher::Aes256Gcm),
+            2 => Some(Cipher::ChaCha20Poly1305),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn seal(
+        &self,
+        key: &[u8; 32],
+        nonce: &[u8],
+        payload: Payload<'_, '_>,
+    ) -> Result<Vec<u8>, Bip32Error> {
+        let result = match self {
+            Cipher::Aes256Gcm => Aes256Gcm::new(key.into()).encrypt(nonce.into(), payload),
+            Cipher::ChaCha20Poly1305 => {
+                ChaCha20Poly1305::new(key.into()).encrypt(nonce.into(), payload)
+            }
+        };
+        result.map_err(|_| encryption_error("encryption failed"))
+    }
+
+    pub(crate) fn open(
+        &self,
+        key: &[u8; 32],
+        nonce: &[u8],
+        payload: Payload<'_, '_>,
+    ) -> Result<Vec<u8>, Bip32Error> {
+        let result = match self {
+            Cipher::Aes256Gcm => Aes256Gcm::new(key.into()).decrypt(nonce.into(), payload),
+            Cipher::ChaCha20Poly1305 => {
+                ChaCha20Poly1305::new(key.into()).decrypt(nonce.into(), payload)
+            }
+        };
+        result.map_err(|_| encryption_error("decryption failed: wrong key or tampered ciphertext"))
+    }
+}
+
+/// An ECIES ciphertext. It records the key origin of the recipient key, so
+/// that the holder of an ancestor key knows which child key to derive.
+///
+/// Serialized as `version || cipher || depth || fingerprint || path ||
+/// ephemeral_pubkey || nonce || ciphertext`. Everything before the
+/// ciphertext is authenticated as associated data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EciesCiphertext {
+    /// The AEAD used
+    pub cipher: Cipher,
+    /// The key origin of the recipient key
+    pub recipient: KeyDerivation,
+    /// The sender's ephemeral public key
+    pub ephemeral: ecdsa::VerifyingKey,
+    /// The AEAD nonce
+    pub nonce: [u8; NONCE_LENGTH],
+    /// The AEAD ciphertext, including its tag
+    pub ciphertext: Vec<u8>,
+}
+
+impl EciesCiphertext {
+    /// Encrypt a message to a derived public key, recording its derivation
+    pub fn encrypt<K>(recipient: &K, cipher: Cipher, plaintext: &[u8]) -> Result<Self, Bip32Error>
+    where
+        K: DerivedKey + AsRef<ecdsa::VerifyingKey>,
+    {
+        let ephemeral = ecdsa::SigningKey::random(&mut OsRng);
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut nonce);
+
+        let mut result = Self {
+            cipher,
+            recipient: recipient.derivation().clone(),
+            ephemeral: *ephemeral.verifying_key(),
+            nonce,
+            ciphertext: vec![],
+        };
+        let key = result.key(
+            &shared_secret(&ephemeral, recipient.as_ref()),
+            recipient.as_ref(),
+        );
+        let header = result.header()?;
+        result.ciphertext = cipher.seal(
+            &key,
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )?;
+        Ok(result)
+    }
+
+    /// Decrypt with the recipient key itself
+    pub fn decrypt_with_key(
+        &self,
+        key: &ecdsa::SigningKey,
+    ) -> Result<Zeroizing<Vec<u8>>, Bip32Error> {
+        let aead_key = self.key(&shared_secret(key, &self.ephemeral), key.verifying_key());
+        let header = self.header()?;
+        self.cipher
+            .open(
+                &aead_key,
+                &self.nonce,
+                Payload {
+                    msg: &self.ciphertext,
+                    aad: &header,
+                },
+            )
+            .map(Zeroizing::new)
+    }
+
+    /// Decrypt with the recipient key or any of its private ancestors. The
+    /// recipient key is derived along the recorded key origin.
+    pub fn decrypt(&self, key: &DerivedXPriv) -> Result<Zeroizing<Vec<u8>>, Bip32Error> {
+        if !key.derivation().is_possible_ancestor_of(&self.recipient) {
+            return Err(encryption_error(
+                "key is not an ancestor of the recipient key",
+            ));
+        }
+        let path = key
+            .derivation()
+            .path_to_descendant(&self.recipient)
+            .ok_or_else(|| encryption_error("key is not an ancestor of the recipient key"))?;
+        let child = key.derive_path(path)?;
+        self.decrypt_with_key(child.as_ref())
+    }
+
+    /// The AEAD key. Both public keys are bound by the HKDF salt, and the
+    /// cipher by the HKDF info.
+    fn key(&self, shared: &SharedSecret, recipient: &ecdsa::VerifyingKey) -> Zeroizing<[u8; 32]> {
+        let salt = [self.ephemeral.to_sec1_bytes(), recipient.to_sec1_bytes()].concat();
+        let info = [ECIES_INFO, &[self.cipher.id()]].concat();
+        let mut okm = Zeroizing::new([0u8; 32]);
+        Hkdf::<Sha256>::new(Some(&salt), &shared[..])
+            .expand(&info, &mut okm[..])
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        okm
+    }
+
+    /// The authenticated header
+    fn header(&self) -> Result<Vec<u8>, Bip32Error> {
+        if self.recipient.path.len() > KeyDerivation::MAX_DEPTH {
+            return Err(Bip32Error::InvalidBip32Path);
+        }
+        let mut header = vec![
+            ECIES_VERSION,
+            self.cipher.id(),
+            self.recipient.path.len() as u8,
+        ];
+        self.recipient.write_to(&mut header)?;
+        header.extend_from_slice(&self.ephemeral.to_sec1_bytes());
+        header.extend_from_slice(&self.nonce);
+        Ok(header)
+    }
+
+    /// Serialize the ciphertext
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Bip32Error> {
+        let mut buf = self.header()?;
+        buf.extend_from_slice(&self.ciphertext);
+        Ok(buf)
+    }
+
+    /// Deserialize a ciphertext
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, Bip32Error> {
+        let mut reader = buf;
+        let mut prefix = [0u8; 3];
+        reader.read_exact(&mut prefix)?;
+        if prefix[0] != ECIES_VERSION {
+            return Err(encryption_error(format!("unknown version {}", prefix[0])));
+        }
+        let cipher = Cipher::from_id(prefix[1])
+            .ok_or_else(|| encryption_error(format!("unknown cipher {}", prefix[1])))?;
+        let recipient = KeyDerivation::read_with_length(&mut reader, 4 + 4 * prefix[2] as usize)?;
+
+        let mut ephemeral = [0u8; 33];
+        reader.read_exact(&mut ephemeral)?;
+        let ephemeral = ecdsa::VerifyingKey::from_sec1_bytes(&ephemeral)?;
+        let mut nonce = [0u8; NONCE_LENGTH];
+        reader.read_exact(&mut nonce)?;
+
+        Ok(Self {
+            cipher,
+            recipient,
+            ephemeral,
+            nonce,
+            ciphertext: reader.to_vec(),
+        })
+    }
+}
+
+macro_rules! inherit_ecdh {
+    ($struct_name:ident) => {
+        impl $struct_name {
+            /// Compute the raw ECDH shared secret with a public key
+            pub fn shared_secret<K: AsRef<ecdsa::VerifyingKey>>(&self, public: &K) -> SharedSecret {
+                shared_secret(self.as_ref(), public.as_ref())
+            }
+
+            /// Compute an ECDH shared secret with a public key, and expand it
+            /// with HKDF-SHA256 into a 32-byte key
+            pub fn ecdh_key<K: AsRef<ecdsa::VerifyingKey>>(
+                &self,
+                public: &K,
+                salt: &[u8],
+                info: &[u8],
+            ) -> Zeroizing<[u8; 32]> {
+                ecdh_key(self.as_ref(), public.as_ref(), salt, info)
+            }
+        }
+    };
+}
+
+inherit_ecdh!(XPriv);
+inherit_ecdh!(DerivedXPriv);
+inherit_ecdh!(Privkey);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        derived::{DerivedPubkey, DerivedXPub},
+        primitives::Hint,
+    };
+
+    fn root(seed: u8) -> DerivedXPriv {
+        DerivedXPriv::root_from_seed(&[seed; 32], Some(Hint::SegWit)).unwrap()
+    }
+
+    #[test]
+    fn it_agrees_on_shared_secrets() {
+        let alice = root(1).derive_path("m/0'/1").unwrap();
+        let bob = root(2).derive_path("m/7").unwrap();
+        let bob_pub = bob.verify_key();
+        let bob_pubkey = DerivedPubkey::new(*bob_pub.as_ref(), bob_pub.derivation().clone());
+
+        assert_eq!(
+            alice.shared_secret(&bob_pubkey),
+            bob.shared_secret(&alice.verify_key())
+        );
+        assert_eq!(
+            alice.ecdh_key(&bob_pub, b"salt", b"info"),
+            bob.ecdh_key(&alice.verify_key(), b"salt", b"info")
+        );
+        assert_ne!(
+            alice.ecdh_key(&bob_pub, b"salt", b"info"),
+            alice.ecdh_key(&bob_pub, b"salt", b"other")
+        );
+        assert_eq!(
+            Privkey::from(&alice).shared_secret(&bob_pub),
+            AsRef::<XPriv>::as_ref(&alice).shared_secret(&bob_pub)
+        );
+    }
+
+    #[test]
+    fn it_round_trips_ciphertexts() {
+        let root = root(3);
+        let recipient: DerivedXPub = root.derive_path("m/13'/0/4").unwrap().verify_key();
+
+        for cipher in [Cipher::Aes256Gcm, Cipher::ChaCha20Poly1305].iter() {
+            let ct = EciesCiphertext::encrypt(&recipient, *cipher, b"attack at dawn").unwrap();
+            assert_eq!(&ct.recipient, recipient.derivation());
+
+            let parsed = EciesCiphertext::from_bytes(&ct.to_bytes().unwrap()).unwrap();
+            assert_eq!(parsed, ct);
+
+            // the root, an intermediate ancestor, and the key itself can decrypt
+            assert_eq!(&parsed.decrypt(&root).unwrap()[..], b"attack at dawn");
+            let account = root.derive_path("m/13'").unwrap();
+            assert_eq!(&parsed.decrypt(&account).unwrap()[..], b"attack at dawn");
+            let key = root.derive_path("m/13'/0/4").unwrap();
+            assert_eq!(
+                &parsed.decrypt_with_key(key.as_ref()).unwrap()[..],
+                b"attack at dawn"
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_wrong_keys_and_tampering() {
+        let root = root(4);
+        let recipient = root.derive_path("m/1/2").unwrap().verify_key();
+        let ct = EciesCiphertext::encrypt(&recipient, Cipher::ChaCha20Poly1305, b"hello").unwrap();
+
+        let sibling = root.derive_path("m/1/3").unwrap();
+        assert!(ct.decrypt_with_key(sibling.as_ref()).is_err());
+        assert!(ct.decrypt(&sibling).is_err());
+
+        // the derivation is authenticated
+        let mut redirected = ct.clone();
+        redirected.recipient = sibling.derivation().clone();
+        assert!(redirected.decrypt(&root).is_err());
+
+        let mut bytes = ct.to_bytes().unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(EciesCiphertext::from_bytes(&bytes)
+            .unwrap()
+            .decrypt(&root)
+            .is_err());
+
+        let mut bytes = ct.to_bytes().unwrap();
+        bytes[1] = Cipher::Aes256Gcm.id();
+        assert!(EciesCiphertext::from_bytes(&bytes)
+            .unwrap()
+            .decrypt(&root)
+            .is_err());
+
+        bytes[0] = 9;
+        assert!(EciesCiphertext::from_bytes(&bytes).is_err());
+    }
+}
@@ -1,6 +1,7 @@
+use alloc::{string::String, vec, vec::Vec};
 use coins_core::hashes::{Digest, Hash256};
+use core::marker::PhantomData;
 use k256::ecdsa;
-use std::marker::PhantomData;
 
 use crate::{
     primitives::{ChainCode, Hint, KeyFingerprint, XKeyInfo},
@@ -47,12 +48,73 @@ pub trait NetworkParams {
     const BIP49_PRIV_VERSION: u32;
     /// The Bip84 pubkey version bytes
     const BIP84_PRIV_VERSION: u32;
+    /// The SLIP-132 multisig P2SH-P2WSH privkey version bytes. Defaults to
+    /// the Bip32 version bytes
+    const BIP49_MULTISIG_PRIV_VERSION: u32 = Self::PRIV_VERSION;
+    /// The SLIP-132 multisig P2WSH privkey version bytes. Defaults to
+    /// the Bip32 version bytes
+    const BIP84_MULTISIG_PRIV_VERSION: u32 = Self::PRIV_VERSION;
+    /// The Bip86 privkey version bytes. Bip86 reuses the Bip32 version bytes
+    const BIP86_PRIV_VERSION: u32 = Self::PRIV_VERSION;
     /// The Bip32 pubkey version bytes
     const PUB_VERSION: u32;
     /// The Bip49 pubkey version bytes
     const BIP49_PUB_VERSION: u32;
     /// The Bip84 pubkey version bytes
     const BIP84_PUB_VERSION: u32;
+    /// The SLIP-132 multisig P2SH-P2WSH pubkey version bytes. Defaults to
+    /// the Bip32 version bytes
+    const BIP49_MULTISIG_PUB_VERSION: u32 = Self::PUB_VERSION;
+    /// The SLIP-132 multisig P2WSH pubkey version bytes. Defaults to
+    /// the Bip32 version bytes
+    const BIP84_MULTISIG_PUB_VERSION: u32 = Self::PUB_VERSION;
+    /// The Bip86 pubkey version bytes. Bip86 reuses the Bip32 version bytes
+    const BIP86_PUB_VERSION: u32 = Self::PUB_VERSION;
+    /// The WIF private key version byte. Defaults to the Bitcoin mainnet
+    /// version byte
+    const WIF_VERSION: u8 = 0x80;
+
+    /// The privkey version bytes for a hint
+    fn priv_version(hint: Hint) -> u32 {
+        match hint {
+            Hint::Legacy => Self::PRIV_VERSION,
+            Hint::Compatibility => Self::BIP49_PRIV_VERSION,
+            Hint::SegWit => Self::BIP84_PRIV_VERSION,
+            Hint::Taproot => Self::BIP86_PRIV_VERSION,
+            Hint::CompatibilityMultisig => Self::BIP49_MULTISIG_PRIV_VERSION,
+            Hint::SegWitMultisig => Self::BIP84_MULTISIG_PRIV_VERSION,
+        }
+    }
+
+    /// The pubkey version bytes for a hint
+    fn pub_version(hint: Hint) -> u32 {
+        match hint {
+            Hint::Legacy => Self::PUB_VERSION,
+            Hint::Compatibility => Self::BIP49_PUB_VERSION,
+            Hint::SegWit => Self::BIP84_PUB_VERSION,
+            Hint::Taproot => Self::BIP86_PUB_VERSION,
+            Hint::CompatibilityMultisig => Self::BIP49_MULTISIG_PUB_VERSION,
+            Hint::SegWitMultisig => Self::BIP84_MULTISIG_PUB_VERSION,
+        }
+    }
+
+    /// The hint registered for some privkey version bytes, if any. Bytes
+    /// shared by several hints resolve in the order of `Hint::ALL`
+    fn priv_hint(version: u32) -> Option<Hint> {
+        Hint::ALL
+            .iter()
+            .copied()
+            .find(|hint| Self::priv_version(*hint) == version)
+    }
+
+    /// The hint registered for some pubkey version bytes, if any. Bytes
+    /// shared by several hints resolve in the order of `Hint::ALL`
+    fn pub_hint(version: u32) -> Option<Hint> {
+        Hint::ALL
+            .iter()
+            .copied()
+            .find(|hint| Self::pub_version(*hint) == version)
+    }
 }
 
 params!(
@@ -61,9 +123,14 @@ params!(
         bip32: 0x0488_ADE4,
         bip49: 0x049d_7878,
         bip84: 0x04b2_430c,
+        bip49_multisig: 0x0295_b005,
+        bip84_multisig: 0x02aa_7a99,
         bip32_pub: 0x0488_B21E,
         bip49_pub: 0x049d_7cb2,
-        bip84_pub: 0x04b2_4746
+        bip84_pub: 0x04b2_4746,
+        bip49_multisig_pub: 0x0295_b43f,
+        bip84_multisig_pub: 0x02aa_7ed3,
+        wif: 0x80
     }
 );
 
@@ -73,9 +140,14 @@ params!(
         bip32: 0x0435_8394,
         bip49: 0x044a_4e28,
         bip84: 0x045f_18bc,
+        bip49_multisig: 0x0242_85b5,
+        bip84_multisig: 0x0257_5048,
         bip32_pub: 0x0435_87CF,
         bip49_pub: 0x044a_5262,
-        bip84_pub: 0x045f_1cf6
+        bip84_pub: 0x045f_1cf6,
+        bip49_multisig_pub: 0x0242_89ef,
+        bip84_multisig_pub: 0x0257_5483,
+        wif: 0xef
     }
 );
 
@@ -94,7 +166,7 @@ pub trait XKeyEncoder {
     fn write_key_details<K, W>(writer: &mut W, key: &K) -> Result<usize, Bip32Error>
     where
         K: AsRef<XKeyInfo>,
-        W: std::io::Write,
+        W: coins_core::io::Write,
     {
         let key = key.as_ref();
         let mut written = writer.write(&[key.depth])?;
@@ -104,22 +176,22 @@ pub trait XKeyEncoder {
         Ok(written)
     }
 
-    /// Serialize the xpub to `std::io::Write`
+    /// Serialize the xpub to `coins_core::io::Write`
     fn write_xpub<W, K>(writer: &mut W, key: &K) -> Result<usize, Bip32Error>
     where
-        W: std::io::Write,
+        W: coins_core::io::Write,
         K: AsRef<XPub>;
 
-    /// Serialize the xpriv to `std::io::Write`
+    /// Serialize the xpriv to `coins_core::io::Write`
     fn write_xpriv<W, K>(writer: &mut W, key: &K) -> Result<usize, Bip32Error>
     where
-        W: std::io::Write,
+        W: coins_core::io::Write,
         K: AsRef<XPriv>;
 
     #[doc(hidden)]
     fn read_depth<R>(reader: &mut R) -> Result<u8, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let mut buf = [0u8; 1];
         reader.read_exact(&mut buf)?;
@@ -129,7 +201,7 @@ pub trait XKeyEncoder {
     #[doc(hidden)]
     fn read_parent<R>(reader: &mut R) -> Result<KeyFingerprint, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
@@ -139,7 +211,7 @@ pub trait XKeyEncoder {
     #[doc(hidden)]
     fn read_index<R>(reader: &mut R) -> Result<u32, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
@@ -149,7 +221,7 @@ pub trait XKeyEncoder {
     #[doc(hidden)]
     fn read_chain_code<R>(reader: &mut R) -> Result<ChainCode, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let mut buf = [0u8; 32];
         reader.read_exact(&mut buf)?;
@@ -159,7 +231,7 @@ pub trait XKeyEncoder {
     #[doc(hidden)]
     fn read_xpriv_body<R>(reader: &mut R, hint: Hint) -> Result<XPriv, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let depth = Self::read_depth(reader)?;
         let parent = Self::read_parent(reader)?;
@@ -193,7 +265,7 @@ pub trait XKeyEncoder {
     // network.
     fn read_xpriv_without_network<R>(reader: &mut R) -> Result<XPriv, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
@@ -201,7 +273,7 @@ pub trait XKeyEncoder {
         Self::read_xpriv_body(reader, Hint::Legacy)
     }
 
-    /// Attempt to instantiate an `XPriv` from a `std::io::Read`
+    /// Attempt to instantiate an `XPriv` from a `coins_core::io::Read`
     ///
     /// ```
     /// use coins_bip32::{Bip32Error, xkeys::XPriv, enc::{XKeyEncoder, MainnetEncoder}};
@@ -214,12 +286,12 @@ pub trait XKeyEncoder {
     /// ```
     fn read_xpriv<R>(reader: &mut R) -> Result<XPriv, Bip32Error>
     where
-        R: std::io::Read;
+        R: coins_core::io::Read;
 
     #[doc(hidden)]
     fn read_xpub_body<R>(reader: &mut R, hint: Hint) -> Result<XPub, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let depth = Self::read_depth(reader)?;
         let parent = Self::read_parent(reader)?;
@@ -247,7 +319,7 @@ pub trait XKeyEncoder {
     // network.
     fn read_xpub_without_network<R>(reader: &mut R) -> Result<XPub, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
@@ -255,7 +327,7 @@ pub trait XKeyEncoder {
         Self::read_xpub_body(reader, Hint::Legacy)
     }
 
-    /// Attempt to instantiate an `XPub` from a `std::io::Read`
+    /// Attempt to instantiate an `XPub` from a `coins_core::io::Read`
     ///
     /// ```
     /// use coins_bip32::{Bip32Error, xkeys::XPub, enc::{XKeyEncoder, MainnetEncoder}};
@@ -268,7 +340,7 @@ pub trait XKeyEncoder {
     /// ```
     fn read_xpub<R>(reader: &mut R) -> Result<XPub, Bip32Error>
     where
-        R: std::io::Read;
+        R: coins_core::io::Read;
 
     /// Serialize an XPriv to base58
     fn xpriv_to_base58<K>(k: &K) -> Result<String, Bip32Error>
@@ -326,34 +398,26 @@ where {
 }
 
 impl<P: NetworkParams> XKeyEncoder for BitcoinEncoder<P> {
-    /// Serialize the xpub to `std::io::Write`
+    /// Serialize the xpub to `coins_core::io::Write`
     fn write_xpub<W, K>(writer: &mut W, key: &K) -> Result<usize, Bip32Error>
     where
-        W: std::io::Write,
+        W: coins_core::io::Write,
         K: AsRef<XPub>,
     {
-        let version = match key.as_ref().xkey_info.hint {
-            Hint::Legacy => P::PUB_VERSION,
-            Hint::Compatibility => P::BIP49_PUB_VERSION,
-            Hint::SegWit => P::BIP84_PUB_VERSION,
-        };
+        let version = P::pub_version(key.as_ref().xkey_info.hint);
         let mut written = writer.write(&version.to_be_bytes())?;
         written += Self::write_key_details(writer, key.as_ref())?;
         written += writer.write(key.as_ref().key.to_sec1_bytes().as_ref())?;
         Ok(written)
     }
 
-    /// Serialize the xpriv to `std::io::Write`
+    /// Serialize the xpriv to `coins_core::io::Write`
     fn write_xpriv<W, K>(writer: &mut W, key: &K) -> Result<usize, Bip32Error>
     where
-        W: std::io::Write,
+        W: coins_core::io::Write,
         K: AsRef<XPriv>,
     {
-        let version = match key.as_ref().xkey_info.hint {
-            Hint::Legacy => P::PRIV_VERSION,
-            Hint::Compatibility => P::BIP49_PRIV_VERSION,
-            Hint::SegWit => P::BIP84_PRIV_VERSION,
-        };
+        let version = P::priv_version(key.as_ref().xkey_info.hint);
         let mut written = writer.write(&version.to_be_bytes())?;
         written += Self::write_key_details(writer, key.as_ref())?;
         written += writer.write(&[0])?;
@@ -363,43 +427,175 @@ impl<P: NetworkParams> XKeyEncoder for BitcoinEncoder<P> {
 
     fn read_xpriv<R>(reader: &mut R) -> Result<XPriv, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
         let version_bytes = u32::from_be_bytes(buf);
 
-        // Can't use associated constants in matches :()
-        let hint = if version_bytes == P::PRIV_VERSION {
-            Hint::Legacy
-        } else if version_bytes == P::BIP49_PRIV_VERSION {
-            Hint::Compatibility
-        } else if version_bytes == P::BIP84_PRIV_VERSION {
-            Hint::SegWit
-        } else {
-            return Err(Bip32Error::BadXPrivVersionBytes(buf));
-        };
+        let hint = P::priv_hint(version_bytes).ok_or(Bip32Error::BadXPrivVersionBytes(buf))?;
         Self::read_xpriv_body(reader, hint)
     }
 
     fn read_xpub<R>(reader: &mut R) -> Result<XPub, Bip32Error>
     where
-        R: std::io::Read,
+        R: coins_core::io::Read,
     {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
         let version_bytes = u32::from_be_bytes(buf);
 
-        // Can't use associated constants in matches :()
-        let hint = if version_bytes == P::PUB_VERSION {
-            Hint::Legacy
-        } else if version_bytes == P::BIP49_PUB_VERSION {
-            Hint::Compatibility
-        } else if version_bytes == P::BIP84_PUB_VERSION {
-            Hint::SegWit
-        } else {
-            return Err(Bip32Error::BadXPrivVersionBytes(buf));
-        };
+        let hint = P::pub_hint(version_bytes).ok_or(Bip32Error::BadXPubVersionBytes(buf))?;
         Self::read_xpub_body(reader, hint)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const XPUB: &str = "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y";
+    const XPRV: &str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+
+    #[test]
+    fn it_round_trips_slip132_multisig_keys() {
+        let mainnet_pubs = [
+            ("Ypub6e6v9EAfES4ohxw54o2j3mMcYDRA65h9RoPknwmLNPTh3Ri3283FZLjaWkWmFoA1DutB97oQiDQCgHNgZifmAJBf3CmGaRyRZpPEfbVa724", Hint::CompatibilityMultisig),
+            ("Zpub6xwBStqaP7cHZG8Bu9pMFrT7iBZc2hgeLuuyaLfDkPqa6XXGGnCpBQPiXxUMFhovdYzytbPyAskkZZzFHR5mxXsFuYThALnuqYSt49f87xb", Hint::SegWitMultisig),
+        ];
+        let testnet_pubs = [
+            ("Upub5LmrvZUzdhttJnAbjMtEDQybrLqNKbj9mMJsfNBnrMxAq2T81VP15672RvgRGAYKbMQx9DRAsZz198vRgw1hyMTFZqyaEnhUUv8f7Gz2HCW", Hint::CompatibilityMultisig),
+            ("Vpub5fc8EE9unPSNA5MiZifrRW572JypGDiegTq6Sm5gENL3t8GMG9YZh9mAT8e1G5CEzzXkth1jLELZ2RXzQdRimb8rSBfzphWxkeCJVsrYzWD", Hint::SegWitMultisig),
+        ];
+        let mainnet_privs = [
+            ("YprvANkMzkodih9AKGoi75b8yBgRAzVm6H9JGAfvV5uVdfogeNzy4nwEYvEGXfQzUiCb2NVk8YVMSDXJ6Ct4NLwLQmFYbDzUJ12XDSEnATR3W3g", Hint::CompatibilityMultisig),
+            ("ZprvAhadJRUYsNgeAZzpwSNmBGmvLxeD2u8oBHC9GUoP1gBZhUpCKT6oAytQYsNaUcrWS1cYt25utssqyVVd63MMCzw9TZgtsur1VAJRYzwAEqZ", Hint::SegWitMultisig),
+        ];
+        let testnet_privs = [
+            ("Uprv95RJn67y7xyEv63EmeSe8qJQV7uyKoBJbib3MWKx7eJARyk44AGz4fbiSqaeV5auPp2X8e77ba76Z4RoVZHHDpX97sCmxMka8XzCc9fe9K9", Hint::CompatibilityMultisig),
+            ("Vprv16YtLrHXxePM6QH2pP6gJegXe6NyWYhkJkS5emb9diQdXPdfDxwD42xU9Bd6xrfuNz9CGdoZgVDQD26VwcBzwg23TFNCX6dsjQCrgPBnUZm", Hint::SegWitMultisig),
+        ];
+
+        let xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+        for (s, hint) in mainnet_pubs.iter() {
+            let key = MainnetEncoder::xpub_from_base58(s).unwrap();
+            assert_eq!(key.xkey_info.hint, *hint);
+            assert_eq!(key, xpub);
+            assert_eq!(&MainnetEncoder::xpub_to_base58(&key).unwrap(), s);
+            assert!(matches!(
+                TestnetEncoder::xpub_from_base58(s),
+                Err(Bip32Error::BadXPubVersionBytes(_))
+            ));
+        }
+        for (s, hint) in testnet_pubs.iter() {
+            let key = TestnetEncoder::xpub_from_base58(s).unwrap();
+            assert_eq!(key.xkey_info.hint, *hint);
+            assert_eq!(key, xpub);
+            assert_eq!(&TestnetEncoder::xpub_to_base58(&key).unwrap(), s);
+        }
+
+        let xpriv = MainnetEncoder::xpriv_from_base58(XPRV).unwrap();
+        for (s, hint) in mainnet_privs.iter() {
+            let key = MainnetEncoder::xpriv_from_base58(s).unwrap();
+            assert_eq!(key.xkey_info.hint, *hint);
+            assert_eq!(key, xpriv);
+            assert_eq!(&MainnetEncoder::xpriv_to_base58(&key).unwrap(), s);
+            assert!(matches!(
+                TestnetEncoder::xpriv_from_base58(s),
+                Err(Bip32Error::BadXPrivVersionBytes(_))
+            ));
+        }
+        for (s, hint) in testnet_privs.iter() {
+            let key = TestnetEncoder::xpriv_from_base58(s).unwrap();
+            assert_eq!(key.xkey_info.hint, *hint);
+            assert_eq!(key, xpriv);
+            assert_eq!(&TestnetEncoder::xpriv_to_base58(&key).unwrap(), s);
+        }
+    }
+
+    #[test]
+    fn it_round_trips_every_hint() {
+        let xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+        let xpriv = MainnetEncoder::xpriv_from_base58(XPRV).unwrap();
+
+        for hint in Hint::ALL.iter().filter(|h| **h != Hint::Taproot) {
+            let mut key = xpub;
+            key.xkey_info.hint = *hint;
+            let s = MainnetEncoder::xpub_to_base58(&key).unwrap();
+            let parsed = MainnetEncoder::xpub_from_base58(&s).unwrap();
+            assert_eq!(parsed.xkey_info.hint, *hint);
+
+            let mut key = xpriv.clone();
+            key.xkey_info.hint = *hint;
+            let s = TestnetEncoder::xpriv_to_base58(&key).unwrap();
+            let parsed = TestnetEncoder::xpriv_from_base58(&s).unwrap();
+            assert_eq!(parsed.xkey_info.hint, *hint);
+        }
+    }
+
+    #[test]
+    fn it_encodes_taproot_keys_with_bip32_version_bytes() {
+        let mut xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+        xpub.xkey_info.hint = Hint::Taproot;
+        assert_eq!(MainnetEncoder::xpub_to_base58(&xpub).unwrap(), XPUB);
+        assert_eq!(
+            MainnetEncoder::xpub_from_base58(XPUB)
+                .unwrap()
+                .xkey_info
+                .hint,
+            Hint::Legacy
+        );
+
+        // Networks that register distinct BIP86 version bytes round-trip the hint
+        #[derive(Debug)]
+        struct Distinct;
+        impl NetworkParams for Distinct {
+            const PRIV_VERSION: u32 = Main::PRIV_VERSION;
+            const BIP49_PRIV_VERSION: u32 = Main::BIP49_PRIV_VERSION;
+            const BIP84_PRIV_VERSION: u32 = Main::BIP84_PRIV_VERSION;
+            const BIP49_MULTISIG_PRIV_VERSION: u32 = Main::BIP49_MULTISIG_PRIV_VERSION;
+            const BIP84_MULTISIG_PRIV_VERSION: u32 = Main::BIP84_MULTISIG_PRIV_VERSION;
+            const BIP86_PRIV_VERSION: u32 = 0x0123_4567;
+            const PUB_VERSION: u32 = Main::PUB_VERSION;
+            const BIP49_PUB_VERSION: u32 = Main::BIP49_PUB_VERSION;
+            const BIP84_PUB_VERSION: u32 = Main::BIP84_PUB_VERSION;
+            const BIP49_MULTISIG_PUB_VERSION: u32 = Main::BIP49_MULTISIG_PUB_VERSION;
+            const BIP84_MULTISIG_PUB_VERSION: u32 = Main::BIP84_MULTISIG_PUB_VERSION;
+            const BIP86_PUB_VERSION: u32 = 0x0123_4568;
+            const WIF_VERSION: u8 = Main::WIF_VERSION;
+        }
+        type DistinctEncoder = BitcoinEncoder<Distinct>;
+
+        let s = DistinctEncoder::xpub_to_base58(&xpub).unwrap();
+        let parsed = DistinctEncoder::xpub_from_base58(&s).unwrap();
+        assert_eq!(parsed.xkey_info.hint, Hint::Taproot);
+        assert_eq!(parsed, xpub);
+    }
+
+    #[test]
+    fn it_defaults_newer_version_bytes() {
+        // Params written before the multisig hints and WIF still compile, and
+        // fall back to the Bip32 and mainnet version bytes
+        #[derive(Debug)]
+        struct Minimal;
+        impl NetworkParams for Minimal {
+            const PRIV_VERSION: u32 = Main::PRIV_VERSION;
+            const BIP49_PRIV_VERSION: u32 = Main::BIP49_PRIV_VERSION;
+            const BIP84_PRIV_VERSION: u32 = Main::BIP84_PRIV_VERSION;
+            const PUB_VERSION: u32 = Main::PUB_VERSION;
+            const BIP49_PUB_VERSION: u32 = Main::BIP49_PUB_VERSION;
+            const BIP84_PUB_VERSION: u32 = Main::BIP84_PUB_VERSION;
+        }
+
+        assert_eq!(Minimal::WIF_VERSION, Main::WIF_VERSION);
+        assert_eq!(
+            Minimal::pub_version(Hint::SegWitMultisig),
+            Main::PUB_VERSION
+        );
+        assert_eq!(
+            Minimal::priv_version(Hint::CompatibilityMultisig),
+            Main::PRIV_VERSION
+        );
+        assert_eq!(Minimal::pub_hint(Main::PUB_VERSION), Some(Hint::Legacy));
+    }
+}
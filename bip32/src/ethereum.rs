@@ -0,0 +1,374 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use k256::ecdsa::{self, RecoveryId};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    bip44::{Bip44Path, Chain, Purpose, ETHER},
+    derived::{DerivedPubkey, DerivedXPriv, DerivedXPub},
+    path::DerivationPath,
+    wif::Privkey,
+    xkeys::{Parent, XPriv, XPub},
+    Bip32Error,
+};
+
+/// The SLIP-44 coin type for ether
+pub const ETH_COIN_TYPE: u32 = ETHER.index;
+
+/// The prefix committed to by EIP-191 personal messages
+pub const PERSONAL_MESSAGE_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n";
+
+fn malformatted(msg: impl Into<String>) -> Bip32Error {
+    Bip32Error::MalformattedEthereum(msg.into())
+}
+
+/// Compute the keccak256 hash of some data
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// The keccak digest of an EIP-191 personal message,
+/// `keccak256("\x19Ethereum Signed Message:\n" || len || message)`
+pub fn personal_message_digest(message: &[u8]) -> Keccak256 {
+    let mut digest = Keccak256::new();
+    digest.update(PERSONAL_MESSAGE_PREFIX);
+    digest.update(message.len().to_string());
+    digest.update(message);
+    digest
+}
+
+/// The keccak digest of EIP-712 typed data,
+/// `keccak256("\x19\x01" || domainSeparator || hashStruct(message))`
+pub fn typed_data_digest(domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> Keccak256 {
+    let mut digest = Keccak256::new();
+    digest.update([0x19, 0x01]);
+    digest.update(domain_separator);
+    digest.update(struct_hash);
+    digest
+}
+
+/// The path of the `index`th account under the standard BIP44 ether path,
+/// `m/44'/60'/0'/0/{index}`
+pub fn account_path(index: u32) -> Result<DerivationPath, Bip32Error> {
+    Bip44Path::new(Purpose::Bip44, ETH_COIN_TYPE)
+        .chain(Chain::External)
+        .index(index)
+        .to_path()
+}
+
+/// Derive the `index`th account key at `m/44'/60'/0'/0/{index}` from a root
+pub fn derive_account<K: Parent>(root: &K, index: u32) -> Result<K, Bip32Error> {
+    root.derive_path(account_path(index)?)
+}
+
+/// A 20-byte Ethereum address. Displayed with an EIP-55 checksum.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    /// The address of a pubkey: the last 20 bytes of the keccak256 hash of
+    /// the uncompressed key
+    pub fn from_pubkey(key: &ecdsa::VerifyingKey) -> Self {
+        let point = key.to_encoded_point(false);
+        let hash = keccak256(&point.as_bytes()[1..]);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash[12..]);
+        Self(address)
+    }
+
+    /// The EIP-55 mixed-case checksum encoding, with a `0x` prefix
+    pub fn to_checksum(&self) -> String {
+        let lower = hex::encode(self.0);
+        let hash = keccak256(lower.as_bytes());
+        let mut checksummed = String::with_capacity(42);
+        checksummed.push_str("0x");
+        for (i, c) in lower.chars().enumerate() {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            match nibble >= 8 {
+                true => checksummed.push(c.to_ascii_uppercase()),
+                false => checksummed.push(c),
+            }
+        }
+        checksummed
+    }
+}
+
+impl core::fmt::Debug for Address {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Address").field(&self.to_checksum()).finish()
+    }
+}
+
+impl core::fmt::Display for Address {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+impl core::str::FromStr for Address {
+    type Err = Bip32Error;
+
+    /// Parse a hex address. All-lowercase and all-uppercase addresses are
+    /// accepted as-is, mixed-case addresses must have a valid EIP-55 checksum.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        let mut address = [0u8; 20];
+        hex::decode_to_slice(digits, &mut address).map_err(|e| malformatted(e.to_string()))?;
+        let address = Self(address);
+
+        let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+            && digits.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && address.to_checksum()[2..] != *digits {
+            return Err(malformatted(format!("bad EIP-55 checksum in {}", s)));
+        }
+        Ok(address)
+    }
+}
+
+/// A recoverable signature, serialized as `r || s || v` with `v = 27 + recid`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoverableSignature {
+    /// The signature
+    pub signature: ecdsa::Signature,
+    /// The recovery id
+    pub recovery_id: RecoveryId,
+}
+
+impl RecoverableSignature {
+    /// The `v` value, `27 + recid`
+    pub const fn v(&self) -> u8 {
+        27 + self.recovery_id.to_byte()
+    }
+
+    /// Serialize as `r || s || v`
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut buf = [0u8; 65];
+        buf[..64].copy_from_slice(&self.signature.to_bytes());
+        buf[64] = self.v();
+        buf
+    }
+
+    /// Deserialize from `r || s || v`. Accepts `v` as either `recid` or
+    /// `27 + recid`
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, Bip32Error> {
+        if buf.len() != 65 {
+            return Err(malformatted(format!(
+                "expected a 65-byte signature, got {} bytes",
+                buf.len()
+            )));
+        }
+        let v = match buf[64] {
+            v @ 0..=1 => v,
+            v @ 27..=28 => v - 27,
+            v => return Err(malformatted(format!("unsupported v value {}", v))),
+        };
+        Ok(Self {
+            signature: ecdsa::Signature::from_slice(&buf[..64])?,
+            recovery_id: RecoveryId::from_byte(v).expect("v is 0 or 1"),
+        })
+    }
+
+    /// Recover the address that signed some digest
+    pub fn recover(&self, digest: Keccak256) -> Result<Address, Bip32Error> {
+        let key =
+            ecdsa::VerifyingKey::recover_from_digest(digest, &self.signature, self.recovery_id)?;
+        Ok(Address::from_pubkey(&key))
+    }
+
+    /// Recover the address that signed an EIP-191 personal message
+    pub fn recover_personal_message(&self, message: &[u8]) -> Result<Address, Bip32Error> {
+        self.recover(personal_message_digest(message))
+    }
+
+    /// Recover the address that signed EIP-712 typed data
+    pub fn recover_typed_data(
+        &self,
+        domain_separator: &[u8; 32],
+        struct_hash: &[u8; 32],
+    ) -> Result<Address, Bip32Error> {
+        self.recover(typed_data_digest(domain_separator, struct_hash))
+    }
+}
+
+macro_rules! inherit_eth_signer {
+    ($struct_name:ident) => {
+        impl $struct_name {
+            /// The Ethereum address of this key
+            pub fn eth_address(&self) -> Address {
+                Address::from_pubkey(AsRef::<ecdsa::SigningKey>::as_ref(self).verifying_key())
+            }
+
+            /// Sign an EIP-191 personal message
+            pub fn sign_personal_message(
+                &self,
+                message: &[u8],
+            ) -> Result<RecoverableSignature, Bip32Error> {
+                let (signature, recovery_id) =
+                    self.sign_digest_recoverable(personal_message_digest(message))?;
+                Ok(RecoverableSignature {
+                    signature,
+                    recovery_id,
+                })
+            }
+
+            /// Sign EIP-712 typed data, given its domain separator and the
+            /// hash of its message struct
+            pub fn sign_typed_data(
+                &self,
+                domain_separator: &[u8; 32],
+                struct_hash: &[u8; 32],
+            ) -> Result<RecoverableSignature, Bip32Error> {
+                let (signature, recovery_id) =
+                    self.sign_digest_recoverable(typed_data_digest(domain_separator, struct_hash))?;
+                Ok(RecoverableSignature {
+                    signature,
+                    recovery_id,
+                })
+            }
+        }
+    };
+}
+
+macro_rules! inherit_eth_verifier {
+    ($struct_name:ident) => {
+        impl $struct_name {
+            /// The Ethereum address of this key
+            pub fn eth_address(&self) -> Address {
+                Address::from_pubkey(AsRef::<ecdsa::VerifyingKey>::as_ref(self))
+            }
+        }
+    };
+}
+
+inherit_eth_signer!(XPriv);
+inherit_eth_signer!(DerivedXPriv);
+inherit_eth_signer!(Privkey);
+inherit_eth_verifier!(XPub);
+inherit_eth_verifier!(DerivedXPub);
+inherit_eth_verifier!(DerivedPubkey);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn it_checksums_addresses() {
+        // https://eips.ethereum.org/EIPS/eip-55
+        let cases = [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ];
+        for case in cases.iter() {
+            let address = Address::from_str(case).unwrap();
+            assert_eq!(address.to_checksum(), *case);
+            assert_eq!(Address::from_str(&case.to_lowercase()).unwrap(), address);
+            assert_eq!(
+                Address::from_str(&case[2..].to_uppercase()).unwrap(),
+                address
+            );
+        }
+        assert!(matches!(
+            Address::from_str("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(Bip32Error::MalformattedEthereum(_))
+        ));
+        assert!(Address::from_str("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+    }
+
+    #[test]
+    fn it_signs_personal_messages() {
+        // web3.js eth.accounts.sign documentation example
+        let key =
+            Privkey::from_hex("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318")
+                .unwrap();
+        let address = key.eth_address();
+        assert_eq!(
+            address.to_string(),
+            "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+        assert_eq!(
+            hex::encode(personal_message_digest(b"Some data").finalize()),
+            "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+        );
+
+        let signature = key.sign_personal_message(b"Some data").unwrap();
+        assert_eq!(
+            hex::encode(signature.to_bytes()),
+            "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c"
+        );
+        assert_eq!(
+            RecoverableSignature::from_bytes(&signature.to_bytes()).unwrap(),
+            signature
+        );
+        assert_eq!(
+            signature.recover_personal_message(b"Some data").unwrap(),
+            address
+        );
+        assert_ne!(
+            signature.recover_personal_message(b"Other data").unwrap(),
+            address
+        );
+    }
+
+    #[test]
+    fn it_signs_typed_data() {
+        // https://eips.ethereum.org/EIPS/eip-712 example "Mail" message
+        let key = Privkey::from_bytes(&keccak256(b"cow")).unwrap();
+        assert_eq!(
+            key.eth_address().to_string(),
+            "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+        );
+        let mut domain_separator = [0u8; 32];
+        hex::decode_to_slice(
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f",
+            &mut domain_separator,
+        )
+        .unwrap();
+        let mut struct_hash = [0u8; 32];
+        hex::decode_to_slice(
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e",
+            &mut struct_hash,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(typed_data_digest(&domain_separator, &struct_hash).finalize()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+
+        let signature = key
+            .sign_typed_data(&domain_separator, &struct_hash)
+            .unwrap();
+        assert_eq!(
+            hex::encode(signature.to_bytes()),
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c"
+        );
+        assert_eq!(
+            signature
+                .recover_typed_data(&domain_separator, &struct_hash)
+                .unwrap(),
+            key.eth_address()
+        );
+    }
+
+    #[test]
+    fn it_derives_bip44_accounts() {
+        // the seed of "abandon abandon ... about" without a password
+        let seed = hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").unwrap();
+        let root = XPriv::root_from_seed(&seed, None).unwrap();
+        assert_eq!(
+            account_path(0).unwrap(),
+            DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap()
+        );
+
+        let account = derive_account(&root, 0).unwrap();
+        assert_eq!(
+            account.eth_address().to_string(),
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94"
+        );
+        assert_eq!(account.verify_key().eth_address(), account.eth_address());
+    }
+}
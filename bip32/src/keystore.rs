@@ -0,0 +1,566 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::Payload;
+use rand::{rngs::OsRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    derived::{DerivedKey, DerivedXPriv},
+    ecies::Cipher,
+    path::KeyDerivation,
+    primitives::{ChainCode, Hint, KeyFingerprint, XKeyInfo},
+    xkeys::XPriv,
+    Bip32Error,
+};
+
+/// The current keystore format version
+pub const KEYSTORE_VERSION: u32 = 1;
+
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+// depth || parent || index || chain code || key
+const PLAINTEXT_LENGTH: usize = 1 + 4 + 4 + 32 + 32;
+
+// Refuse to run a KDF more expensive than this. This bounds the memory and
+// work a malicious keystore file can demand, in bytes allocated and bytes
+// processed. The defaults use 128 MiB and 64 MiB.
+const MAX_KDF_MEMORY: u128 = 1 << 31;
+const MAX_KDF_WORK: u128 = 1 << 34;
+const MAX_ARGON2_LANES: u32 = 16;
+
+fn keystore_error(msg: impl Into<String>) -> Bip32Error {
+    Bip32Error::EncryptionError(msg.into())
+}
+
+mod hex_bytes {
+    pub(super) fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: &str = serde::Deserialize::deserialize(deserializer)?;
+        hex::decode(s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The password-based key derivation function and its cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", rename_all = "lowercase")]
+pub enum Kdf {
+    /// scrypt with `N = 2^log_n`
+    Scrypt {
+        /// The log2 of the CPU/memory cost
+        log_n: u8,
+        /// The block size
+        r: u32,
+        /// The parallelism
+        p: u32,
+    },
+    /// Argon2id, version 0x13
+    Argon2id {
+        /// The memory cost in KiB
+        m_cost: u32,
+        /// The number of passes
+        t_cost: u32,
+        /// The parallelism
+        p_cost: u32,
+    },
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Self::scrypt()
+    }
+}
+
+impl Kdf {
+    /// scrypt with `N = 2^17, r = 8, p = 1`
+    pub const fn scrypt() -> Self {
+        Kdf::Scrypt {
+            log_n: 17,
+            r: 8,
+            p: 1,
+        }
+    }
+
+    /// Argon2id with 64 MiB of memory, 3 passes and 1 lane
+    pub const fn argon2id() -> Self {
+        Kdf::Argon2id {
+            m_cost: 64 * 1024,
+            t_cost: 3,
+            p_cost: 1,
+        }
+    }
+
+    fn check_cost(&self) -> Result<(), Bip32Error> {
+        let (memory, work, lanes_ok) = match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                let n = 1u128.checked_shl(log_n.into()).unwrap_or(u128::MAX);
+                let memory = n.saturating_mul(128 * u128::from(r));
+                // each of the p lanes fills the array, then reads it back
+                (memory, memory.saturating_mul(2 * u128::from(p)), true)
+            }
+            Kdf::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => {
+                let memory = 1024 * u128::from(m_cost);
+                (
+                    memory,
+                    memory * u128::from(t_cost),
+                    p_cost <= MAX_ARGON2_LANES,
+                )
+            }
+        };
+        match memory <= MAX_KDF_MEMORY && work <= MAX_KDF_WORK && lanes_ok {
+            true => Ok(()),
+            false => Err(keystore_error("KDF cost exceeds the allowed maximum")),
+        }
+    }
+
+    /// Derive a 32-byte key from a password and salt
+    fn derive(&self, password: &[u8], salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, Bip32Error> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, 32)
+                    .map_err(|e| keystore_error(format!("bad scrypt params: {}", e)))?;
+                scrypt::scrypt(password, salt, &params, &mut key[..])
+                    .map_err(|e| keystore_error(format!("scrypt failed: {}", e)))?;
+            }
+            Kdf::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => {
+                let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(32))
+                    .map_err(|e| keystore_error(format!("bad argon2 params: {}", e)))?;
+                Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password, salt, &mut key[..])
+                    .map_err(|e| keystore_error(format!("argon2 failed: {}", e)))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// A KDF with its salt
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct KdfParams {
+    /// The KDF and its cost parameters
+    #[serde(flatten)]
+    pub kdf: Kdf,
+    /// The salt
+    #[serde(with = "hex_bytes")]
+    pub salt: Vec<u8>,
+}
+
+/// An AEAD with its nonce
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CipherParams {
+    /// The AEAD
+    pub name: Cipher,
+    /// The nonce
+    #[serde(with = "hex_bytes")]
+    pub nonce: Vec<u8>,
+}
+
+/// The authenticated fields of a keystore
+#[derive(serde::Serialize)]
+struct Header<'a> {
+    version: u32,
+    kdf: &'a KdfParams,
+    cipher: &'a CipherParams,
+    derivation: &'a Option<KeyDerivation>,
+    hint: Hint,
+}
+
+/// A password-encrypted extended private key, serialized as versioned JSON.
+///
+/// The key's depth, parent, index, chain code and secret are encrypted. The
+/// key derivation and hint are stored in the clear, and authenticated along
+/// with the KDF and cipher parameters, so any modification is detected on
+/// decryption.
+///
+/// ```
+/// # #[cfg(feature = "mainnet")]
+/// # fn main() -> Result<(), coins_bip32::Bip32Error> {
+/// use coins_bip32::{derived::DerivedXPriv, ecies::Cipher, keystore::{Kdf, Keystore}};
+///
+/// let key = DerivedXPriv::root_from_seed(&[0u8; 32], None)?;
+/// let kdf = Kdf::Scrypt { log_n: 10, r: 8, p: 1 };
+/// let json = Keystore::encrypt(&key, b"hunter2", kdf, Cipher::Aes256Gcm)?.to_json()?;
+///
+/// let decrypted = Keystore::from_json(&json)?.decrypt(b"hunter2")?;
+/// assert_eq!(decrypted.verify_key(), key.verify_key());
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "mainnet"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Keystore {
+    /// The format version
+    pub version: u32,
+    /// The KDF parameters
+    pub kdf: KdfParams,
+    /// The cipher parameters
+    pub cipher: CipherParams,
+    /// The key derivation, if known
+    pub derivation: Option<KeyDerivation>,
+    /// The key's hint
+    pub hint: Hint,
+    /// The encrypted key, including the AEAD tag
+    #[serde(with = "hex_bytes")]
+    pub ciphertext: Vec<u8>,
+}
+
+impl Keystore {
+    /// Encrypt a derived key under a password
+    pub fn encrypt(
+        key: &DerivedXPriv,
+        password: &[u8],
+        kdf: Kdf,
+        cipher: Cipher,
+    ) -> Result<Self, Bip32Error> {
+        Self::encrypt_with_derivation(
+            key.as_ref(),
+            Some(key.derivation().clone()),
+            password,
+            kdf,
+            cipher,
+        )
+    }
+
+    /// Encrypt an extended key without a known derivation under a password
+    pub fn encrypt_xpriv(
+        key: &XPriv,
+        password: &[u8],
+        kdf: Kdf,
+        cipher: Cipher,
+    ) -> Result<Self, Bip32Error> {
+        Self::encrypt_with_derivation(key, None, password, kdf, cipher)
+    }
+
+    fn encrypt_with_derivation(
+        key: &XPriv,
+        derivation: Option<KeyDerivation>,
+        password: &[u8],
+        kdf: Kdf,
+        cipher: Cipher,
+    ) -> Result<Self, Bip32Error> {
+        // never make a keystore that would be refused on decryption
+        kdf.check_cost()?;
+
+        let mut salt = vec![0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = vec![0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut nonce);
+
+        let info = key.xkey_info;
+        let mut plaintext = Zeroizing::new(Vec::with_capacity(PLAINTEXT_LENGTH));
+        plaintext.push(info.depth);
+        plaintext.extend_from_slice(&info.parent.0);
+        plaintext.extend_from_slice(&info.index.to_be_bytes());
+        plaintext.extend_from_slice(&info.chain_code.0);
+        plaintext.extend_from_slice(&key.key.to_bytes());
+
+        let mut keystore = Self {
+            version: KEYSTORE_VERSION,
+            kdf: KdfParams { kdf, salt },
+            cipher: CipherParams {
+                name: cipher,
+                nonce,
+            },
+            derivation,
+            hint: info.hint,
+            ciphertext: vec![],
+        };
+        let aead_key = kdf.derive(password, &keystore.kdf.salt)?;
+        let header = keystore.header()?;
+        keystore.ciphertext = cipher.seal(
+            &aead_key,
+            &keystore.cipher.nonce,
+            Payload {
+                msg: &plaintext,
+                aad: &header,
+            },
+        )?;
+        Ok(keystore)
+    }
+
+    /// Decrypt the extended key
+    pub fn decrypt_xpriv(&self, password: &[u8]) -> Result<XPriv, Bip32Error> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(keystore_error(format!(
+                "unsupported keystore version {}",
+                self.version
+            )));
+        }
+        if self.cipher.nonce.len() != NONCE_LENGTH {
+            return Err(keystore_error("bad nonce length"));
+        }
+        self.kdf.kdf.check_cost()?;
+
+        let aead_key = self.kdf.kdf.derive(password, &self.kdf.salt)?;
+        let header = self.header()?;
+        let plaintext = Zeroizing::new(self.cipher.name.open(
+            &aead_key,
+            &self.cipher.nonce,
+            Payload {
+                msg: &self.ciphertext,
+                aad: &header,
+            },
+        )?);
+        if plaintext.len() != PLAINTEXT_LENGTH {
+            return Err(keystore_error("bad plaintext length"));
+        }
+
+        let mut parent = [0u8; 4];
+        parent.copy_from_slice(&plaintext[1..5]);
+        let mut index = [0u8; 4];
+        index.copy_from_slice(&plaintext[5..9]);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&plaintext[9..41]);
+        let xpriv = XPriv::new(
+            k256::ecdsa::SigningKey::from_slice(&plaintext[41..])?,
+            XKeyInfo {
+                depth: plaintext[0],
+                parent: KeyFingerprint(parent),
+                index: u32::from_be_bytes(index),
+                chain_code: ChainCode(chain_code),
+                hint: self.hint,
+            },
+        );
+        chain_code.zeroize();
+        Ok(xpriv)
+    }
+
+    /// Decrypt the key with its derivation. Fails if the keystore was made
+    /// from an `XPriv` without a derivation.
+    pub fn decrypt(&self, password: &[u8]) -> Result<DerivedXPriv, Bip32Error> {
+        let derivation = self
+            .derivation
+            .clone()
+            .ok_or_else(|| keystore_error("keystore has no key derivation"))?;
+        Ok(DerivedXPriv::new(self.decrypt_xpriv(password)?, derivation))
+    }
+
+    /// The fields authenticated as associated data
+    fn header(&self) -> Result<Vec<u8>, Bip32Error> {
+        serde_json::to_vec(&Header {
+            version: self.version,
+            kdf: &self.kdf,
+            cipher: &self.cipher,
+            derivation: &self.derivation,
+            hint: self.hint,
+        })
+        .map_err(|e| keystore_error(e.to_string()))
+    }
+
+    /// Serialize as JSON
+    pub fn to_json(&self) -> Result<String, Bip32Error> {
+        serde_json::to_string_pretty(self).map_err(|e| keystore_error(e.to_string()))
+    }
+
+    /// Deserialize from JSON
+    pub fn from_json(json: &str) -> Result<Self, Bip32Error> {
+        serde_json::from_str(json).map_err(|e| keystore_error(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::xkeys::Parent;
+
+    const SCRYPT: Kdf = Kdf::Scrypt {
+        log_n: 10,
+        r: 8,
+        p: 1,
+    };
+    const ARGON2: Kdf = Kdf::Argon2id {
+        m_cost: 1024,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    fn key() -> DerivedXPriv {
+        DerivedXPriv::root_from_seed(&[9u8; 32], Some(Hint::Compatibility))
+            .unwrap()
+            .derive_path("m/49'/0'/0'")
+            .unwrap()
+    }
+
+    fn assert_same(a: &XPriv, b: &XPriv) {
+        assert_eq!(a, b);
+        assert_eq!(a.xkey_info.hint, b.xkey_info.hint);
+        assert_eq!(a.xkey_info.chain_code, b.xkey_info.chain_code);
+    }
+
+    #[test]
+    fn it_round_trips() {
+        let key = key();
+        for (kdf, cipher) in [
+            (SCRYPT, Cipher::Aes256Gcm),
+            (ARGON2, Cipher::ChaCha20Poly1305),
+        ]
+        .iter()
+        {
+            let keystore = Keystore::encrypt(&key, b"password", *kdf, *cipher).unwrap();
+            let json = keystore.to_json().unwrap();
+            assert!(!json.contains(key.secret_hex().as_str()));
+
+            let parsed = Keystore::from_json(&json).unwrap();
+            assert_eq!(parsed, keystore);
+            let decrypted = parsed.decrypt(b"password").unwrap();
+            assert_same(decrypted.as_ref(), key.as_ref());
+            assert_eq!(decrypted.derivation(), key.derivation());
+        }
+
+        let xpriv: &XPriv = key.as_ref();
+        let keystore = Keystore::encrypt_xpriv(xpriv, b"pw", SCRYPT, Cipher::Aes256Gcm).unwrap();
+        assert_same(&keystore.decrypt_xpriv(b"pw").unwrap(), xpriv);
+        assert!(keystore.decrypt(b"pw").is_err());
+    }
+
+    #[test]
+    fn it_has_a_stable_json_layout() {
+        let keystore = Keystore::encrypt(&key(), b"pw", ARGON2, Cipher::ChaCha20Poly1305).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&keystore.to_json().unwrap()).unwrap();
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["kdf"]["name"], "argon2id");
+        assert_eq!(value["kdf"]["m_cost"], 1024);
+        assert_eq!(value["kdf"]["salt"].as_str().unwrap().len(), 32);
+        assert_eq!(value["cipher"]["name"], "chacha20-poly1305");
+        assert_eq!(value["derivation"]["path"], "m/49'/0'/0'");
+        assert_eq!(value["hint"], "Compatibility");
+    }
+
+    #[test]
+    fn it_detects_tampering() {
+        let keystore = Keystore::encrypt(&key(), b"password", SCRYPT, Cipher::Aes256Gcm).unwrap();
+        assert!(keystore.decrypt(b"passwore").is_err());
+
+        let mut tampered: Vec<Keystore> = vec![];
+        let mut t = keystore.clone();
+        t.ciphertext[0] ^= 1;
+        tampered.push(t);
+
+        let mut t = keystore.clone();
+        t.cipher.nonce[0] ^= 1;
+        tampered.push(t);
+
+        let mut t = keystore.clone();
+        t.kdf.salt[0] ^= 1;
+        tampered.push(t);
+
+        let mut t = keystore.clone();
+        t.kdf.kdf = Kdf::Scrypt {
+            log_n: 11,
+            r: 8,
+            p: 1,
+        };
+        tampered.push(t);
+
+        let mut t = keystore.clone();
+        t.cipher.name = Cipher::ChaCha20Poly1305;
+        tampered.push(t);
+
+        let mut t = keystore.clone();
+        t.hint = Hint::SegWit;
+        tampered.push(t);
+
+        let mut t = keystore.clone();
+        t.derivation = Some(t.derivation.unwrap().extended(0));
+        tampered.push(t);
+
+        let mut t = keystore.clone();
+        t.derivation = None;
+        tampered.push(t);
+
+        let mut t = keystore.clone();
+        t.version = 2;
+        tampered.push(t);
+
+        for t in tampered.iter() {
+            assert!(t.decrypt_xpriv(b"password").is_err(), "{:?}", t);
+        }
+    }
+
+    #[test]
+    fn it_rejects_expensive_kdfs() {
+        let expensive = [
+            Kdf::Scrypt {
+                log_n: 30,
+                r: 8,
+                p: 1,
+            },
+            // accepted by scrypt, but asks for 2^56 bytes
+            Kdf::Scrypt {
+                log_n: 20,
+                r: 1 << 29,
+                p: 1,
+            },
+            Kdf::Scrypt {
+                log_n: 17,
+                r: 8,
+                p: 1 << 20,
+            },
+            Kdf::Scrypt {
+                log_n: 255,
+                r: u32::MAX,
+                p: u32::MAX,
+            },
+            Kdf::Argon2id {
+                m_cost: 1 << 22,
+                t_cost: 1,
+                p_cost: 1,
+            },
+            Kdf::Argon2id {
+                m_cost: 1 << 21,
+                t_cost: 64,
+                p_cost: 1,
+            },
+            Kdf::Argon2id {
+                m_cost: 1 << 16,
+                t_cost: 1,
+                p_cost: 1 << 20,
+            },
+        ];
+        for kdf in expensive.iter() {
+            assert!(matches!(
+                Keystore::encrypt(&key(), b"pw", *kdf, Cipher::Aes256Gcm),
+                Err(Bip32Error::EncryptionError(_))
+            ));
+
+            let mut keystore = Keystore::encrypt(&key(), b"pw", SCRYPT, Cipher::Aes256Gcm).unwrap();
+            keystore.kdf.kdf = *kdf;
+            assert!(matches!(
+                keystore.decrypt_xpriv(b"pw"),
+                Err(Bip32Error::EncryptionError(_))
+            ));
+        }
+
+        // the defaults, and the old scrypt maximum, are within budget
+        for kdf in [
+            Kdf::scrypt(),
+            Kdf::argon2id(),
+            Kdf::Scrypt {
+                log_n: 20,
+                r: 8,
+                p: 1,
+            },
+        ]
+        .iter()
+        {
+            kdf.check_cost().unwrap();
+        }
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![forbid(unsafe_code)]
 #![warn(
     missing_docs,
@@ -9,7 +10,9 @@
     unused_extern_crates
 )]
 
-//! This crate provides a basic implementation of BIP32, BIP49, and BIP84.
+//! This crate provides a basic implementation of BIP32, BIP49, and BIP84,
+//! as well as SLIP-10 derivation for ed25519 and NIST P-256 keys, and BIP340
+//! Schnorr signing with BIP341 taproot tweaks.
 //! It can be easily adapted to support other networks, using the
 //! paramaterizable encoder.
 //!
@@ -27,10 +30,19 @@
 //! - This crate is NOT designed to be used in adversarial environments.
 //! - This crate has NOT had a comprehensive security review.
 //!
+//! # Features
+//!
+//! The `std` feature is on by default. Without it, the crate builds on `alloc`.
+//! Derivation, signing and xkey encoding work as usual. Account discovery and
+//! ECIES need `std`, and only the built-in networks are available.
+//!
 //! # Usage
 //! ```
 //! use coins_bip32::prelude::*;
 //!
+//! # #[cfg(not(feature = "mainnet"))]
+//! # fn main() {}
+//! # #[cfg(feature = "mainnet")]
 //! # fn main() -> Result<(), Bip32Error> {
 //! let digest = coins_core::Hash256::default();
 //!
@@ -50,8 +62,17 @@
 //! # }
 //! ```
 
+extern crate alloc;
+
 pub use k256::ecdsa;
 
+// used by the benchmarks
+#[cfg(test)]
+use criterion as _;
+// used by tests of feature-gated modules
+#[cfg(test)]
+use {serde_json as _, tokio as _};
+
 #[macro_use]
 pub(crate) mod macros;
 
@@ -67,9 +88,62 @@ pub mod primitives;
 /// Extended keys and related functionality
 pub mod xkeys;
 
+/// Runtime-selectable network parameters and version byte detection
+pub mod network;
+
 /// Provides keys that are coupled with their derivation path
 pub mod derived;
 
+/// Caching derivation of many keys under common prefixes
+pub mod cache;
+
+/// Verifiable ancestry proofs between derived keys
+pub mod ancestry;
+
+/// Typed BIP44-family account paths, SLIP-44 coin types and path policies
+pub mod bip44;
+
+/// Gap-limit account discovery over a pluggable usage oracle
+#[cfg(feature = "std")]
+pub mod discovery;
+
+/// Output descriptor key expressions with key origins and ranged wildcards
+pub mod descriptor;
+
+/// PSBT key-origin records (`PSBT_IN_BIP32_DERIVATION`, `PSBT_GLOBAL_XPUB`)
+pub mod psbt;
+
+/// Multisig cosigner sets with BIP67 key sorting and wallet-config export
+pub mod multisig;
+
+/// SLIP-10 derivation over secp256k1, and over ed25519 and NIST P-256 with the
+/// `ed25519` and `nist256p1` features
+pub mod slip10;
+
+/// BIP340 Schnorr signatures and BIP341 taproot tweaks
+pub mod schnorr;
+
+/// Single private keys and Wallet Import Format
+pub mod wif;
+
+/// BIP85 deterministic entropy from a root key
+pub mod bip85;
+
+/// BIP137 signed messages
+pub mod message;
+
+/// ECDH shared secrets and ECIES encryption to derived keys
+#[cfg(feature = "ecies")]
+pub mod ecies;
+
+/// Password-encrypted keystores for extended private keys
+#[cfg(feature = "keystore")]
+pub mod keystore;
+
+/// Ethereum addresses and EIP-191/EIP-712 signing
+#[cfg(feature = "ethereum")]
+pub mod ethereum;
+
 #[doc(hidden)]
 #[cfg(any(feature = "mainnet", feature = "testnet"))]
 pub mod defaults;
@@ -77,6 +151,7 @@ pub mod defaults;
 /// Quickstart types and traits
 pub mod prelude;
 
+use alloc::{string::String, vec::Vec};
 use thiserror::Error;
 
 /// The hardened derivation flag. Keys at or above this index are hardened.
@@ -99,9 +174,9 @@ pub enum Bip32Error {
     #[error("elliptic curve error")]
     EllipticCurveError(/*#[from]*/ k256::elliptic_curve::Error),
 
-    /// Error bubbled up froom std::io
+    /// Error bubbled up from coins_core::io
     #[error(transparent)]
-    IoError(#[from] std::io::Error),
+    IoError(#[from] coins_core::io::Error),
 
     /// Error bubbled up froom Ser
     #[error(transparent)]
@@ -119,6 +194,10 @@ pub enum Bip32Error {
     #[error("Attempted to derive the hardened child of an xpub")]
     HardenedDerivationFailed,
 
+    /// Attempted non-hardened derivation on a curve that only supports hardened derivation
+    #[error("Attempted non-hardened derivation on a hardened-only curve")]
+    NonHardenedDerivationFailed,
+
     /// Attempted to tweak an xpriv or xpub directly
     #[error("Attempted to tweak an xpriv or xpub directly")]
     BadTweak,
@@ -131,6 +210,14 @@ pub enum Bip32Error {
     #[error("Version bytes 0x{0:x?} don't match any network xpub version bytes")]
     BadXPubVersionBytes([u8; 4]),
 
+    /// The network has no version bytes for the key's hint
+    #[error("Network {0} has no version bytes for hint {1:?}")]
+    UnsupportedHint(String, primitives::Hint),
+
+    /// Registering a network would make version byte detection ambiguous
+    #[error("Network conflicts with already-known network {0}")]
+    NetworkConflict(String),
+
     /// Bad padding byte on serialized xprv
     #[error("Expected 0 padding byte. Got {0}")]
     BadPadding(u8),
@@ -140,8 +227,8 @@ pub enum Bip32Error {
     BadB58Checksum,
 
     /// Bubbled up error from bs58 library
-    #[error(transparent)]
-    B58Error(#[from] bs58::decode::Error),
+    #[error("{0}")]
+    B58Error(/*#[from]*/ bs58::decode::Error),
 
     /// Parsing an string derivation failed because an index string was malformatted
     #[error("Malformatted index during derivation: {0}")]
@@ -151,6 +238,54 @@ pub enum Bip32Error {
     #[error("Attempted to deserialize a DER signature to a recoverable signature. Use deserialize_vrs instead")]
     NoRecoveryId,
 
+    /// Parsing a descriptor key expression failed
+    #[error("Malformatted descriptor key expression: {0}")]
+    MalformattedDescriptor(String),
+
+    /// A PSBT record had an unexpected key type
+    #[error("Unexpected PSBT key type 0x{0:02x}")]
+    UnexpectedPsbtKeyType(u8),
+
+    /// A PSBT record was truncated or had a malformed key
+    #[error("Malformatted PSBT record: {0}")]
+    MalformattedPsbtRecord(String),
+
+    /// A WIF or raw private key was malformatted
+    #[error("Malformatted private key: {0}")]
+    MalformattedPrivkey(String),
+
+    /// A path violates BIP44-family conventions
+    #[error("Path violates policy: {}", bip44::display_violations(.0))]
+    PathPolicyViolation(Vec<bip44::PolicyViolation>),
+
+    /// The output type hint has no single-key script
+    #[error("Hint {0:?} has no single-key script")]
+    NoSingleKeyScript(primitives::Hint),
+
+    /// A multisig cosigner set or its config file was invalid
+    #[error("Invalid cosigner set: {0}")]
+    InvalidCosignerSet(String),
+
+    /// A BIP85 path or application parameter was invalid
+    #[error("Invalid BIP85 request: {0}")]
+    InvalidBip85Request(String),
+
+    /// A BIP137 message signature was malformatted or for another address type
+    #[error("Malformatted message signature: {0}")]
+    MalformattedMessageSignature(String),
+
+    /// ECIES or keystore encryption or decryption failed
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
+    /// An Ethereum address or signature was malformatted
+    #[error("Malformatted Ethereum value: {0}")]
+    MalformattedEthereum(String),
+
+    /// Error bubbled up from core base58check decoding
+    #[error(transparent)]
+    EncodingError(#[from] coins_core::enc::EncodingError),
+
     /// Attempted to deserialize a very long path
     #[error("Invalid Bip32 Path.")]
     InvalidBip32Path,
@@ -168,8 +303,14 @@ impl From<k256::elliptic_curve::Error> for Bip32Error {
     }
 }
 
-impl From<std::convert::Infallible> for Bip32Error {
-    fn from(_i: std::convert::Infallible) -> Self {
+impl From<bs58::decode::Error> for Bip32Error {
+    fn from(e: bs58::decode::Error) -> Self {
+        Self::B58Error(e)
+    }
+}
+
+impl From<core::convert::Infallible> for Bip32Error {
+    fn from(_i: core::convert::Infallible) -> Self {
         unimplemented!("unreachable, but required by type system")
     }
 }
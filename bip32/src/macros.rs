@@ -56,6 +56,85 @@ macro_rules! inherit_signer {
                 self.$attr.sign_digest_recoverable(digest)
             }
         }
+
+        impl $struct_name {
+            /// Get the BIP340 signing key. The key is negated if necessary so
+            /// that its public key has an even y coordinate.
+            pub fn schnorr_signing_key(&self) -> crate::schnorr::SchnorrSigningKey {
+                crate::schnorr::schnorr_key(AsRef::<k256::ecdsa::SigningKey>::as_ref(self))
+            }
+
+            /// Produce a BIP340 signature over an arbitrary message with the
+            /// untweaked key.
+            pub fn sign_schnorr(
+                &self,
+                msg: &[u8],
+                aux_rand: &[u8; 32],
+            ) -> Result<crate::schnorr::SchnorrSignature, crate::Bip32Error> {
+                Ok(self.schnorr_signing_key().sign_raw(msg, aux_rand)?)
+            }
+
+            /// Get the BIP341 tweaked signing key for a taproot output. Pass
+            /// `None` as the merkle root for key-path-only outputs.
+            pub fn taproot_signing_key(
+                &self,
+                merkle_root: Option<&[u8; 32]>,
+            ) -> Result<crate::schnorr::SchnorrSigningKey, crate::Bip32Error> {
+                crate::schnorr::tweak_signing_key(&self.schnorr_signing_key(), merkle_root)
+            }
+
+            /// Produce a BIP137 signed message for an address type.
+            pub fn sign_message(
+                &self,
+                message: &[u8],
+                address_type: crate::message::MessageAddressType,
+            ) -> Result<crate::message::MessageSignature, crate::Bip32Error> {
+                crate::message::sign_message(
+                    AsRef::<k256::ecdsa::SigningKey>::as_ref(self),
+                    message,
+                    address_type,
+                )
+            }
+
+            /// Verify that a BIP137 signed message was produced by this key.
+            pub fn verify_message(
+                &self,
+                message: &[u8],
+                signature: &crate::message::MessageSignature,
+            ) -> Result<(), crate::Bip32Error> {
+                signature.verify(
+                    message,
+                    AsRef::<k256::ecdsa::SigningKey>::as_ref(self).verifying_key(),
+                )
+            }
+
+            /// Recover the pubkey that signed a BIP137 message, and the address
+            /// type the signature commits to.
+            pub fn recover_pubkey(
+                message: &[u8],
+                signature: &crate::message::MessageSignature,
+            ) -> Result<
+                (
+                    k256::ecdsa::VerifyingKey,
+                    crate::message::MessageAddressType,
+                ),
+                crate::Bip32Error,
+            > {
+                crate::message::recover_pubkey(message, signature)
+            }
+
+            /// Produce a BIP340 signature for a taproot key-path spend.
+            pub fn sign_taproot(
+                &self,
+                msg: &[u8],
+                merkle_root: Option<&[u8; 32]>,
+                aux_rand: &[u8; 32],
+            ) -> Result<crate::schnorr::SchnorrSignature, crate::Bip32Error> {
+                Ok(self
+                    .taproot_signing_key(merkle_root)?
+                    .sign_raw(msg, aux_rand)?)
+            }
+        }
     };
 }
 
@@ -69,6 +148,66 @@ macro_rules! inherit_verifier {
                 data.copy_from_slice(&generic_array);
                 data
             }
+
+            /// Get the BIP340 x-only representation of the public key.
+            pub fn x_only_pubkey(&self) -> crate::schnorr::XOnlyPubkey {
+                crate::schnorr::x_only(AsRef::<k256::ecdsa::VerifyingKey>::as_ref(self))
+            }
+
+            /// Verify a BIP340 signature over an arbitrary message against the
+            /// untweaked key.
+            pub fn verify_schnorr(
+                &self,
+                msg: &[u8],
+                signature: &crate::schnorr::SchnorrSignature,
+            ) -> Result<(), crate::Bip32Error> {
+                Ok(self.x_only_pubkey().verify_raw(msg, signature)?)
+            }
+
+            /// Verify that a BIP137 signed message was produced by this key.
+            pub fn verify_message(
+                &self,
+                message: &[u8],
+                signature: &crate::message::MessageSignature,
+            ) -> Result<(), crate::Bip32Error> {
+                signature.verify(message, AsRef::<k256::ecdsa::VerifyingKey>::as_ref(self))
+            }
+
+            /// Recover the pubkey that signed a BIP137 message, and the address
+            /// type the signature commits to.
+            pub fn recover_pubkey(
+                message: &[u8],
+                signature: &crate::message::MessageSignature,
+            ) -> Result<
+                (
+                    k256::ecdsa::VerifyingKey,
+                    crate::message::MessageAddressType,
+                ),
+                crate::Bip32Error,
+            > {
+                crate::message::recover_pubkey(message, signature)
+            }
+
+            /// Get the BIP341 taproot output key. Pass `None` as the merkle
+            /// root for key-path-only outputs.
+            pub fn taproot_output_key(
+                &self,
+                merkle_root: Option<&[u8; 32]>,
+            ) -> Result<crate::schnorr::XOnlyPubkey, crate::Bip32Error> {
+                crate::schnorr::tweak_pubkey(&self.x_only_pubkey(), merkle_root).map(|(key, _)| key)
+            }
+
+            /// Verify a BIP340 signature from a taproot key-path spend.
+            pub fn verify_taproot(
+                &self,
+                msg: &[u8],
+                merkle_root: Option<&[u8; 32]>,
+                signature: &crate::schnorr::SchnorrSignature,
+            ) -> Result<(), crate::Bip32Error> {
+                Ok(self
+                    .taproot_output_key(merkle_root)?
+                    .verify_raw(msg, signature)?)
+            }
         }
 
         impl<D> k256::ecdsa::signature::DigestVerifier<D, k256::ecdsa::Signature> for $struct_name
@@ -93,9 +232,14 @@ macro_rules! params {
             bip32: $bip32:expr,
             bip49: $bip49:expr,
             bip84: $bip84:expr,
+            bip49_multisig: $bip49ms:expr,
+            bip84_multisig: $bip84ms:expr,
             bip32_pub: $bip32pub:expr,
             bip49_pub: $bip49pub:expr,
-            bip84_pub: $bip84pub:expr
+            bip84_pub: $bip84pub:expr,
+            bip49_multisig_pub: $bip49mspub:expr,
+            bip84_multisig_pub: $bip84mspub:expr,
+            wif: $wif:expr
         }
     ) => {
         $(#[$outer])*
@@ -106,9 +250,14 @@ macro_rules! params {
             const PRIV_VERSION: u32 = $bip32;
             const BIP49_PRIV_VERSION: u32 = $bip49;
             const BIP84_PRIV_VERSION: u32 = $bip84;
+            const BIP49_MULTISIG_PRIV_VERSION: u32 = $bip49ms;
+            const BIP84_MULTISIG_PRIV_VERSION: u32 = $bip84ms;
             const PUB_VERSION: u32 = $bip32pub;
             const BIP49_PUB_VERSION: u32 = $bip49pub;
             const BIP84_PUB_VERSION: u32 = $bip84pub;
+            const BIP49_MULTISIG_PUB_VERSION: u32 = $bip49mspub;
+            const BIP84_MULTISIG_PUB_VERSION: u32 = $bip84mspub;
+            const WIF_VERSION: u8 = $wif;
         }
     }
 }
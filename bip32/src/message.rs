@@ -0,0 +1,388 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use coins_core::hashes::{Digest, Hash160, Hash256};
+use k256::ecdsa::{self, RecoveryId};
+
+use crate::Bip32Error;
+
+/// The prefix committed to by signed messages, including its length byte
+pub const MESSAGE_MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+/// The length of a serialized message signature
+pub const MESSAGE_SIGNATURE_LENGTH: usize = 65;
+
+fn malformatted(msg: impl Into<String>) -> Bip32Error {
+    Bip32Error::MalformattedMessageSignature(msg.into())
+}
+
+/// Hash a message for signing, as `Hash256(magic || varint(len) || message)`
+pub fn message_digest(message: &[u8]) -> Hash256 {
+    let mut len = vec![];
+    coins_core::ser::write_compact_int(&mut len, message.len() as u64)
+        .expect("no IO error on vec write");
+    let mut digest = Hash256::default();
+    digest.update(MESSAGE_MAGIC);
+    digest.update(&len);
+    digest.update(message);
+    digest
+}
+
+/// The address type a message signature commits to. BIP137 encodes this in
+/// the signature's header byte, along with the recovery id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageAddressType {
+    /// P2PKH with an uncompressed pubkey. Headers 27-30
+    P2pkhUncompressed,
+    /// P2PKH with a compressed pubkey. Headers 31-34
+    P2pkh,
+    /// P2SH-wrapped P2WPKH. Headers 35-38
+    P2shP2wpkh,
+    /// Native P2WPKH. Headers 39-42
+    P2wpkh,
+}
+
+impl MessageAddressType {
+    /// The header byte for this address type with recovery id 0
+    pub const fn header_base(&self) -> u8 {
+        match self {
+            Self::P2pkhUncompressed => 27,
+            Self::P2pkh => 31,
+            Self::P2shP2wpkh => 35,
+            Self::P2wpkh => 39,
+        }
+    }
+
+    /// Parse a header byte into an address type and recovery id
+    pub fn from_header(header: u8) -> Result<(Self, RecoveryId), Bip32Error> {
+        let address_type = match header {
+            27..=30 => Self::P2pkhUncompressed,
+            31..=34 => Self::P2pkh,
+            35..=38 => Self::P2shP2wpkh,
+            39..=42 => Self::P2wpkh,
+            _ => return Err(malformatted(format!("unknown header byte {}", header))),
+        };
+        let recovery_id = RecoveryId::from_byte(header - address_type.header_base())
+            .expect("header ranges hold 4 ids");
+        Ok((address_type, recovery_id))
+    }
+
+    /// `true` if the address commits to a compressed pubkey
+    pub const fn is_compressed(&self) -> bool {
+        !matches!(self, Self::P2pkhUncompressed)
+    }
+
+    /// The 20-byte hash carried by an address of this type for the key. This
+    /// is the pubkey hash for P2PKH and P2WPKH, and the script hash for
+    /// P2SH-P2WPKH.
+    pub fn address_hash(&self, key: &ecdsa::VerifyingKey) -> [u8; 20] {
+        let pubkey = key.to_encoded_point(self.is_compressed());
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&Hash160::digest(pubkey.as_bytes()));
+        if let Self::P2shP2wpkh = self {
+            let mut redeem_script = vec![0x00, 0x14];
+            redeem_script.extend(hash);
+            hash.copy_from_slice(&Hash160::digest(&redeem_script));
+        }
+        hash
+    }
+}
+
+/// A BIP137 message signature: a header byte encoding the address type and
+/// recovery id, followed by the 64-byte compact signature. Usually encoded
+/// as base64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageSignature {
+    /// The address type the signature commits to
+    pub address_type: MessageAddressType,
+    /// The recovery id
+    pub recovery_id: RecoveryId,
+    /// The signature
+    pub signature: ecdsa::Signature,
+}
+
+impl MessageSignature {
+    /// Serialize as `header || r || s`
+    pub fn to_bytes(&self) -> [u8; MESSAGE_SIGNATURE_LENGTH] {
+        let mut buf = [0u8; MESSAGE_SIGNATURE_LENGTH];
+        buf[0] = self.address_type.header_base() + self.recovery_id.to_byte();
+        buf[1..].copy_from_slice(&self.signature.to_bytes());
+        buf
+    }
+
+    /// Deserialize from `header || r || s`
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, Bip32Error> {
+        if buf.len() != MESSAGE_SIGNATURE_LENGTH {
+            return Err(malformatted(format!(
+                "expected 65 bytes, got {}",
+                buf.len()
+            )));
+        }
+        let (address_type, recovery_id) = MessageAddressType::from_header(buf[0])?;
+        Ok(Self {
+            address_type,
+            recovery_id,
+            signature: ecdsa::Signature::from_slice(&buf[1..])?,
+        })
+    }
+
+    /// Serialize as base64
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.to_bytes())
+    }
+
+    /// Deserialize from base64
+    pub fn from_base64(s: &str) -> Result<Self, Bip32Error> {
+        let buf = STANDARD
+            .decode(s.trim())
+            .map_err(|e| malformatted(e.to_string()))?;
+        Self::from_bytes(&buf)
+    }
+
+    /// Recover the pubkey that signed the message
+    pub fn recover_pubkey(&self, message: &[u8]) -> Result<ecdsa::VerifyingKey, Bip32Error> {
+        Ok(ecdsa::VerifyingKey::recover_from_digest(
+            message_digest(message),
+            &self.signature,
+            self.recovery_id,
+        )?)
+    }
+
+    /// Verify that the message was signed by the key
+    pub fn verify(&self, message: &[u8], key: &ecdsa::VerifyingKey) -> Result<(), Bip32Error> {
+        if &self.recover_pubkey(message)? != key {
+            return Err(ecdsa::Error::new().into());
+        }
+        Ok(())
+    }
+
+    /// Verify that the message was signed by the owner of an address, given
+    /// its type and 20-byte hash. Electrum signs for segwit addresses using
+    /// P2PKH headers, so those are accepted for P2SH-P2WPKH and P2WPKH.
+    pub fn verify_address(
+        &self,
+        message: &[u8],
+        address_type: MessageAddressType,
+        address_hash: &[u8; 20],
+    ) -> Result<(), Bip32Error> {
+        let compatible = self.address_type == address_type
+            || (self.address_type == MessageAddressType::P2pkh
+                && matches!(
+                    address_type,
+                    MessageAddressType::P2shP2wpkh | MessageAddressType::P2wpkh
+                ));
+        if !compatible {
+            return Err(malformatted(format!(
+                "signature is for {:?}, not {:?}",
+                self.address_type, address_type
+            )));
+        }
+        let key = self.recover_pubkey(message)?;
+        if &address_type.address_hash(&key) != address_hash {
+            return Err(ecdsa::Error::new().into());
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Display for MessageSignature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.to_base64())
+    }
+}
+
+impl core::str::FromStr for MessageSignature {
+    type Err = Bip32Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_base64(s)
+    }
+}
+
+/// Sign a message for an address type
+pub fn sign_message(
+    key: &ecdsa::SigningKey,
+    message: &[u8],
+    address_type: MessageAddressType,
+) -> Result<MessageSignature, Bip32Error> {
+    let (signature, recovery_id) = key.sign_digest_recoverable(message_digest(message))?;
+    Ok(MessageSignature {
+        address_type,
+        recovery_id,
+        signature,
+    })
+}
+
+/// Recover the pubkey that signed a message, and the address type the
+/// signature commits to
+pub fn recover_pubkey(
+    message: &[u8],
+    signature: &MessageSignature,
+) -> Result<(ecdsa::VerifyingKey, MessageAddressType), Bip32Error> {
+    Ok((signature.recover_pubkey(message)?, signature.address_type))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        derived::{DerivedKey, DerivedPubkey, DerivedXPriv},
+        enc::{MainnetEncoder, XKeyEncoder},
+        path::KeyDerivation,
+        wif::Privkey,
+        xkeys::Parent,
+    };
+    use coins_core::enc::{decode_base58, decode_bech32};
+
+    fn base58_hash(version: u8, address: &str) -> [u8; 20] {
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&decode_base58(version, address).unwrap()[1..]);
+        hash
+    }
+
+    #[test]
+    fn it_verifies_core_signatures() {
+        // Bitcoin Core test/functional/rpc_signmessage.py
+        let key = Privkey::from_wif::<crate::enc::Test>(
+            "cUeKHd5orzT3mz8P9pxyREHfsWtVfgsfDjiZZBcjUBAaGk1BTj7N",
+        )
+        .unwrap();
+        let message = b"This is just a test message";
+        let expected = "INbVnW4e6PeRmsv2Qgu8NuopvrVjkcxob+sX8OcZG0SALhWybUjzMLPdAsXI46YZGb0KQTRii+wWIQzRpG/U+S0=";
+        let address = base58_hash(0x6f, "mpLQjfK79b7CCV4VMJWEWAj5Mpx8Up5zxB");
+
+        let signature = key
+            .sign_message(message, MessageAddressType::P2pkh)
+            .unwrap();
+        assert_eq!(signature.to_base64(), expected);
+
+        let parsed: MessageSignature = expected.parse().unwrap();
+        assert_eq!(parsed, signature);
+        parsed
+            .verify_address(message, MessageAddressType::P2pkh, &address)
+            .unwrap();
+        key.verify_message(message, &parsed).unwrap();
+        assert!(parsed
+            .verify_address(b"another message", MessageAddressType::P2pkh, &address)
+            .is_err());
+    }
+
+    #[test]
+    fn it_verifies_electrum_signatures() {
+        // Electrum electrum/tests/test_bitcoin.py
+        let cases = [
+            (
+                "L1TnU2zbNaAqMoVh65Cyvmcjzbrj41Gs9iTLcWbpJCMynXuap6UN",
+                &b"Chancellor on brink of second bailout for banks"[..],
+                "15hETetDmcXm1mM4sEf7U2KXC9hDHFMSzz",
+                "H/9jMOnj4MFbH3d7t4yCQ9i7DgZU/VZ278w3+ySv2F4yIsdqjsc5ng3kmN8OZAThgyfCZOQxZCWza9V5XzlVY0Y=",
+            ),
+            (
+                "5Hxn5C4SQuiV6e62A1MtZmbSeQyrLFhu5uYks62pU5VBUygK2KD",
+                &b"Electrum"[..],
+                "1GPHVTY8UD9my6jyP4tb2TYJwUbDetyNC6",
+                "G84dmJ8TKIDKMT9qBRhpX2sNmR0y5t+POcYnFFJCs66lJmAs3T8A6Sbpx7KA6yTQ9djQMabwQXRrDomOkIKGn18=",
+            ),
+        ];
+        for (wif, message, address, expected) in cases.iter() {
+            let key = Privkey::from_wif::<crate::enc::Main>(wif).unwrap();
+            let address_type = match key.is_compressed() {
+                true => MessageAddressType::P2pkh,
+                false => MessageAddressType::P2pkhUncompressed,
+            };
+            let signature = key.sign_message(message, address_type).unwrap();
+            assert_eq!(&signature.to_base64(), expected);
+            signature
+                .verify_address(message, address_type, &base58_hash(0x00, address))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn it_handles_segwit_headers() {
+        let xpriv = MainnetEncoder::xpriv_from_base58("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi").unwrap();
+        let derived = DerivedXPriv::new(
+            xpriv,
+            KeyDerivation {
+                root: [0u8; 4].into(),
+                path: vec![].into(),
+            },
+        )
+        .derive_path("m/84'/0'/0'/0/0")
+        .unwrap();
+        let xpub = derived.verify_key();
+        let pubkey = DerivedPubkey::new(
+            *AsRef::<ecdsa::VerifyingKey>::as_ref(&xpub),
+            xpub.derivation().clone(),
+        );
+        let message = b"address ownership";
+
+        for (address_type, range) in [
+            (MessageAddressType::P2pkhUncompressed, 27..=30),
+            (MessageAddressType::P2pkh, 31..=34),
+            (MessageAddressType::P2shP2wpkh, 35..=38),
+            (MessageAddressType::P2wpkh, 39..=42),
+        ] {
+            let signature = derived.sign_message(message, address_type).unwrap();
+            assert!(range.contains(&signature.to_bytes()[0]));
+            pubkey.verify_message(message, &signature).unwrap();
+
+            let (recovered, found) = recover_pubkey(message, &signature).unwrap();
+            assert_eq!(&recovered, pubkey.as_ref());
+            assert_eq!(found, address_type);
+            assert_eq!(
+                DerivedPubkey::recover_pubkey(message, &signature).unwrap(),
+                (recovered, found)
+            );
+            assert_eq!(
+                DerivedXPriv::recover_pubkey(message, &signature).unwrap(),
+                (recovered, found)
+            );
+
+            let hash = address_type.address_hash(pubkey.as_ref());
+            signature
+                .verify_address(message, address_type, &hash)
+                .unwrap();
+        }
+
+        // a P2WPKH address from bech32
+        let (_, program) =
+            decode_bech32("bc", "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu").unwrap();
+        let key = Privkey::from_wif::<crate::enc::Main>(
+            "KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d",
+        )
+        .unwrap();
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&program);
+        assert_eq!(
+            MessageAddressType::P2wpkh.address_hash(&key.verify_key()),
+            hash
+        );
+
+        // Electrum-style P2PKH headers are accepted for segwit addresses
+        let signature = key
+            .sign_message(message, MessageAddressType::P2pkh)
+            .unwrap();
+        signature
+            .verify_address(message, MessageAddressType::P2wpkh, &hash)
+            .unwrap();
+        // but BIP137 segwit headers are not accepted for other types
+        let signature = key
+            .sign_message(message, MessageAddressType::P2wpkh)
+            .unwrap();
+        assert!(signature
+            .verify_address(message, MessageAddressType::P2shP2wpkh, &hash)
+            .is_err());
+
+        assert!(MessageSignature::from_base64("AAAA").is_err());
+        let mut bytes = signature.to_bytes();
+        bytes[0] = 43;
+        assert!(matches!(
+            MessageSignature::from_bytes(&bytes),
+            Err(Bip32Error::MalformattedMessageSignature(_))
+        ));
+    }
+}
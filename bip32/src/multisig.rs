@@ -0,0 +1,447 @@
+use alloc::{borrow::ToOwned, format, string::String, vec, vec::Vec};
+use core::{convert::TryInto, fmt, str::FromStr};
+
+use k256::ecdsa;
+
+use crate::{
+    derived::{DerivedKey, DerivedPubkey, DerivedXPub},
+    enc::XKeyEncoder,
+    path::{DerivationPath, KeyDerivation},
+    primitives::{Hint, KeyFingerprint, XKeyInfo},
+    xkeys::Parent,
+    Bip32Error, BIP32_HARDEN,
+};
+
+/// The largest cosigner set accepted. This is the P2SH standardness limit,
+/// and the limit of most hardware signers.
+pub const MAX_COSIGNERS: usize = 15;
+
+fn invalid(msg: impl Into<String>) -> Bip32Error {
+    Bip32Error::InvalidCosignerSet(msg.into())
+}
+
+/// The script format of a multisig wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MultisigFormat {
+    /// Bare P2SH
+    P2sh,
+    /// P2WSH nested in P2SH
+    P2shP2wsh,
+    /// Native P2WSH
+    P2wsh,
+}
+
+impl MultisigFormat {
+    /// The name used in wallet-config files
+    pub const fn name(&self) -> &'static str {
+        match self {
+            MultisigFormat::P2sh => "P2SH",
+            MultisigFormat::P2shP2wsh => "P2SH-P2WSH",
+            MultisigFormat::P2wsh => "P2WSH",
+        }
+    }
+
+    /// The SLIP-132 hint for xpubs of this format
+    pub const fn hint(&self) -> Hint {
+        match self {
+            MultisigFormat::P2sh => Hint::Legacy,
+            MultisigFormat::P2shP2wsh => Hint::CompatibilityMultisig,
+            MultisigFormat::P2wsh => Hint::SegWitMultisig,
+        }
+    }
+}
+
+impl fmt::Display for MultisigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for MultisigFormat {
+    type Err = Bip32Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "P2SH" => Ok(MultisigFormat::P2sh),
+            "P2SH-P2WSH" | "P2WSH-P2SH" => Ok(MultisigFormat::P2shP2wsh),
+            "P2WSH" => Ok(MultisigFormat::P2wsh),
+            other => Err(invalid(format!("unknown format {}", other))),
+        }
+    }
+}
+
+/// A set of origin-tagged cosigner xpubs with a signing threshold. The
+/// cosigners are kept in the order given. Pubkeys derived from the set are
+/// sorted as in BIP67.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CosignerSet {
+    threshold: usize,
+    format: MultisigFormat,
+    cosigners: Vec<DerivedXPub>,
+}
+
+impl CosignerSet {
+    /// Instantiate a cosigner set. Fails if the threshold is not in
+    /// `1..=cosigners.len()`, if there are more than `MAX_COSIGNERS`, if any
+    /// xpub appears twice, or if the xpubs are at different depths.
+    pub fn new(
+        threshold: usize,
+        format: MultisigFormat,
+        cosigners: Vec<DerivedXPub>,
+    ) -> Result<Self, Bip32Error> {
+        let n = cosigners.len();
+        if n == 0 || n > MAX_COSIGNERS {
+            return Err(invalid(format!(
+                "expected 1 to {} cosigners, got {}",
+                MAX_COSIGNERS, n
+            )));
+        }
+        if threshold == 0 || threshold > n {
+            return Err(invalid(format!(
+                "threshold {} is not in 1..={}",
+                threshold, n
+            )));
+        }
+
+        let depth = key_info(&cosigners[0]).depth;
+        for (i, cosigner) in cosigners.iter().enumerate() {
+            let info = key_info(cosigner);
+            if info.depth != depth {
+                return Err(invalid(format!(
+                    "cosigner {} has depth {}, expected {}",
+                    i, info.depth, depth
+                )));
+            }
+            if cosigner.derivation().path.len() != depth as usize {
+                return Err(invalid(format!(
+                    "cosigner {} origin path does not match its depth {}",
+                    i, depth
+                )));
+            }
+            let key: &ecdsa::VerifyingKey = cosigner.as_ref();
+            if let Some(j) = cosigners[..i]
+                .iter()
+                .position(|other| AsRef::<ecdsa::VerifyingKey>::as_ref(other) == key)
+            {
+                return Err(invalid(format!(
+                    "cosigners {} and {} are the same xpub",
+                    j, i
+                )));
+            }
+        }
+
+        Ok(Self {
+            threshold,
+            format,
+            cosigners,
+        })
+    }
+
+    /// The number of signatures required
+    pub const fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// The script format
+    pub const fn format(&self) -> MultisigFormat {
+        self.format
+    }
+
+    /// The cosigner xpubs, in the order given
+    pub fn cosigners(&self) -> &[DerivedXPub] {
+        &self.cosigners
+    }
+
+    /// The number of cosigners
+    pub const fn len(&self) -> usize {
+        self.cosigners.len()
+    }
+
+    /// `true` if there are no cosigners. Never true for a valid set
+    pub const fn is_empty(&self) -> bool {
+        self.cosigners.is_empty()
+    }
+
+    /// The derivation path shared by all cosigners, if any
+    pub fn common_path(&self) -> Option<&DerivationPath> {
+        let path = &self.cosigners[0].derivation().path;
+        self.cosigners
+            .iter()
+            .all(|c| &c.derivation().path == path)
+            .then_some(path)
+    }
+
+    /// Derive the cosigner pubkeys at some unhardened path below each
+    /// cosigner, sorted as in BIP67
+    pub fn derive_sorted<E, P>(&self, path: P) -> Result<Vec<DerivedPubkey>, Bip32Error>
+    where
+        E: Into<Bip32Error>,
+        P: TryInto<DerivationPath, Error = E>,
+    {
+        let path: DerivationPath = path.try_into().map_err(Into::into)?;
+        if path.iter().any(|index| *index >= BIP32_HARDEN) {
+            return Err(Bip32Error::HardenedDerivationFailed);
+        }
+
+        let mut pubkeys = self
+            .cosigners
+            .iter()
+            .map(|cosigner| {
+                let child = cosigner.derive_path(&path)?;
+                Ok(DerivedPubkey::new(
+                    *AsRef::<ecdsa::VerifyingKey>::as_ref(&child),
+                    child.derivation().clone(),
+                ))
+            })
+            .collect::<Result<Vec<_>, Bip32Error>>()?;
+        pubkeys.sort_by_key(|key| key.to_sec1_bytes());
+        Ok(pubkeys)
+    }
+
+    /// Derive the sorted cosigner pubkeys at `change/index`
+    pub fn derive_pubkeys(
+        &self,
+        change: u32,
+        index: u32,
+    ) -> Result<Vec<DerivedPubkey>, Bip32Error> {
+        self.derive_sorted(DerivationPath::from(vec![change, index]))
+    }
+
+    /// Serialize as a Coldcard/Specter multisig setup file. The derivation
+    /// is written once if shared, and before each key otherwise.
+    pub fn to_config<E: XKeyEncoder>(&self, name: &str) -> Result<String, Bip32Error> {
+        let mut lines = vec![
+            format!("Name: {}", name),
+            format!("Policy: {} of {}", self.threshold, self.len()),
+        ];
+        let common = self.common_path();
+        if let Some(path) = common {
+            lines.push(format!("Derivation: {}", path.derivation_string()));
+        }
+        lines.push(format!("Format: {}", self.format));
+        lines.push(String::new());
+
+        for cosigner in self.cosigners.iter() {
+            if common.is_none() {
+                lines.push(format!(
+                    "Derivation: {}",
+                    cosigner.derivation().path.derivation_string()
+                ));
+            }
+            lines.push(format!(
+                "{}: {}",
+                hex::encode_upper(cosigner.derivation().root.0),
+                E::xpub_to_base58(cosigner)?
+            ));
+        }
+        lines.push(String::new());
+        Ok(lines.join("\n"))
+    }
+
+    /// Parse a Coldcard/Specter multisig setup file, returning its name and
+    /// cosigner set. Comments start with `#`. A `Derivation` line applies to
+    /// the keys after it.
+    pub fn from_config<E: XKeyEncoder>(config: &str) -> Result<(String, Self), Bip32Error> {
+        let mut name = String::new();
+        let mut policy = None;
+        let mut format = MultisigFormat::P2sh;
+        let mut derivation: Option<DerivationPath> = None;
+        let mut cosigners = vec![];
+
+        for line in config.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(|| invalid(format!("malformed line: {}", line)))?;
+
+            match key.to_ascii_lowercase().as_str() {
+                "name" => name = value.to_owned(),
+                "policy" => policy = Some(parse_policy(value)?),
+                "derivation" => derivation = Some(value.parse()?),
+                "format" => format = value.parse()?,
+                _ => {
+                    let root = parse_fingerprint(key)?;
+                    let xpub = E::xpub_from_base58(value)?;
+                    let path = derivation
+                        .clone()
+                        .ok_or_else(|| invalid(format!("no derivation for key {}", key)))?;
+                    cosigners.push(DerivedXPub::new(xpub, KeyDerivation { root, path }));
+                }
+            }
+        }
+
+        let (threshold, n) = policy.ok_or_else(|| invalid("missing policy"))?;
+        if n != cosigners.len() {
+            return Err(invalid(format!(
+                "policy has {} cosigners, found {}",
+                n,
+                cosigners.len()
+            )));
+        }
+        Ok((name, Self::new(threshold, format, cosigners)?))
+    }
+}
+
+fn key_info(xpub: &DerivedXPub) -> &XKeyInfo {
+    xpub.as_ref()
+}
+
+fn parse_policy(value: &str) -> Result<(usize, usize), Bip32Error> {
+    let parts: Vec<&str> = if value.contains('/') {
+        value.split('/').collect()
+    } else {
+        value.split(" of ").collect()
+    };
+    match parts.as_slice() {
+        [m, n] => {
+            let m = m.trim().parse().map_err(|_| invalid(value))?;
+            let n = n.trim().parse().map_err(|_| invalid(value))?;
+            Ok((m, n))
+        }
+        _ => Err(invalid(format!("malformed policy: {}", value))),
+    }
+}
+
+fn parse_fingerprint(value: &str) -> Result<KeyFingerprint, Bip32Error> {
+    let bytes = hex::decode(value).map_err(|_| invalid(format!("bad fingerprint {}", value)))?;
+    let bytes: [u8; 4] = bytes
+        .try_into()
+        .map_err(|_| invalid(format!("bad fingerprint {}", value)))?;
+    Ok(bytes.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{derived::DerivedXPriv, enc::MainnetEncoder};
+
+    fn cosigner(seed: u8, path: &str) -> DerivedXPub {
+        DerivedXPriv::root_from_seed(&[seed; 32], Some(Hint::SegWitMultisig))
+            .unwrap()
+            .derive_path(path)
+            .unwrap()
+            .verify_key()
+    }
+
+    fn set() -> CosignerSet {
+        let cosigners = (1..=3).map(|s| cosigner(s, "m/48'/0'/0'/2'")).collect();
+        CosignerSet::new(2, MultisigFormat::P2wsh, cosigners).unwrap()
+    }
+
+    #[test]
+    fn it_validates_sets() {
+        let a = cosigner(1, "m/48'/0'/0'/2'");
+        let b = cosigner(2, "m/48'/0'/0'/2'");
+        let shallow = cosigner(3, "m/48'/0'/0'");
+
+        assert!(CosignerSet::new(0, MultisigFormat::P2wsh, vec![a.clone(), b.clone()]).is_err());
+        assert!(CosignerSet::new(3, MultisigFormat::P2wsh, vec![a.clone(), b.clone()]).is_err());
+        assert!(CosignerSet::new(1, MultisigFormat::P2wsh, vec![]).is_err());
+
+        let dup = CosignerSet::new(
+            2,
+            MultisigFormat::P2wsh,
+            vec![a.clone(), b.clone(), a.clone()],
+        );
+        assert!(matches!(dup, Err(Bip32Error::InvalidCosignerSet(m)) if m.contains("same xpub")));
+
+        let depth = CosignerSet::new(2, MultisigFormat::P2wsh, vec![a, b, shallow]);
+        assert!(matches!(depth, Err(Bip32Error::InvalidCosignerSet(m)) if m.contains("depth")));
+    }
+
+    #[test]
+    fn it_derives_sorted_pubkeys() {
+        let set = set();
+        let keys = set.derive_pubkeys(0, 7).unwrap();
+        assert_eq!(keys.len(), 3);
+        for pair in keys.windows(2) {
+            assert!(pair[0].to_sec1_bytes() < pair[1].to_sec1_bytes());
+        }
+        for key in keys.iter() {
+            assert_eq!(
+                key.derivation().path.derivation_string(),
+                "m/48'/0'/0'/2'/0/7"
+            );
+            let cosigner = set
+                .cosigners()
+                .iter()
+                .find(|c| c.derivation().root == key.derivation().root)
+                .unwrap();
+            assert_eq!(
+                AsRef::<ecdsa::VerifyingKey>::as_ref(&cosigner.derive_path("m/0/7").unwrap()),
+                AsRef::<ecdsa::VerifyingKey>::as_ref(key)
+            );
+        }
+
+        // sorting is independent of cosigner order
+        let mut reversed = set.cosigners().to_vec();
+        reversed.reverse();
+        let reversed = CosignerSet::new(2, MultisigFormat::P2wsh, reversed).unwrap();
+        assert_eq!(
+            reversed
+                .derive_pubkeys(0, 7)
+                .unwrap()
+                .iter()
+                .map(|k| k.to_sec1_bytes())
+                .collect::<Vec<_>>(),
+            keys.iter().map(|k| k.to_sec1_bytes()).collect::<Vec<_>>()
+        );
+
+        assert!(set.derive_sorted("m/0'/1").is_err());
+    }
+
+    #[test]
+    fn it_round_trips_configs() {
+        let set = set();
+        let config = set.to_config::<MainnetEncoder>("Vault").unwrap();
+        assert!(config.starts_with(
+            "Name: Vault\nPolicy: 2 of 3\nDerivation: m/48'/0'/0'/2'\nFormat: P2WSH\n\n"
+        ));
+        assert!(config.contains(&format!(
+            "{}: Zpub",
+            hex::encode_upper(set.cosigners()[0].derivation().root.0)
+        )));
+
+        let (name, parsed) = CosignerSet::from_config::<MainnetEncoder>(&config).unwrap();
+        assert_eq!(name, "Vault");
+        assert_eq!(parsed, set);
+
+        // per-key derivations
+        let mixed = CosignerSet::new(
+            1,
+            MultisigFormat::P2shP2wsh,
+            vec![cosigner(1, "m/48'/0'/0'/1'"), cosigner(2, "m/48'/0'/5'/1'")],
+        )
+        .unwrap();
+        assert!(mixed.common_path().is_none());
+        let config = mixed.to_config::<MainnetEncoder>("Mixed").unwrap();
+        assert_eq!(config.matches("Derivation:").count(), 2);
+        let (_, parsed) = CosignerSet::from_config::<MainnetEncoder>(&config).unwrap();
+        assert_eq!(parsed, mixed);
+    }
+
+    #[test]
+    fn it_parses_coldcard_exports() {
+        let a = cosigner(1, "m/48'/0'/0'/2'");
+        let b = cosigner(2, "m/48'/0'/0'/2'");
+        let config = format!(
+            "# Coldcard Multisig setup file (exported)\n#\nName: CC-2-of-2\nPolicy: 2/2\nDerivation: m/48'/0'/0'/2'\nFormat: P2WSH\n\n{}: {}\n{}: {}\n",
+            hex::encode_upper(a.derivation().root.0),
+            MainnetEncoder::xpub_to_base58(&a).unwrap(),
+            hex::encode_upper(b.derivation().root.0),
+            MainnetEncoder::xpub_to_base58(&b).unwrap(),
+        );
+        let (name, set) = CosignerSet::from_config::<MainnetEncoder>(&config).unwrap();
+        assert_eq!(name, "CC-2-of-2");
+        assert_eq!(set.threshold(), 2);
+        assert_eq!(set.format(), MultisigFormat::P2wsh);
+
+        let bad_count = config.replace("Policy: 2/2", "Policy: 2/3");
+        assert!(CosignerSet::from_config::<MainnetEncoder>(&bad_count).is_err());
+        let no_policy = config.replace("Policy: 2/2\n", "");
+        assert!(CosignerSet::from_config::<MainnetEncoder>(&no_policy).is_err());
+    }
+}
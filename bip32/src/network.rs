@@ -0,0 +1,433 @@
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{
+    enc::{decode_b58_check, encode_b58_check, MainnetEncoder, NetworkParams, XKeyEncoder},
+    primitives::Hint,
+    xkeys::{XPriv, XPub},
+    Bip32Error,
+};
+
+/// The version bytes used to serialize extended keys carrying a hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionBytes {
+    /// The hint these version bytes encode
+    pub hint: Hint,
+    /// The xpriv version bytes
+    pub xpriv: u32,
+    /// The xpub version bytes
+    pub xpub: u32,
+}
+
+/// Value-level network parameters for extended key serialization. Unlike
+/// `NetworkParams`, these can be selected and registered at runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    /// A human-readable network name. Must be unique among registered networks
+    pub name: Cow<'static, str>,
+    /// The version bytes supported by the network. Bytes shared by several
+    /// hints resolve to the earliest entry
+    pub versions: Cow<'static, [VersionBytes]>,
+    /// The WIF private key version byte. Unlike extended key version bytes,
+    /// this may be shared between networks
+    pub wif: u8,
+}
+
+macro_rules! versions {
+    ($($hint:ident: $xpriv:expr, $xpub:expr;)*) => {
+        Cow::Borrowed(&[
+            $(VersionBytes {
+                hint: Hint::$hint,
+                xpriv: $xpriv,
+                xpub: $xpub,
+            },)*
+        ])
+    };
+}
+
+/// Bitcoin mainnet
+pub const BITCOIN: Network = Network {
+    name: Cow::Borrowed("bitcoin"),
+    versions: versions! {
+        Legacy: 0x0488_ADE4, 0x0488_B21E;
+        Compatibility: 0x049d_7878, 0x049d_7cb2;
+        SegWit: 0x04b2_430c, 0x04b2_4746;
+        CompatibilityMultisig: 0x0295_b005, 0x0295_b43f;
+        SegWitMultisig: 0x02aa_7a99, 0x02aa_7ed3;
+        Taproot: 0x0488_ADE4, 0x0488_B21E;
+    },
+    wif: 0x80,
+};
+
+/// Bitcoin testnet. Signet and regtest keys use the same version bytes
+pub const BITCOIN_TESTNET: Network = Network {
+    name: Cow::Borrowed("testnet"),
+    versions: versions! {
+        Legacy: 0x0435_8394, 0x0435_87CF;
+        Compatibility: 0x044a_4e28, 0x044a_5262;
+        SegWit: 0x045f_18bc, 0x045f_1cf6;
+        CompatibilityMultisig: 0x0242_85b5, 0x0242_89ef;
+        SegWitMultisig: 0x0257_5048, 0x0257_5483;
+        Taproot: 0x0435_8394, 0x0435_87CF;
+    },
+    wif: 0xef,
+};
+
+/// Litecoin mainnet (Ltpv/Ltub and Mtpv/Mtub)
+pub const LITECOIN: Network = Network {
+    name: Cow::Borrowed("litecoin"),
+    versions: versions! {
+        Legacy: 0x019d_9cfe, 0x019d_a462;
+        Compatibility: 0x01b2_6792, 0x01b2_6ef6;
+    },
+    wif: 0xb0,
+};
+
+/// Litecoin testnet (ttpv/ttub)
+pub const LITECOIN_TESTNET: Network = Network {
+    name: Cow::Borrowed("litecoin-testnet"),
+    versions: versions! {
+        Legacy: 0x0436_ef7d, 0x0436_f6e1;
+    },
+    wif: 0xef,
+};
+
+/// Dogecoin mainnet (dgpv/dgub)
+pub const DOGECOIN: Network = Network {
+    name: Cow::Borrowed("dogecoin"),
+    versions: versions! {
+        Legacy: 0x02fa_c398, 0x02fa_cafd;
+    },
+    wif: 0x9e,
+};
+
+/// The networks known without registration
+pub const BUILTIN_NETWORKS: [Network; 5] = [
+    BITCOIN,
+    BITCOIN_TESTNET,
+    LITECOIN,
+    LITECOIN_TESTNET,
+    DOGECOIN,
+];
+
+#[cfg(feature = "std")]
+static REGISTERED: RwLock<Vec<Network>> = RwLock::new(Vec::new());
+
+impl Network {
+    /// Build a network from compile-time `NetworkParams`
+    pub fn from_params<P: NetworkParams>(name: impl Into<Cow<'static, str>>) -> Self {
+        let versions = Hint::ALL
+            .iter()
+            .map(|hint| VersionBytes {
+                hint: *hint,
+                xpriv: P::priv_version(*hint),
+                xpub: P::pub_version(*hint),
+            })
+            .collect::<Vec<_>>();
+        Self {
+            name: name.into(),
+            versions: versions.into(),
+            wif: P::WIF_VERSION,
+        }
+    }
+
+    /// The xpriv version bytes for a hint, if the network supports it
+    pub fn priv_version(&self, hint: Hint) -> Option<u32> {
+        self.versions
+            .iter()
+            .find(|v| v.hint == hint)
+            .map(|v| v.xpriv)
+    }
+
+    /// The xpub version bytes for a hint, if the network supports it
+    pub fn pub_version(&self, hint: Hint) -> Option<u32> {
+        self.versions
+            .iter()
+            .find(|v| v.hint == hint)
+            .map(|v| v.xpub)
+    }
+
+    /// The hint encoded by some xpriv version bytes, if any
+    pub fn priv_hint(&self, version: u32) -> Option<Hint> {
+        self.versions
+            .iter()
+            .find(|v| v.xpriv == version)
+            .map(|v| v.hint)
+    }
+
+    /// The hint encoded by some xpub version bytes, if any
+    pub fn pub_hint(&self, version: u32) -> Option<Hint> {
+        self.versions
+            .iter()
+            .find(|v| v.xpub == version)
+            .map(|v| v.hint)
+    }
+
+    #[cfg(feature = "std")]
+    fn uses_version(&self, version: u32) -> bool {
+        self.versions
+            .iter()
+            .any(|v| v.xpriv == version || v.xpub == version)
+    }
+
+    /// Serialize an XPriv to base58 with this network's version bytes
+    pub fn xpriv_to_base58<K: AsRef<XPriv>>(&self, k: &K) -> Result<String, Bip32Error> {
+        let key = k.as_ref();
+        let hint = key.xkey_info.hint;
+        let version = self
+            .priv_version(hint)
+            .ok_or_else(|| Bip32Error::UnsupportedHint(self.name.to_string(), hint))?;
+
+        // The key body is network-independent, so any encoder may write it
+        let mut v = version.to_be_bytes().to_vec();
+        MainnetEncoder::write_key_details(&mut v, key)?;
+        v.push(0);
+        v.extend(key.key.to_bytes());
+        Ok(encode_b58_check(&v))
+    }
+
+    /// Serialize an XPub to base58 with this network's version bytes
+    pub fn xpub_to_base58<K: AsRef<XPub>>(&self, k: &K) -> Result<String, Bip32Error> {
+        let key = k.as_ref();
+        let hint = key.xkey_info.hint;
+        let version = self
+            .pub_version(hint)
+            .ok_or_else(|| Bip32Error::UnsupportedHint(self.name.to_string(), hint))?;
+
+        let mut v = version.to_be_bytes().to_vec();
+        MainnetEncoder::write_key_details(&mut v, key)?;
+        v.extend(key.key.to_sec1_bytes().iter());
+        Ok(encode_b58_check(&v))
+    }
+
+    /// Read an XPriv from a b58check string, requiring this network's version bytes
+    pub fn xpriv_from_base58(&self, s: &str) -> Result<XPriv, Bip32Error> {
+        let data = decode_b58_check(s)?;
+        let version = read_version(&data)?;
+        let hint = self
+            .priv_hint(version)
+            .ok_or(Bip32Error::BadXPrivVersionBytes(version.to_be_bytes()))?;
+        MainnetEncoder::read_xpriv_body(&mut &data[4..], hint)
+    }
+
+    /// Read an XPub from a b58check string, requiring this network's version bytes
+    pub fn xpub_from_base58(&self, s: &str) -> Result<XPub, Bip32Error> {
+        let data = decode_b58_check(s)?;
+        let version = read_version(&data)?;
+        let hint = self
+            .pub_hint(version)
+            .ok_or(Bip32Error::BadXPubVersionBytes(version.to_be_bytes()))?;
+        MainnetEncoder::read_xpub_body(&mut &data[4..], hint)
+    }
+}
+
+fn read_version(data: &[u8]) -> Result<u32, Bip32Error> {
+    let mut buf = [0u8; 4];
+    coins_core::io::Read::read_exact(&mut &data[..], &mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+/// Register a network for runtime lookup and detection. Fails if the name or
+/// any version bytes are already used by a known network, as detection
+/// would then be ambiguous. Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn register(network: Network) -> Result<(), Bip32Error> {
+    let mut registered = REGISTERED.write().expect("lock poisoned");
+    let conflict = BUILTIN_NETWORKS
+        .iter()
+        .chain(registered.iter())
+        .find(|known| {
+            known.name == network.name
+                || network
+                    .versions
+                    .iter()
+                    .any(|v| known.uses_version(v.xpriv) || known.uses_version(v.xpub))
+        });
+    if let Some(known) = conflict {
+        return Err(Bip32Error::NetworkConflict(known.name.to_string()));
+    }
+    registered.push(network);
+    Ok(())
+}
+
+/// All known networks. Built-in networks come first, followed by registered
+/// networks in registration order
+pub fn networks() -> Vec<Network> {
+    #[allow(unused_mut)]
+    let mut networks = BUILTIN_NETWORKS.to_vec();
+    #[cfg(feature = "std")]
+    networks.extend(REGISTERED.read().expect("lock poisoned").iter().cloned());
+    networks
+}
+
+/// Look up a known network by name
+pub fn by_name(name: &str) -> Option<Network> {
+    networks().into_iter().find(|n| n.name == name)
+}
+
+/// Find the network and hint associated with some xpriv version bytes
+pub fn detect_xpriv_version(version: u32) -> Option<(Network, Hint)> {
+    networks()
+        .into_iter()
+        .find_map(|n| n.priv_hint(version).map(|hint| (n, hint)))
+}
+
+/// Find the network and hint associated with some xpub version bytes
+pub fn detect_xpub_version(version: u32) -> Option<(Network, Hint)> {
+    networks()
+        .into_iter()
+        .find_map(|n| n.pub_hint(version).map(|hint| (n, hint)))
+}
+
+/// An extended key along with the network and hint detected while parsing it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detected<K> {
+    /// The parsed key
+    pub key: K,
+    /// The network whose version bytes the key used
+    pub network: Network,
+    /// The hint encoded by the version bytes
+    pub hint: Hint,
+}
+
+impl XPriv {
+    /// Parse an XPriv from any known network, detecting the network and hint
+    /// from its version bytes
+    pub fn from_str_detect(s: &str) -> Result<Detected<XPriv>, Bip32Error> {
+        let data = decode_b58_check(s)?;
+        let version = read_version(&data)?;
+        let (network, hint) = detect_xpriv_version(version)
+            .ok_or(Bip32Error::BadXPrivVersionBytes(version.to_be_bytes()))?;
+        let key = MainnetEncoder::read_xpriv_body(&mut &data[4..], hint)?;
+        Ok(Detected { key, network, hint })
+    }
+}
+
+impl XPub {
+    /// Parse an XPub from any known network, detecting the network and hint
+    /// from its version bytes
+    pub fn from_str_detect(s: &str) -> Result<Detected<XPub>, Bip32Error> {
+        let data = decode_b58_check(s)?;
+        let version = read_version(&data)?;
+        let (network, hint) = detect_xpub_version(version)
+            .ok_or(Bip32Error::BadXPubVersionBytes(version.to_be_bytes()))?;
+        let key = MainnetEncoder::read_xpub_body(&mut &data[4..], hint)?;
+        Ok(Detected { key, network, hint })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::enc::{Main, Test, TestnetEncoder};
+
+    const XPUB: &str = "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y";
+    const XPRV: &str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+
+    #[test]
+    fn builtins_match_compile_time_params() {
+        assert_eq!(Network::from_params::<Main>("bitcoin"), BITCOIN);
+        assert_eq!(Network::from_params::<Test>("testnet"), BITCOIN_TESTNET);
+    }
+
+    #[test]
+    fn it_detects_network_and_hint() {
+        let xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+        let xpriv = MainnetEncoder::xpriv_from_base58(XPRV).unwrap();
+
+        for network in BUILTIN_NETWORKS.iter() {
+            for v in network.versions.iter() {
+                let mut key = xpub;
+                key.xkey_info.hint = v.hint;
+                let s = network.xpub_to_base58(&key).unwrap();
+
+                let detected = XPub::from_str_detect(&s).unwrap();
+                assert_eq!(&detected.network, network);
+                assert_eq!(detected.key, xpub);
+                assert_eq!(detected.key.xkey_info.hint, detected.hint);
+                assert_eq!(network.pub_hint(v.xpub), Some(detected.hint));
+                assert_eq!(network.xpub_from_base58(&s).unwrap(), xpub);
+
+                let mut key = xpriv.clone();
+                key.xkey_info.hint = v.hint;
+                let s = network.xpriv_to_base58(&key).unwrap();
+
+                let detected = XPriv::from_str_detect(&s).unwrap();
+                assert_eq!(&detected.network, network);
+                assert_eq!(detected.key, xpriv);
+                assert_eq!(network.priv_hint(v.xpriv), Some(detected.hint));
+            }
+        }
+
+        let tpub = TestnetEncoder::xpub_to_base58(&xpub).unwrap();
+        let detected = XPub::from_str_detect(&tpub).unwrap();
+        assert_eq!(detected.network, BITCOIN_TESTNET);
+        assert_eq!(detected.hint, Hint::Legacy);
+
+        let ltub = LITECOIN.xpub_to_base58(&xpub).unwrap();
+        assert!(ltub.starts_with("Ltub"));
+        assert!(matches!(
+            BITCOIN.xpub_from_base58(&ltub),
+            Err(Bip32Error::BadXPubVersionBytes(_))
+        ));
+        assert!(matches!(
+            XPriv::from_str_detect(&ltub),
+            Err(Bip32Error::BadXPrivVersionBytes(_))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_unsupported_hints() {
+        let mut xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+        xpub.xkey_info.hint = Hint::SegWit;
+        assert!(matches!(
+            DOGECOIN.xpub_to_base58(&xpub),
+            Err(Bip32Error::UnsupportedHint(_, Hint::SegWit))
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn it_registers_networks() {
+        let custom = Network {
+            name: "custom-signet".into(),
+            versions: vec![VersionBytes {
+                hint: Hint::SegWit,
+                xpriv: 0x0a0b_0c0d,
+                xpub: 0x0a0b_0c0e,
+            }]
+            .into(),
+            wif: 0xef,
+        };
+        let mut xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+        xpub.xkey_info.hint = Hint::SegWit;
+        let s = custom.xpub_to_base58(&xpub).unwrap();
+        assert!(XPub::from_str_detect(&s).is_err());
+
+        register(custom.clone()).unwrap();
+        assert_eq!(by_name("custom-signet"), Some(custom.clone()));
+
+        let detected = XPub::from_str_detect(&s).unwrap();
+        assert_eq!(detected.network, custom);
+        assert_eq!(detected.hint, Hint::SegWit);
+
+        // duplicate names and version bytes are ambiguous
+        assert!(matches!(
+            register(custom),
+            Err(Bip32Error::NetworkConflict(_))
+        ));
+        let clash = Network {
+            name: "bitcoin-clone".into(),
+            ..BITCOIN
+        };
+        match register(clash) {
+            Err(Bip32Error::NetworkConflict(name)) => assert_eq!(name, "bitcoin"),
+            _ => panic!("expected conflict"),
+        }
+    }
+}
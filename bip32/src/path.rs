@@ -1,12 +1,20 @@
-use std::{
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
     convert::TryFrom,
-    io::{Read, Write},
     iter::{FromIterator, IntoIterator},
     slice::Iter,
     str::FromStr,
 };
 
-use coins_core::ser::ByteFormat;
+use coins_core::{
+    io::{Read, Write},
+    ser::ByteFormat,
+};
 
 use crate::{primitives::KeyFingerprint, Bip32Error, BIP32_HARDEN};
 
@@ -25,7 +33,7 @@ fn try_parse_index(s: &str) -> Result<u32, Bip32Error> {
         .map_err(|_| Bip32Error::MalformattedDerivation(s.to_owned()))
 }
 
-fn encode_index(idx: u32, harden: char) -> String {
+pub(crate) fn encode_index(idx: u32, harden: char) -> String {
     let mut s = (idx % BIP32_HARDEN).to_string();
     if idx >= BIP32_HARDEN {
         s.push(harden);
@@ -39,7 +47,7 @@ pub const fn harden_index(index: u32) -> u32 {
 }
 
 /// A Bip32 derivation path
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct DerivationPath(Vec<u32>);
 
 impl serde::Serialize for DerivationPath {
@@ -65,7 +73,7 @@ impl<'de> serde::Deserialize<'de> for DerivationPath {
 impl DerivationPath {
     #[doc(hidden)]
     pub fn custom_string(&self, root: &str, joiner: char, harden: char) -> String {
-        std::iter::once(root.to_owned())
+        core::iter::once(root.to_owned())
             .chain(self.0.iter().map(|s| encode_index(*s, harden)))
             .collect::<Vec<String>>()
             .join(&joiner.to_string())
@@ -82,17 +90,17 @@ impl DerivationPath {
     }
 
     /// Returns `True` if there are no indices in the path
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
 
     /// The number of derivations in the path
-    pub fn len(&self) -> usize {
+    pub const fn len(&self) -> usize {
         self.0.len()
     }
 
     /// Make an iterator over the path indices
-    pub fn iter(&self) -> Iter<u32> {
+    pub fn iter(&self) -> Iter<'_, u32> {
         self.0.iter()
     }
 
@@ -193,8 +201,7 @@ impl FromStr for DerivationPath {
     type Err = Bip32Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.to_owned()
-            .split('/')
+        s.split('/')
             .filter(|v| v != &"m")
             .map(try_parse_index)
             .collect::<Result<Vec<u32>, Bip32Error>>()
@@ -248,6 +255,39 @@ impl KeyDerivation {
     }
 }
 
+impl KeyDerivation {
+    /// The maximum number of path indices. Depth is serialized as a single byte in extended keys
+    pub const MAX_DEPTH: usize = 255;
+
+    /// Read a derivation serialized in exactly `length` bytes, as given by the
+    /// surrounding container (e.g. a PSBT value). The length must be a 4-byte
+    /// fingerprint followed by at most `MAX_DEPTH` 4-byte little-endian indices.
+    pub fn read_with_length<T>(reader: &mut T, length: usize) -> Result<Self, Bip32Error>
+    where
+        T: Read,
+    {
+        let depth = length.saturating_sub(4) / 4;
+        if length != 4 + 4 * depth || depth > Self::MAX_DEPTH {
+            return Err(Bip32Error::InvalidBip32Path);
+        }
+
+        let mut finger = [0u8; 4];
+        reader.read_exact(&mut finger)?;
+
+        let mut path = vec![];
+        for _ in 0..depth {
+            let mut buf = [0u8; 4];
+            reader.read_exact(&mut buf)?;
+            path.push(u32::from_le_bytes(buf));
+        }
+
+        Ok(KeyDerivation {
+            root: finger.into(),
+            path: path.into(),
+        })
+    }
+}
+
 impl ByteFormat for KeyDerivation {
     type Error = Bip32Error;
 
@@ -255,34 +295,16 @@ impl ByteFormat for KeyDerivation {
         4 + 4 * self.path.len()
     }
 
-    fn read_from<T>(_reader: &mut T) -> Result<Self, Self::Error>
+    /// Reads the remainder of the reader as a single derivation. Callers that
+    /// know the serialized length should prefer `KeyDerivation::read_with_length`
+    fn read_from<T>(reader: &mut T) -> Result<Self, Self::Error>
     where
         T: Read,
-        Self: std::marker::Sized,
+        Self: core::marker::Sized,
     {
-        unimplemented!()
-        // if limit == 0 {
-        //     return Err(SerError::RequiresLimit.into());
-        // }
-
-        // if limit > 255 {
-        //     return Err(Bip32Error::InvalidBip32Path);
-        // }
-
-        // let mut finger = [0u8; 4];
-        // reader.read_exact(&mut finger)?;
-
-        // let mut path = vec![];
-        // for _ in 0..limit {
-        //     let mut buf = [0u8; 4];
-        //     reader.read_exact(&mut buf)?;
-        //     path.push(u32::from_le_bytes(buf));
-        // }
-
-        // Ok(KeyDerivation {
-        //     root: finger.into(),
-        //     path: path.into(),
-        // })
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf)?;
+        Self::read_with_length(&mut &buf[..], buf.len())
     }
 
     fn write_to<T>(&self, writer: &mut T) -> Result<usize, Self::Error>
@@ -297,6 +319,7 @@ impl ByteFormat for KeyDerivation {
     }
 }
 
+/// Derivation path tests
 #[cfg(test)]
 pub mod test {
     use super::*;
@@ -385,7 +408,7 @@ pub mod test {
         let cases = ["//", "m/", "-", "h", "toast", "憂鬱"];
 
         for case in cases.iter() {
-            let path: Result<DerivationPath, _> = case.parse().map_err(Into::into);
+            let path: Result<DerivationPath, _> = case.parse();
             match path {
                 Ok(_) => panic!("expected an error"),
                 Err(Bip32Error::MalformattedDerivation(e)) => assert_eq!(&e, case),
@@ -394,6 +417,42 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn it_round_trips_key_derivations() {
+        let derivation = KeyDerivation {
+            root: [0xd3, 0x4d, 0xb3, 0x3f].into(),
+            path: vec![harden_index(84), harden_index(0), harden_index(0), 1, 7].into(),
+        };
+        let hex = derivation.serialize_hex();
+        assert_eq!(hex, "d34db33f5400008000000080000000800100000007000000");
+        assert_eq!(KeyDerivation::deserialize_hex(&hex).unwrap(), derivation);
+
+        let mut buf = vec![];
+        derivation.write_to(&mut buf).unwrap();
+        buf.extend([0xff; 3]);
+        let read = KeyDerivation::read_with_length(&mut &buf[..], 24).unwrap();
+        assert_eq!(read, derivation);
+    }
+
+    #[test]
+    fn it_rejects_invalid_derivation_lengths() {
+        let long = vec![0u8; 4 + 4 * 256];
+        let cases: [&[u8]; 4] = [&[], &[0; 3], &[0; 9], &long];
+        for case in cases.iter() {
+            match KeyDerivation::read_with_length(&mut &case[..], case.len()) {
+                Err(Bip32Error::InvalidBip32Path) => {}
+                other => panic!("expected InvalidBip32Path, got {:?}", other),
+            }
+        }
+
+        // the container may claim more bytes than are available
+        assert!(matches!(
+            KeyDerivation::read_with_length(&mut &[0u8; 8][..], 12),
+            Err(Bip32Error::IoError(_))
+        ));
+        assert!(KeyDerivation::read_with_length(&mut &[0u8; 4 + 4 * 255][..], 4 + 4 * 255).is_ok());
+    }
+
     #[test]
     fn it_stringifies_derivation_paths() {
         let cases = [
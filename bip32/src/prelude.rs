@@ -1,7 +1,12 @@
+pub use crate::bip44::{Bip44Path, Chain, PathPolicy, PolicyViolation, Purpose};
 pub use crate::derived::{DerivedKey, DerivedPubkey, DerivedXPriv, DerivedXPub};
 pub use crate::enc::{MainnetEncoder, TestnetEncoder, XKeyEncoder};
+pub use crate::message::{MessageAddressType, MessageSignature};
+pub use crate::network::Network;
 pub use crate::path::KeyDerivation;
 pub use crate::primitives::*;
+pub use crate::schnorr::{SchnorrSignature, SchnorrSigningKey, XOnlyPubkey};
+pub use crate::wif::Privkey;
 pub use crate::xkeys::{Parent, XPriv, XPub};
 pub use crate::Bip32Error;
 
@@ -1,11 +1,13 @@
 use crate::Bip32Error;
+use coins_core::io::{Read, Write};
 use coins_core::ser::ByteFormat;
-use std::io::{Read, Write};
+use k256::elliptic_curve::subtle::{Choice, ConstantTimeEq};
 
-/// We treat the bip32 xpub bip49 ypub and bip84 zpub convention as a hint regarding address type.
+/// We treat the bip32 xpub bip49 ypub and bip84 zpub convention (and its SLIP-132 multisig
+/// extensions) as a hint regarding address type.
 /// Downstream crates are free to follow or ignore these hints when generating addresses from
 /// extended keys.
-#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum Hint {
     /// Standard Bip32 hint
     Legacy,
@@ -13,6 +15,26 @@ pub enum Hint {
     Compatibility,
     /// Bip32 + Bip84 hint for Native SegWit
     SegWit,
+    /// Bip32 + Bip86 hint for single-key Taproot
+    Taproot,
+    /// SLIP-132 hint for multisig Witness-via-P2SH (P2SH-P2WSH)
+    CompatibilityMultisig,
+    /// SLIP-132 hint for multisig Native SegWit (P2WSH)
+    SegWitMultisig,
+}
+
+impl Hint {
+    /// All hints, in the order used to resolve version bytes shared by
+    /// several hints. E.g. BIP86 keys use the BIP32 version bytes, so those
+    /// bytes resolve to `Legacy`.
+    pub const ALL: [Hint; 6] = [
+        Hint::Legacy,
+        Hint::Compatibility,
+        Hint::SegWit,
+        Hint::CompatibilityMultisig,
+        Hint::SegWitMultisig,
+        Hint::Taproot,
+    ];
 }
 
 /// A 4-byte key fingerprint
@@ -35,7 +57,7 @@ impl ByteFormat for KeyFingerprint {
     fn read_from<R>(reader: &mut R) -> Result<Self, Self::Error>
     where
         R: Read,
-        Self: std::marker::Sized,
+        Self: core::marker::Sized,
     {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
@@ -57,23 +79,42 @@ impl KeyFingerprint {
     }
 }
 
-impl std::fmt::Debug for KeyFingerprint {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for KeyFingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("KeyFingerprint {:x?}", self.0))
     }
 }
 
-/// A 32-byte chain code
-#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+/// A 32-byte chain code. Equality is checked in constant time.
+///
+/// The chain code is `Copy`, as are `XKeyInfo` and `XPub`, so its copies are
+/// not tracked. Only the copy owned by an `XPriv` is zeroized, when the
+/// `XPriv` is dropped. Copies taken from it, e.g. into an `XPub` or a bare
+/// `XKeyInfo`, are not wiped, and should be zeroized by their holder if the
+/// chain code is sensitive.
+#[derive(Eq, Debug, Clone, Copy, zeroize::Zeroize)]
 pub struct ChainCode(pub [u8; 32]);
 
+impl ConstantTimeEq for ChainCode {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+impl PartialEq for ChainCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
 impl From<[u8; 32]> for ChainCode {
     fn from(v: [u8; 32]) -> Self {
         Self(v)
     }
 }
 
-/// Info associated with an extended key
+/// Info associated with an extended key. Its chain code is not zeroized on
+/// drop, see `ChainCode`.
 #[derive(Copy, Clone, Debug)]
 pub struct XKeyInfo {
     /// The key depth in the HD tree
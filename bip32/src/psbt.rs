@@ -0,0 +1,240 @@
+use alloc::{string::String, vec, vec::Vec};
+use coins_core::io::{Read, Write};
+
+use coins_core::ser::{read_compact_int, write_compact_int, ByteFormat};
+use k256::ecdsa;
+
+use crate::{
+    derived::{DerivedKey, DerivedPubkey, DerivedXPub},
+    enc::XKeyEncoder,
+    path::KeyDerivation,
+    primitives::XKeyInfo,
+    Bip32Error,
+};
+
+/// The PSBT global key type for an extended pubkey and its origin
+pub const PSBT_GLOBAL_XPUB: u8 = 0x01;
+/// The PSBT input key type for a pubkey and its origin
+pub const PSBT_IN_BIP32_DERIVATION: u8 = 0x06;
+/// The PSBT output key type for a pubkey and its origin
+pub const PSBT_OUT_BIP32_DERIVATION: u8 = 0x02;
+
+/// The length of a BIP32-serialized extended key
+const XKEY_LENGTH: usize = 78;
+
+fn malformatted(msg: impl Into<String>) -> Bip32Error {
+    Bip32Error::MalformattedPsbtRecord(msg.into())
+}
+
+fn read_exact_vec<R: Read>(reader: &mut R, length: u64) -> Result<Vec<u8>, Bip32Error> {
+    let mut buf = vec![];
+    reader.take(length).read_to_end(&mut buf)?;
+    if (buf.len() as u64) != length {
+        return Err(malformatted("record is truncated"));
+    }
+    Ok(buf)
+}
+
+/// Read a key-value record, returning the key type, key data and value
+fn read_record<R: Read>(reader: &mut R) -> Result<(u8, Vec<u8>, Vec<u8>), Bip32Error> {
+    let key_length = read_compact_int(reader)?;
+    if key_length == 0 {
+        return Err(malformatted("empty key"));
+    }
+    let key = read_exact_vec(reader, key_length)?;
+    let value_length = read_compact_int(reader)?;
+    let value = read_exact_vec(reader, value_length)?;
+    Ok((key[0], key[1..].to_vec(), value))
+}
+
+fn write_record<W: Write>(
+    writer: &mut W,
+    key_type: u8,
+    key_data: &[u8],
+    origin: &KeyDerivation,
+) -> Result<usize, Bip32Error> {
+    if origin.path.len() > KeyDerivation::MAX_DEPTH {
+        return Err(Bip32Error::InvalidBip32Path);
+    }
+    let mut written = write_compact_int(writer, 1 + key_data.len() as u64)?;
+    written += writer.write(&[key_type])?;
+    written += writer.write(key_data)?;
+    written += write_compact_int(writer, origin.serialized_length() as u64)?;
+    written += origin.write_to(writer)?;
+    Ok(written)
+}
+
+fn read_origin(value: &[u8]) -> Result<KeyDerivation, Bip32Error> {
+    KeyDerivation::read_with_length(&mut &value[..], value.len())
+}
+
+/// Write a `PSBT_IN_BIP32_DERIVATION` or `PSBT_OUT_BIP32_DERIVATION` record
+pub fn write_bip32_derivation<W: Write>(
+    writer: &mut W,
+    key_type: u8,
+    pubkey: &DerivedPubkey,
+) -> Result<usize, Bip32Error> {
+    write_record(
+        writer,
+        key_type,
+        &pubkey.to_sec1_bytes(),
+        pubkey.derivation(),
+    )
+}
+
+/// Read a `PSBT_IN_BIP32_DERIVATION` or `PSBT_OUT_BIP32_DERIVATION` record,
+/// requiring the given key type.
+pub fn read_bip32_derivation<R: Read>(
+    reader: &mut R,
+    key_type: u8,
+) -> Result<DerivedPubkey, Bip32Error> {
+    let (found, key_data, value) = read_record(reader)?;
+    if found != key_type {
+        return Err(Bip32Error::UnexpectedPsbtKeyType(found));
+    }
+    if key_data.len() != 33 {
+        return Err(malformatted("expected a 33-byte compressed pubkey"));
+    }
+    let key = ecdsa::VerifyingKey::from_sec1_bytes(&key_data)?;
+    Ok(DerivedPubkey::new(key, read_origin(&value)?))
+}
+
+/// Write a `PSBT_GLOBAL_XPUB` record, using the encoder's version bytes
+pub fn write_global_xpub<E, W>(writer: &mut W, xpub: &DerivedXPub) -> Result<usize, Bip32Error>
+where
+    E: XKeyEncoder,
+    W: Write,
+{
+    let info: &XKeyInfo = xpub.as_ref();
+    if info.depth as usize != xpub.derivation().path.len() {
+        return Err(Bip32Error::InvalidBip32Path);
+    }
+    let mut key_data = vec![];
+    E::write_xpub(&mut key_data, xpub)?;
+    write_record(writer, PSBT_GLOBAL_XPUB, &key_data, xpub.derivation())
+}
+
+/// Read a `PSBT_GLOBAL_XPUB` record. The number of path indices must match
+/// the depth of the extended key.
+pub fn read_global_xpub<E, R>(reader: &mut R) -> Result<DerivedXPub, Bip32Error>
+where
+    E: XKeyEncoder,
+    R: Read,
+{
+    let (found, key_data, value) = read_record(reader)?;
+    if found != PSBT_GLOBAL_XPUB {
+        return Err(Bip32Error::UnexpectedPsbtKeyType(found));
+    }
+    if key_data.len() != XKEY_LENGTH {
+        return Err(malformatted("expected a 78-byte extended pubkey"));
+    }
+    let xpub = E::read_xpub(&mut &key_data[..])?;
+    let origin = read_origin(&value)?;
+    if xpub.xkey_info.depth as usize != origin.path.len() {
+        return Err(Bip32Error::InvalidBip32Path);
+    }
+    Ok(DerivedXPub::new(xpub, origin))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        enc::{MainnetEncoder, TestnetEncoder},
+        path::harden_index,
+        xkeys::Parent,
+    };
+
+    // BIP32 test vector 1, chain m/0H
+    const XPUB: &str = "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw";
+
+    #[test]
+    fn it_round_trips_bip32_derivations() {
+        let xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+        let origin = KeyDerivation {
+            root: [0x34, 0x42, 0x19, 0x3e].into(),
+            path: vec![harden_index(0)].into(),
+        };
+        let pubkey = DerivedPubkey::new(xpub.key, origin.clone());
+
+        let mut buf = vec![];
+        let written = write_bip32_derivation(&mut buf, PSBT_IN_BIP32_DERIVATION, &pubkey).unwrap();
+        assert_eq!(written, buf.len());
+        assert_eq!(
+            hex::encode(&buf),
+            format!(
+                "2206{}083442193e00000080",
+                hex::encode(pubkey.to_sec1_bytes())
+            )
+        );
+
+        let read = read_bip32_derivation(&mut &buf[..], PSBT_IN_BIP32_DERIVATION).unwrap();
+        assert_eq!(read.to_sec1_bytes(), pubkey.to_sec1_bytes());
+        assert_eq!(read.derivation(), &origin);
+
+        assert!(matches!(
+            read_bip32_derivation(&mut &buf[..], PSBT_OUT_BIP32_DERIVATION),
+            Err(Bip32Error::UnexpectedPsbtKeyType(PSBT_IN_BIP32_DERIVATION))
+        ));
+        assert!(matches!(
+            read_bip32_derivation(&mut &buf[..buf.len() - 1], PSBT_IN_BIP32_DERIVATION),
+            Err(Bip32Error::MalformattedPsbtRecord(_))
+        ));
+
+        // value lengths that are not a fingerprint plus whole indices
+        let mut bad = buf[..35].to_vec();
+        bad.extend([0x06, 0x34, 0x42, 0x19, 0x3e, 0x00, 0x00]);
+        assert!(matches!(
+            read_bip32_derivation(&mut &bad[..], PSBT_IN_BIP32_DERIVATION),
+            Err(Bip32Error::InvalidBip32Path)
+        ));
+    }
+
+    #[test]
+    fn it_round_trips_global_xpubs() {
+        let xpub = MainnetEncoder::xpub_from_base58(XPUB).unwrap();
+        let derived = DerivedXPub::new(
+            xpub,
+            KeyDerivation {
+                root: [0x34, 0x42, 0x19, 0x3e].into(),
+                path: vec![harden_index(0)].into(),
+            },
+        );
+
+        let mut buf = vec![];
+        write_global_xpub::<MainnetEncoder, _>(&mut buf, &derived).unwrap();
+        assert_eq!(&buf[..2], &[0x4f, PSBT_GLOBAL_XPUB]);
+        assert_eq!(&buf[2..6], &[0x04, 0x88, 0xb2, 0x1e]);
+
+        let read = read_global_xpub::<MainnetEncoder, _>(&mut &buf[..]).unwrap();
+        assert_eq!(read, derived);
+
+        // a tpub record is rejected by the mainnet decoder
+        let mut tbuf = vec![];
+        write_global_xpub::<TestnetEncoder, _>(&mut tbuf, &derived).unwrap();
+        assert!(read_global_xpub::<MainnetEncoder, _>(&mut &tbuf[..]).is_err());
+        assert_eq!(
+            read_global_xpub::<TestnetEncoder, _>(&mut &tbuf[..]).unwrap(),
+            derived
+        );
+
+        // the path must match the key depth
+        let child = derived.derive_child(3).unwrap();
+        let mismatched = DerivedXPub::new(
+            *AsRef::<crate::xkeys::XPub>::as_ref(&child),
+            derived.derivation().clone(),
+        );
+        assert!(matches!(
+            write_global_xpub::<MainnetEncoder, _>(&mut vec![], &mismatched),
+            Err(Bip32Error::InvalidBip32Path)
+        ));
+        let mut bad = buf.clone();
+        let last = bad.len() - 9;
+        bad[last] = 0x04;
+        bad.truncate(bad.len() - 4);
+        assert!(matches!(
+            read_global_xpub::<MainnetEncoder, _>(&mut &bad[..]),
+            Err(Bip32Error::InvalidBip32Path)
+        ));
+    }
+}
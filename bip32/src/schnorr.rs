@@ -0,0 +1,217 @@
+use k256::{
+    ecdsa,
+    elliptic_curve::{ff::PrimeField, sec1::ToEncodedPoint},
+    NonZeroScalar, ProjectivePoint, PublicKey, Scalar,
+};
+use sha2::{Digest, Sha256};
+
+use crate::Bip32Error;
+
+pub use k256::schnorr::{
+    Signature as SchnorrSignature, SigningKey as SchnorrSigningKey, VerifyingKey as XOnlyPubkey,
+};
+
+/// The BIP341 tag used to hash the internal key and merkle root into a tweak
+pub const TAP_TWEAK_TAG: &[u8] = b"TapTweak";
+
+/// Compute a BIP340 tagged hash: `SHA256(SHA256(tag) || SHA256(tag) || data)`
+pub fn tagged_hash(tag: &[u8], data: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new()
+        .chain_update(tag_hash)
+        .chain_update(tag_hash)
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+/// Convert an ECDSA verifying key to its BIP340 x-only representation
+pub fn x_only(key: &ecdsa::VerifyingKey) -> XOnlyPubkey {
+    let point = key.to_encoded_point(true);
+    XOnlyPubkey::from_bytes(&point.as_bytes()[1..]).expect("x coordinate of a valid point")
+}
+
+/// Convert an ECDSA signing key to a BIP340 signing key. The BIP340 key is
+/// negated if necessary, so that its public key has an even y coordinate.
+pub fn schnorr_key(key: &ecdsa::SigningKey) -> SchnorrSigningKey {
+    SchnorrSigningKey::from(*key.as_nonzero_scalar())
+}
+
+/// Compute the BIP341 tweak `hash_TapTweak(P || merkle_root)`. If there is no
+/// script tree, the merkle root is omitted.
+pub fn tap_tweak_hash(internal_key: &XOnlyPubkey, merkle_root: Option<&[u8; 32]>) -> [u8; 32] {
+    let mut data = internal_key.to_bytes().to_vec();
+    if let Some(root) = merkle_root {
+        data.extend(root);
+    }
+    tagged_hash(TAP_TWEAK_TAG, &data)
+}
+
+fn tweak_scalar(
+    internal_key: &XOnlyPubkey,
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<Scalar, Bip32Error> {
+    let tweak = tap_tweak_hash(internal_key, merkle_root);
+    Option::from(Scalar::from_repr(tweak.into())).ok_or(Bip32Error::BadTweak)
+}
+
+/// Apply the BIP341 taproot tweak to an internal key. Returns the x-only
+/// output key, and `true` if the full output key has an odd y coordinate.
+/// The parity is needed when building script-path control blocks.
+pub fn tweak_pubkey(
+    internal_key: &XOnlyPubkey,
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<(XOnlyPubkey, bool), Bip32Error> {
+    let tweak = tweak_scalar(internal_key, merkle_root)?;
+
+    let point =
+        ProjectivePoint::GENERATOR * tweak + ProjectivePoint::from(*internal_key.as_affine());
+    let output = PublicKey::from_affine(point.to_affine())?;
+
+    let encoded = output.to_encoded_point(true);
+    let odd = encoded.as_bytes()[0] == 0x03;
+    Ok((XOnlyPubkey::from_bytes(&encoded.as_bytes()[1..])?, odd))
+}
+
+/// Apply the BIP341 taproot tweak to a signing key. The resulting key signs
+/// for the output key produced by `tweak_pubkey`.
+pub fn tweak_signing_key(
+    key: &SchnorrSigningKey,
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<SchnorrSigningKey, Bip32Error> {
+    let tweak = tweak_scalar(key.verifying_key(), merkle_root)?;
+    let tweaked: Option<NonZeroScalar> =
+        NonZeroScalar::new(tweak + key.as_nonzero_scalar().as_ref()).into();
+    let tweaked = tweaked.ok_or(Bip32Error::BadTweak)?;
+    Ok(SchnorrSigningKey::from(tweaked))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        derived::DerivedXPriv,
+        enc::{MainnetEncoder, XKeyEncoder},
+        primitives::{Hint, XKeyInfo},
+        xkeys::{Parent, XPriv},
+    };
+
+    fn xpriv_from_secret(secret: &str) -> XPriv {
+        let secret = hex::decode(secret).unwrap();
+        XPriv::new(
+            ecdsa::SigningKey::from_slice(&secret).unwrap(),
+            XKeyInfo {
+                depth: 0,
+                parent: [0u8; 4].into(),
+                index: 0,
+                chain_code: [0u8; 32].into(),
+                hint: Hint::Legacy,
+            },
+        )
+    }
+
+    #[test]
+    fn bip340_vectors() {
+        // (secret key, x-only pubkey, aux rand, message, signature)
+        let cases = [
+            (
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0",
+            ),
+            (
+                "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+                "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89",
+                "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a",
+            ),
+        ];
+
+        for (secret, pubkey, aux, msg, sig) in cases.iter() {
+            let xpriv = xpriv_from_secret(secret);
+            let xpub = xpriv.verify_key();
+            let mut aux_rand = [0u8; 32];
+            aux_rand.copy_from_slice(&hex::decode(aux).unwrap());
+            let msg = hex::decode(msg).unwrap();
+
+            assert_eq!(hex::encode(xpub.x_only_pubkey().to_bytes()), *pubkey);
+
+            let signature = xpriv.sign_schnorr(&msg, &aux_rand).unwrap();
+            assert_eq!(hex::encode(signature.to_bytes()), *sig);
+            xpub.verify_schnorr(&msg, &signature).unwrap();
+            assert!(xpub.verify_schnorr(&[0u8; 32][..1], &signature).is_err());
+        }
+    }
+
+    #[test]
+    fn bip341_tweak_vectors() {
+        // (internal key, merkle root, tweak, output key)
+        let cases = [
+            (
+                "d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+                None,
+                "b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70",
+                "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+            ),
+            (
+                "187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+                Some("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"),
+                "cbd8679ba636c1110ea247542cfbd964131a6be84f873f7f3b62a777528ed001",
+                "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+            ),
+        ];
+
+        for (internal, root, tweak, output) in cases.iter() {
+            let internal = XOnlyPubkey::from_bytes(&hex::decode(internal).unwrap()).unwrap();
+            let root = root.map(|r| {
+                let mut buf = [0u8; 32];
+                buf.copy_from_slice(&hex::decode(r).unwrap());
+                buf
+            });
+
+            assert_eq!(
+                hex::encode(tap_tweak_hash(&internal, root.as_ref())),
+                *tweak
+            );
+            let (output_key, _) = tweak_pubkey(&internal, root.as_ref()).unwrap();
+            assert_eq!(hex::encode(output_key.to_bytes()), *output);
+        }
+    }
+
+    #[test]
+    fn it_signs_taproot_key_path_spends() {
+        let xpriv_str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi";
+        let xpriv = MainnetEncoder::xpriv_from_base58(xpriv_str).unwrap();
+        let root = DerivedXPriv::new(
+            xpriv,
+            crate::path::KeyDerivation {
+                root: [0u8; 4].into(),
+                path: vec![].into(),
+            },
+        );
+        let merkle_root = [7u8; 32];
+        let sighash = [42u8; 32];
+
+        for i in 0..8 {
+            let child = root.derive_child(i).unwrap();
+            let child_pub = child.verify_key();
+
+            for merkle_root in [None, Some(&merkle_root)].iter() {
+                let tweaked = child.taproot_signing_key(*merkle_root).unwrap();
+                let output_key = child_pub.taproot_output_key(*merkle_root).unwrap();
+                assert_eq!(tweaked.verifying_key(), &output_key);
+
+                let sig = child
+                    .sign_taproot(&sighash, *merkle_root, &[0u8; 32])
+                    .unwrap();
+                child_pub
+                    .verify_taproot(&sighash, *merkle_root, &sig)
+                    .unwrap();
+                assert!(child_pub.verify_schnorr(&sighash, &sig).is_err());
+            }
+        }
+    }
+}
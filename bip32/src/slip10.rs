@@ -0,0 +1,968 @@
+use alloc::{vec, vec::Vec};
+use coins_core::hashes::{Hash160, Hash160Digest, MarkedDigest, MarkedDigestOutput};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    derived::DerivedKey,
+    path::KeyDerivation,
+    primitives::{ChainCode, Hint, KeyFingerprint, XKeyInfo},
+    xkeys::Parent,
+    Bip32Error, BIP32_HARDEN,
+};
+
+fn hmac_512(key: &[u8], data: &[u8]) -> ([u8; 32], ChainCode) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("key length is ok");
+    mac.update(data);
+    let result = mac.finalize().into_bytes();
+
+    let mut left = [0u8; 32];
+    left.copy_from_slice(&result[..32]);
+    let mut right = [0u8; 32];
+    right.copy_from_slice(&result[32..]);
+
+    (left, ChainCode(right))
+}
+
+/// A curve usable for SLIP-10 hierarchical derivation.
+///
+/// SLIP-10 generalizes BIP32 to curves other than secp256k1. Each curve has
+/// its own master key HMAC key, and may restrict derivation to hardened
+/// indices only.
+pub trait Slip10Curve: Copy + core::fmt::Debug {
+    /// The HMAC key used to generate the master node from a seed.
+    const SEED: &'static [u8];
+
+    /// `false` if the curve only supports hardened derivation. When `false`,
+    /// public parent to public child derivation is impossible.
+    const PUBLIC_DERIVATION: bool;
+
+    /// The private key type.
+    type SigningKey: Clone;
+
+    /// The public key type.
+    type VerifyingKey: Copy + PartialEq;
+
+    /// Interpret the left half of an HMAC output as a master private key.
+    /// Returns `None` if the value is not a valid key, in which case the
+    /// caller retries with a new HMAC.
+    fn master_key(il: &[u8; 32]) -> Option<Self::SigningKey>;
+
+    /// Derive a child private key from the parent and the left half of an
+    /// HMAC output. Returns `None` if the result is not a valid key.
+    fn child_key(parent: &Self::SigningKey, il: &[u8; 32]) -> Option<Self::SigningKey>;
+
+    /// Derive a child public key from the parent and the left half of an HMAC
+    /// output. Returns `None` if the result is not a valid key, or if the
+    /// curve does not support public derivation.
+    fn child_pubkey(parent: &Self::VerifyingKey, il: &[u8; 32]) -> Option<Self::VerifyingKey>;
+
+    /// Serialize the private key to its 32-byte representation.
+    fn secret_bytes(key: &Self::SigningKey) -> [u8; 32];
+
+    /// Derive the public key of a private key.
+    fn verifying_key(key: &Self::SigningKey) -> Self::VerifyingKey;
+
+    /// Serialize the public key to the 33-byte form used as HMAC input and
+    /// for fingerprints. ed25519 keys are prefixed with a `0x00` byte.
+    fn serialize_pubkey(key: &Self::VerifyingKey) -> [u8; 33];
+}
+
+macro_rules! weierstrass_curve {
+    (
+        $(#[$outer:meta])*
+        $name:ident, $krate:ident, $seed:expr
+    ) => {
+        $(#[$outer])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl Slip10Curve for $name {
+            const SEED: &'static [u8] = $seed;
+            const PUBLIC_DERIVATION: bool = true;
+
+            type SigningKey = $krate::ecdsa::SigningKey;
+            type VerifyingKey = $krate::ecdsa::VerifyingKey;
+
+            fn master_key(il: &[u8; 32]) -> Option<Self::SigningKey> {
+                $krate::ecdsa::SigningKey::from_bytes(&(*il).into()).ok()
+            }
+
+            fn child_key(parent: &Self::SigningKey, il: &[u8; 32]) -> Option<Self::SigningKey> {
+                use $krate::elliptic_curve::ff::PrimeField;
+
+                let tweak: $krate::Scalar = Option::from($krate::Scalar::from_repr((*il).into()))?;
+                let tweaked = tweak + parent.as_nonzero_scalar().as_ref();
+                let key: $krate::NonZeroScalar =
+                    Option::from($krate::NonZeroScalar::new(tweaked))?;
+                Some(key.into())
+            }
+
+            fn child_pubkey(
+                parent: &Self::VerifyingKey,
+                il: &[u8; 32],
+            ) -> Option<Self::VerifyingKey> {
+                use $krate::elliptic_curve::ff::PrimeField;
+
+                let tweak: $krate::Scalar = Option::from($krate::Scalar::from_repr((*il).into()))?;
+                let point = $krate::ProjectivePoint::GENERATOR * tweak
+                    + $krate::ProjectivePoint::from(*parent.as_affine());
+                $krate::ecdsa::VerifyingKey::from_affine(point.to_affine()).ok()
+            }
+
+            fn secret_bytes(key: &Self::SigningKey) -> [u8; 32] {
+                key.to_bytes().into()
+            }
+
+            fn verifying_key(key: &Self::SigningKey) -> Self::VerifyingKey {
+                *key.verifying_key()
+            }
+
+            fn serialize_pubkey(key: &Self::VerifyingKey) -> [u8; 33] {
+                let mut data = [0u8; 33];
+                data.copy_from_slice(key.to_encoded_point(true).as_bytes());
+                data
+            }
+        }
+    };
+}
+
+weierstrass_curve!(
+    /// The secp256k1 curve. SLIP-10 derivation on secp256k1 matches BIP32,
+    /// except for the handling of (astronomically unlikely) invalid children.
+    Secp256k1,
+    k256,
+    b"Bitcoin seed"
+);
+
+#[cfg(feature = "nist256p1")]
+weierstrass_curve!(
+    /// The NIST P-256 curve, also known as secp256r1 or prime256v1.
+    Nist256p1,
+    p256,
+    b"Nist256p1 seed"
+);
+
+/// The ed25519 curve. Only hardened derivation is supported.
+#[cfg(feature = "ed25519")]
+#[derive(Debug, Clone, Copy)]
+pub struct Ed25519;
+
+#[cfg(feature = "ed25519")]
+impl Slip10Curve for Ed25519 {
+    const SEED: &'static [u8] = b"ed25519 seed";
+    const PUBLIC_DERIVATION: bool = false;
+
+    type SigningKey = ed25519_dalek::SigningKey;
+    type VerifyingKey = ed25519_dalek::VerifyingKey;
+
+    fn master_key(il: &[u8; 32]) -> Option<Self::SigningKey> {
+        Some(ed25519_dalek::SigningKey::from_bytes(il))
+    }
+
+    fn child_key(_parent: &Self::SigningKey, il: &[u8; 32]) -> Option<Self::SigningKey> {
+        Some(ed25519_dalek::SigningKey::from_bytes(il))
+    }
+
+    fn child_pubkey(_parent: &Self::VerifyingKey, _il: &[u8; 32]) -> Option<Self::VerifyingKey> {
+        None
+    }
+
+    fn secret_bytes(key: &Self::SigningKey) -> [u8; 32] {
+        key.to_bytes()
+    }
+
+    fn verifying_key(key: &Self::SigningKey) -> Self::VerifyingKey {
+        key.verifying_key()
+    }
+
+    fn serialize_pubkey(key: &Self::VerifyingKey) -> [u8; 33] {
+        let mut data = [0u8; 33];
+        data[1..].copy_from_slice(key.as_bytes());
+        data
+    }
+}
+
+/// A SLIP-10 extended private key over curve `C`.
+pub struct Slip10XPriv<C: Slip10Curve> {
+    pub(crate) key: C::SigningKey,
+    pub(crate) xkey_info: XKeyInfo,
+}
+
+/// An ed25519 SLIP-10 extended private key
+#[cfg(feature = "ed25519")]
+pub type Ed25519XPriv = Slip10XPriv<Ed25519>;
+
+/// A NIST P-256 SLIP-10 extended private key
+#[cfg(feature = "nist256p1")]
+pub type Nist256p1XPriv = Slip10XPriv<Nist256p1>;
+
+impl<C: Slip10Curve> Clone for Slip10XPriv<C> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            xkey_info: self.xkey_info,
+        }
+    }
+}
+
+impl<C: Slip10Curve> Drop for Slip10XPriv<C> {
+    fn drop(&mut self) {
+        self.xkey_info.chain_code.zeroize();
+    }
+}
+
+impl<C: Slip10Curve> PartialEq for Slip10XPriv<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.fingerprint() == other.fingerprint() && self.xkey_info == other.xkey_info
+    }
+}
+
+impl<C: Slip10Curve> core::fmt::Debug for Slip10XPriv<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Slip10XPriv")
+            .field("key fingerprint", &self.fingerprint())
+            .field("key info", &self.xkey_info)
+            .finish()
+    }
+}
+
+impl<C: Slip10Curve> AsRef<XKeyInfo> for Slip10XPriv<C> {
+    fn as_ref(&self) -> &XKeyInfo {
+        &self.xkey_info
+    }
+}
+
+impl<C: Slip10Curve> Slip10XPriv<C> {
+    /// Instantiate a new extended private key.
+    pub const fn new(key: C::SigningKey, xkey_info: XKeyInfo) -> Self {
+        Self { key, xkey_info }
+    }
+
+    /// Generate a root node from some seed data, using the curve's SLIP-10
+    /// HMAC key.
+    ///
+    /// # Important:
+    ///
+    /// Use a seed of AT LEAST 128 bits.
+    pub fn root_from_seed(data: &[u8], hint: Option<Hint>) -> Result<Self, Bip32Error> {
+        if data.len() < 16 {
+            return Err(Bip32Error::SeedTooShort);
+        }
+
+        let (mut il, mut chain_code) = hmac_512(C::SEED, data);
+        let key = loop {
+            if let Some(key) = C::master_key(&il) {
+                break key;
+            }
+            // SLIP-10: on an invalid master key, rehash the full HMAC output
+            let mut i = [0u8; 64];
+            i[..32].copy_from_slice(&il);
+            i[32..].copy_from_slice(&chain_code.0);
+            (il, chain_code) = hmac_512(C::SEED, &i);
+        };
+
+        Ok(Self {
+            key,
+            xkey_info: XKeyInfo {
+                depth: 0,
+                parent: KeyFingerprint([0u8; 4]),
+                index: 0,
+                chain_code,
+                hint: hint.unwrap_or(Hint::SegWit),
+            },
+        })
+    }
+
+    /// Derive the associated extended public key
+    pub fn verify_key(&self) -> Slip10XPub<C> {
+        Slip10XPub {
+            key: C::verifying_key(&self.key),
+            xkey_info: self.xkey_info,
+        }
+    }
+
+    /// The fingerprint is the first 4 bytes of the HASH160 of the serialized
+    /// public key.
+    pub fn fingerprint(&self) -> KeyFingerprint {
+        self.verify_key().fingerprint()
+    }
+
+    /// Return a reference to the underlying signing key
+    pub const fn signing_key(&self) -> &C::SigningKey {
+        &self.key
+    }
+
+    /// Return the 32-byte private key
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        C::secret_bytes(&self.key)
+    }
+
+    /// Return the chain code
+    pub const fn chain_code(&self) -> ChainCode {
+        self.xkey_info.chain_code
+    }
+}
+
+impl<C: Slip10Curve> Parent for Slip10XPriv<C> {
+    fn derive_child(&self, index: u32) -> Result<Self, Bip32Error> {
+        let hardened = index >= BIP32_HARDEN;
+        if !hardened && !C::PUBLIC_DERIVATION {
+            return Err(Bip32Error::NonHardenedDerivationFailed);
+        }
+
+        let mut data = Zeroizing::new(Vec::with_capacity(37));
+        if hardened {
+            data.push(0);
+            data.extend(Zeroizing::new(C::secret_bytes(&self.key)).iter());
+        } else {
+            data.extend(C::serialize_pubkey(&C::verifying_key(&self.key)));
+        }
+        data.extend(index.to_be_bytes());
+
+        let (mut il, mut chain_code) = hmac_512(&self.xkey_info.chain_code.0, &data);
+        let key = loop {
+            if let Some(key) = C::child_key(&self.key, &il) {
+                break key;
+            }
+            // SLIP-10: on an invalid child, retry with 0x01 || IR || index
+            let mut data = Zeroizing::new(Vec::with_capacity(37));
+            data.push(1);
+            data.extend(chain_code.0);
+            data.extend(index.to_be_bytes());
+            (il, chain_code) = hmac_512(&self.xkey_info.chain_code.0, &data);
+        };
+        il.zeroize();
+
+        Ok(Self {
+            key,
+            xkey_info: XKeyInfo {
+                depth: self.xkey_info.depth + 1,
+                parent: self.fingerprint(),
+                index,
+                chain_code,
+                hint: self.xkey_info.hint,
+            },
+        })
+    }
+}
+
+/// A SLIP-10 extended public key over curve `C`.
+pub struct Slip10XPub<C: Slip10Curve> {
+    pub(crate) key: C::VerifyingKey,
+    pub(crate) xkey_info: XKeyInfo,
+}
+
+/// An ed25519 SLIP-10 extended public key
+#[cfg(feature = "ed25519")]
+pub type Ed25519XPub = Slip10XPub<Ed25519>;
+
+/// A NIST P-256 SLIP-10 extended public key
+#[cfg(feature = "nist256p1")]
+pub type Nist256p1XPub = Slip10XPub<Nist256p1>;
+
+impl<C: Slip10Curve> Copy for Slip10XPub<C> {}
+
+impl<C: Slip10Curve> Clone for Slip10XPub<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Slip10Curve> PartialEq for Slip10XPub<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<C: Slip10Curve> core::fmt::Debug for Slip10XPub<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Slip10XPub")
+            .field("public key", &self.to_bytes())
+            .field("key fingerprint", &self.fingerprint())
+            .field("key info", &self.xkey_info)
+            .finish()
+    }
+}
+
+impl<C: Slip10Curve> AsRef<XKeyInfo> for Slip10XPub<C> {
+    fn as_ref(&self) -> &XKeyInfo {
+        &self.xkey_info
+    }
+}
+
+impl<C: Slip10Curve> Slip10XPub<C> {
+    /// Instantiate a new extended public key
+    pub const fn new(key: C::VerifyingKey, xkey_info: XKeyInfo) -> Self {
+        Self { key, xkey_info }
+    }
+
+    /// Return a reference to the underlying verifying key
+    pub const fn verifying_key(&self) -> &C::VerifyingKey {
+        &self.key
+    }
+
+    /// Return the 33-byte SLIP-10 serialization of the public key
+    pub fn to_bytes(&self) -> [u8; 33] {
+        C::serialize_pubkey(&self.key)
+    }
+
+    /// Return the bitcoin HASH160 of the serialized public key
+    pub fn pubkey_hash160(&self) -> Hash160Digest {
+        Hash160::digest_marked(&self.to_bytes())
+    }
+
+    /// The fingerprint is the first 4 bytes of the HASH160 of the serialized
+    /// public key.
+    pub fn fingerprint(&self) -> KeyFingerprint {
+        let digest = self.pubkey_hash160();
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(&digest.as_slice()[..4]);
+        buf.into()
+    }
+
+    /// Return the chain code
+    pub const fn chain_code(&self) -> ChainCode {
+        self.xkey_info.chain_code
+    }
+}
+
+impl<C: Slip10Curve> Parent for Slip10XPub<C> {
+    fn derive_child(&self, index: u32) -> Result<Self, Bip32Error> {
+        if index >= BIP32_HARDEN {
+            return Err(Bip32Error::HardenedDerivationFailed);
+        }
+        if !C::PUBLIC_DERIVATION {
+            return Err(Bip32Error::NonHardenedDerivationFailed);
+        }
+
+        let mut data = vec![];
+        data.extend(self.to_bytes());
+        data.extend(index.to_be_bytes());
+
+        let (mut il, mut chain_code) = hmac_512(&self.xkey_info.chain_code.0, &data);
+        let key = loop {
+            if let Some(key) = C::child_pubkey(&self.key, &il) {
+                break key;
+            }
+            let mut data = vec![1u8];
+            data.extend(chain_code.0);
+            data.extend(index.to_be_bytes());
+            (il, chain_code) = hmac_512(&self.xkey_info.chain_code.0, &data);
+        };
+
+        Ok(Self {
+            key,
+            xkey_info: XKeyInfo {
+                depth: self.xkey_info.depth + 1,
+                parent: self.fingerprint(),
+                index,
+                chain_code,
+                hint: self.xkey_info.hint,
+            },
+        })
+    }
+}
+
+/// A SLIP-10 extended private key with its derivation.
+pub struct DerivedSlip10XPriv<C: Slip10Curve> {
+    xpriv: Slip10XPriv<C>,
+    derivation: KeyDerivation,
+}
+
+impl<C: Slip10Curve> Clone for DerivedSlip10XPriv<C> {
+    fn clone(&self) -> Self {
+        Self {
+            xpriv: self.xpriv.clone(),
+            derivation: self.derivation.clone(),
+        }
+    }
+}
+
+impl<C: Slip10Curve> core::fmt::Debug for DerivedSlip10XPriv<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DerivedSlip10XPriv")
+            .field("xpriv", &self.xpriv)
+            .field("derivation", &self.derivation)
+            .finish()
+    }
+}
+
+impl<C: Slip10Curve> AsRef<Slip10XPriv<C>> for DerivedSlip10XPriv<C> {
+    fn as_ref(&self) -> &Slip10XPriv<C> {
+        &self.xpriv
+    }
+}
+
+impl<C: Slip10Curve> AsRef<XKeyInfo> for DerivedSlip10XPriv<C> {
+    fn as_ref(&self) -> &XKeyInfo {
+        &self.xpriv.xkey_info
+    }
+}
+
+impl<C: Slip10Curve> DerivedKey for DerivedSlip10XPriv<C> {
+    fn derivation(&self) -> &KeyDerivation {
+        &self.derivation
+    }
+}
+
+impl<C: Slip10Curve> DerivedSlip10XPriv<C> {
+    /// Instantiate a derived key from the key and derivation. This usually
+    /// should not be called directly. Prefer deriving keys from parents.
+    pub const fn new(xpriv: Slip10XPriv<C>, derivation: KeyDerivation) -> Self {
+        Self { xpriv, derivation }
+    }
+
+    /// Generate a root node from some seed data, using the curve's SLIP-10
+    /// HMAC key.
+    ///
+    /// # Important:
+    ///
+    /// Use a seed of AT LEAST 128 bits.
+    pub fn root_from_seed(data: &[u8], hint: Option<Hint>) -> Result<Self, Bip32Error> {
+        let xpriv = Slip10XPriv::root_from_seed(data, hint)?;
+
+        let derivation = KeyDerivation {
+            root: xpriv.fingerprint(),
+            path: vec![].into(),
+        };
+
+        Ok(Self { xpriv, derivation })
+    }
+
+    /// Derive the corresponding extended public key
+    pub fn verify_key(&self) -> DerivedSlip10XPub<C> {
+        DerivedSlip10XPub {
+            xpub: self.xpriv.verify_key(),
+            derivation: self.derivation.clone(),
+        }
+    }
+}
+
+impl<C: Slip10Curve> Parent for DerivedSlip10XPriv<C> {
+    fn derive_child(&self, index: u32) -> Result<Self, Bip32Error> {
+        Ok(Self {
+            xpriv: self.xpriv.derive_child(index)?,
+            derivation: self.derivation.extended(index),
+        })
+    }
+}
+
+/// A SLIP-10 extended public key with its derivation.
+pub struct DerivedSlip10XPub<C: Slip10Curve> {
+    xpub: Slip10XPub<C>,
+    derivation: KeyDerivation,
+}
+
+impl<C: Slip10Curve> Clone for DerivedSlip10XPub<C> {
+    fn clone(&self) -> Self {
+        Self {
+            xpub: self.xpub,
+            derivation: self.derivation.clone(),
+        }
+    }
+}
+
+impl<C: Slip10Curve> PartialEq for DerivedSlip10XPub<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.xpub == other.xpub && self.derivation == other.derivation
+    }
+}
+
+impl<C: Slip10Curve> core::fmt::Debug for DerivedSlip10XPub<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DerivedSlip10XPub")
+            .field("xpub", &self.xpub)
+            .field("derivation", &self.derivation)
+            .finish()
+    }
+}
+
+impl<C: Slip10Curve> AsRef<Slip10XPub<C>> for DerivedSlip10XPub<C> {
+    fn as_ref(&self) -> &Slip10XPub<C> {
+        &self.xpub
+    }
+}
+
+impl<C: Slip10Curve> AsRef<XKeyInfo> for DerivedSlip10XPub<C> {
+    fn as_ref(&self) -> &XKeyInfo {
+        &self.xpub.xkey_info
+    }
+}
+
+impl<C: Slip10Curve> DerivedKey for DerivedSlip10XPub<C> {
+    fn derivation(&self) -> &KeyDerivation {
+        &self.derivation
+    }
+}
+
+impl<C: Slip10Curve> DerivedSlip10XPub<C> {
+    /// Instantiate a derived key from the key and derivation. This usually
+    /// should not be called directly. Prefer deriving keys from parents.
+    pub const fn new(xpub: Slip10XPub<C>, derivation: KeyDerivation) -> Self {
+        Self { xpub, derivation }
+    }
+}
+
+impl<C: Slip10Curve> Parent for DerivedSlip10XPub<C> {
+    fn derive_child(&self, index: u32) -> Result<Self, Bip32Error> {
+        Ok(Self {
+            xpub: self.xpub.derive_child(index)?,
+            derivation: self.derivation.extended(index),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::path::DerivationPath;
+
+    struct Vector<'a> {
+        path: &'a str,
+        fingerprint: &'a str,
+        chain_code: &'a str,
+        private: &'a str,
+        public: &'a str,
+    }
+
+    const SEED_1: &str = "000102030405060708090a0b0c0d0e0f";
+    #[cfg(any(feature = "ed25519", feature = "nist256p1"))]
+    const SEED_2: &str = "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a29f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542";
+
+    fn validate<C: Slip10Curve>(seed: &str, vectors: &[Vector]) {
+        let seed = hex::decode(seed).unwrap();
+        let m = DerivedSlip10XPriv::<C>::root_from_seed(&seed, None).unwrap();
+
+        for v in vectors.iter() {
+            let path: DerivationPath = v.path.parse().unwrap();
+            let xpriv = m.derive_path(&path).unwrap();
+            let xpub = xpriv.verify_key();
+            let key: &Slip10XPriv<C> = xpriv.as_ref();
+
+            assert_eq!(xpriv.derivation().path, path);
+            assert_eq!(hex::encode(key.xkey_info.parent.0), v.fingerprint);
+            assert_eq!(hex::encode(key.chain_code().0), v.chain_code);
+            assert_eq!(hex::encode(key.secret_bytes()), v.private);
+            assert_eq!(hex::encode(key.verify_key().to_bytes()), v.public);
+
+            if C::PUBLIC_DERIVATION && path.last_hardened().1.is_none() {
+                let from_pub = m.verify_key().derive_path(&path).unwrap();
+                assert_eq!(from_pub, xpub);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "ed25519")]
+    fn slip10_ed25519_vector_1() {
+        validate::<Ed25519>(
+            SEED_1,
+            &[
+                Vector {
+                    path: "m",
+                    fingerprint: "00000000",
+                    chain_code: "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+                    private: "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+                    public: "00a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+                },
+                Vector {
+                    path: "m/0'",
+                    fingerprint: "ddebc675",
+                    chain_code: "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+                    private: "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                    public: "008c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+                },
+                Vector {
+                    path: "m/0'/1'",
+                    fingerprint: "13dab143",
+                    chain_code: "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+                    private: "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+                    public: "001932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187",
+                },
+                Vector {
+                    path: "m/0'/1'/2'",
+                    fingerprint: "ebe4cb29",
+                    chain_code: "2e69929e00b5ab250f49c3fb1c12f252de4fed2c1db88387094a0f8c4c9ccd6c",
+                    private: "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+                    public: "00ae98736566d30ed0e9d2f4486a64bc95740d89c7db33f52121f8ea8f76ff0fc1",
+                },
+                Vector {
+                    path: "m/0'/1'/2'/2'",
+                    fingerprint: "316ec1c6",
+                    chain_code: "8f6d87f93d750e0efccda017d662a1b31a266e4a6f5993b15f5c1f07f74dd5cc",
+                    private: "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+                    public: "008abae2d66361c879b900d204ad2cc4984fa2aa344dd7ddc46007329ac76c429c",
+                },
+                Vector {
+                    path: "m/0'/1'/2'/2'/1000000000'",
+                    fingerprint: "d6322ccd",
+                    chain_code: "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+                    private: "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+                    public: "003c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+                },
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ed25519")]
+    fn slip10_ed25519_vector_2() {
+        validate::<Ed25519>(
+            SEED_2,
+            &[
+                Vector {
+                    path: "m",
+                    fingerprint: "00000000",
+                    chain_code: "ef70a74db9c3a5af931b5fe73ed8e1a53464133654fd55e7a66f8570b8e33c3b",
+                    private: "171cb88b1b3c1db25add599712e36245d75bc65a1a5c9e18d76f9f2b1eab4012",
+                    public: "008fe9693f8fa62a4305a140b9764c5ee01e455963744fe18204b4fb948249308a",
+                },
+                Vector {
+                    path: "m/0'",
+                    fingerprint: "31981b50",
+                    chain_code: "0b78a3226f915c082bf118f83618a618ab6dec793752624cbeb622acb562862d",
+                    private: "1559eb2bbec5790b0c65d8693e4d0875b1747f4970ae8b650486ed7470845635",
+                    public: "0086fab68dcb57aa196c77c5f264f215a112c22a912c10d123b0d03c3c28ef1037",
+                },
+                Vector {
+                    path: "m/0'/2147483647'",
+                    fingerprint: "1e9411b1",
+                    chain_code: "138f0b2551bcafeca6ff2aa88ba8ed0ed8de070841f0c4ef0165df8181eaad7f",
+                    private: "ea4f5bfe8694d8bb74b7b59404632fd5968b774ed545e810de9c32a4fb4192f4",
+                    public: "005ba3b9ac6e90e83effcd25ac4e58a1365a9e35a3d3ae5eb07b9e4d90bcf7506d",
+                },
+                Vector {
+                    path: "m/0'/2147483647'/1'",
+                    fingerprint: "fcadf38c",
+                    chain_code: "73bd9fff1cfbde33a1b846c27085f711c0fe2d66fd32e139d3ebc28e5a4a6b90",
+                    private: "3757c7577170179c7868353ada796c839135b3d30554bbb74a4b1e4a5a58505c",
+                    public: "002e66aa57069c86cc18249aecf5cb5a9cebbfd6fadeab056254763874a9352b45",
+                },
+                Vector {
+                    path: "m/0'/2147483647'/1'/2147483646'",
+                    fingerprint: "aca70953",
+                    chain_code: "0902fe8a29f9140480a00ef244bd183e8a13288e4412d8389d140aac1794825a",
+                    private: "5837736c89570de861ebc173b1086da4f505d4adb387c6a1b1342d5e4ac9ec72",
+                    public: "00e33c0f7d81d843c572275f287498e8d408654fdf0d1e065b84e2e6f157aab09b",
+                },
+                Vector {
+                    path: "m/0'/2147483647'/1'/2147483646'/2'",
+                    fingerprint: "422c654b",
+                    chain_code: "5d70af781f3a37b829f0d060924d5e960bdc02e85423494afc0b1a41bbe196d4",
+                    private: "551d333177df541ad876a60ea71f00447931c0a9da16f227c11ea080d7391b8d",
+                    public: "0047150c75db263559a70d5778bf36abbab30fb061ad69f69ece61a72b0cfa4fc0",
+                },
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "nist256p1")]
+    fn slip10_nist256p1_vector_1() {
+        validate::<Nist256p1>(
+            SEED_1,
+            &[
+                Vector {
+                    path: "m",
+                    fingerprint: "00000000",
+                    chain_code: "beeb672fe4621673f722f38529c07392fecaa61015c80c34f29ce8b41b3cb6ea",
+                    private: "612091aaa12e22dd2abef664f8a01a82cae99ad7441b7ef8110424915c268bc2",
+                    public: "0266874dc6ade47b3ecd096745ca09bcd29638dd52c2c12117b11ed3e458cfa9e8",
+                },
+                Vector {
+                    path: "m/0'",
+                    fingerprint: "be6105b5",
+                    chain_code: "3460cea53e6a6bb5fb391eeef3237ffd8724bf0a40e94943c98b83825342ee11",
+                    private: "6939694369114c67917a182c59ddb8cafc3004e63ca5d3b84403ba8613debc0c",
+                    public: "0384610f5ecffe8fda089363a41f56a5c7ffc1d81b59a612d0d649b2d22355590c",
+                },
+                Vector {
+                    path: "m/0'/1",
+                    fingerprint: "9b02312f",
+                    chain_code: "4187afff1aafa8445010097fb99d23aee9f599450c7bd140b6826ac22ba21d0c",
+                    private: "284e9d38d07d21e4e281b645089a94f4cf5a5a81369acf151a1c3a57f18b2129",
+                    public: "03526c63f8d0b4bbbf9c80df553fe66742df4676b241dabefdef67733e070f6844",
+                },
+                Vector {
+                    path: "m/0'/1/2'",
+                    fingerprint: "b98005c1",
+                    chain_code: "98c7514f562e64e74170cc3cf304ee1ce54d6b6da4f880f313e8204c2a185318",
+                    private: "694596e8a54f252c960eb771a3c41e7e32496d03b954aeb90f61635b8e092aa7",
+                    public: "0359cf160040778a4b14c5f4d7b76e327ccc8c4a6086dd9451b7482b5a4972dda0",
+                },
+                Vector {
+                    path: "m/0'/1/2'/2",
+                    fingerprint: "0e9f3274",
+                    chain_code: "ba96f776a5c3907d7fd48bde5620ee374d4acfd540378476019eab70790c63a0",
+                    private: "5996c37fd3dd2679039b23ed6f70b506c6b56b3cb5e424681fb0fa64caf82aaa",
+                    public: "029f871f4cb9e1c97f9f4de9ccd0d4a2f2a171110c61178f84430062230833ff20",
+                },
+                Vector {
+                    path: "m/0'/1/2'/2/1000000000",
+                    fingerprint: "8b2b5c4b",
+                    chain_code: "b9b7b82d326bb9cb5b5b121066feea4eb93d5241103c9e7a18aad40f1dde8059",
+                    private: "21c4f269ef0a5fd1badf47eeacebeeaa3de22eb8e5b0adcd0f27dd99d34d0119",
+                    public: "02216cd26d31147f72427a453c443ed2cde8a1e53c9cc44e5ddf739725413fe3f4",
+                },
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "nist256p1")]
+    fn slip10_nist256p1_vector_2() {
+        validate::<Nist256p1>(
+            SEED_2,
+            &[
+                Vector {
+                    path: "m",
+                    fingerprint: "00000000",
+                    chain_code: "96cd4465a9644e31528eda3592aa35eb39a9527769ce1855beafc1b81055e75d",
+                    private: "eaa31c2e46ca2962227cf21d73a7ef0ce8b31c756897521eb6c7b39796633357",
+                    public: "02c9e16154474b3ed5b38218bb0463e008f89ee03e62d22fdcc8014beab25b48fa",
+                },
+                Vector {
+                    path: "m/0",
+                    fingerprint: "607f628f",
+                    chain_code: "84e9c258bb8557a40e0d041115b376dd55eda99c0042ce29e81ebe4efed9b86a",
+                    private: "d7d065f63a62624888500cdb4f88b6d59c2927fee9e6d0cdff9cad555884df6e",
+                    public: "039b6df4bece7b6c81e2adfeea4bcf5c8c8a6e40ea7ffa3cf6e8494c61a1fc82cc",
+                },
+                Vector {
+                    path: "m/0/2147483647'",
+                    fingerprint: "946d2a54",
+                    chain_code: "f235b2bc5c04606ca9c30027a84f353acf4e4683edbd11f635d0dcc1cd106ea6",
+                    private: "96d2ec9316746a75e7793684ed01e3d51194d81a42a3276858a5b7376d4b94b9",
+                    public: "02f89c5deb1cae4fedc9905f98ae6cbf6cbab120d8cb85d5bd9a91a72f4c068c76",
+                },
+                Vector {
+                    path: "m/0/2147483647'/1",
+                    fingerprint: "218182d8",
+                    chain_code: "7c0b833106235e452eba79d2bdd58d4086e663bc8cc55e9773d2b5eeda313f3b",
+                    private: "974f9096ea6873a915910e82b29d7c338542ccde39d2064d1cc228f371542bbc",
+                    public: "03abe0ad54c97c1d654c1852dfdc32d6d3e487e75fa16f0fd6304b9ceae4220c64",
+                },
+                Vector {
+                    path: "m/0/2147483647'/1/2147483646'",
+                    fingerprint: "931223e4",
+                    chain_code: "5794e616eadaf33413aa309318a26ee0fd5163b70466de7a4512fd4b1a5c9e6a",
+                    private: "da29649bbfaff095cd43819eda9a7be74236539a29094cd8336b07ed8d4eff63",
+                    public: "03cb8cb067d248691808cd6b5a5a06b48e34ebac4d965cba33e6dc46fe13d9b933",
+                },
+                Vector {
+                    path: "m/0/2147483647'/1/2147483646'/2",
+                    fingerprint: "956c4629",
+                    chain_code: "3bfb29ee8ac4484f09db09c2079b520ea5616df7820f071a20320366fbe226a7",
+                    private: "bb0a77ba01cc31d77205d51d08bd313b979a71ef4de9b062f8958297e746bd67",
+                    public: "020ee02e18967237cf62672983b253ee62fa4dd431f8243bfeccdf39dbe181387f",
+                },
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "nist256p1")]
+    fn slip10_nist256p1_retries() {
+        // derivation retry
+        validate::<Nist256p1>(
+            SEED_1,
+            &[
+                Vector {
+                    path: "m/28578'",
+                    fingerprint: "be6105b5",
+                    chain_code: "e94c8ebe30c2250a14713212f6449b20f3329105ea15b652ca5bdfc68f6c65c2",
+                    private: "06f0db126f023755d0b8d86d4591718a5210dd8d024e3e14b6159d63f53aa669",
+                    public: "02519b5554a4872e8c9c1c847115363051ec43e93400e030ba3c36b52a3e70a5b7",
+                },
+                Vector {
+                    path: "m/28578'/33941",
+                    fingerprint: "3e2b7bc6",
+                    chain_code: "9e87fe95031f14736774cd82f25fd885065cb7c358c1edf813c72af535e83071",
+                    private: "092154eed4af83e078ff9b84322015aefe5769e31270f62c3f66c33888335f3a",
+                    public: "0235bfee614c0d5b2cae260000bb1d0d84b270099ad790022c1ae0b2e782efe120",
+                },
+            ],
+        );
+
+        // seed retry
+        validate::<Nist256p1>(
+            "a7305bc8df8d0951f0cb224c0e95d7707cbdf2c6ce7e8d481fec69c7ff5e9446",
+            &[Vector {
+                path: "m",
+                fingerprint: "00000000",
+                chain_code: "7762f9729fed06121fd13f326884c82f59aa95c57ac492ce8c9654e60efd130c",
+                private: "3b8c18469a4634517d6d0b65448f8e6c62091b45540a1743c5846be55d47d88f",
+                public: "0383619fadcde31063d8c5cb00dbfe1713f3e6fa169d8541a798752a1c1ca0cb20",
+            }],
+        );
+    }
+
+    #[test]
+    fn slip10_secp256k1_vector_1() {
+        validate::<Secp256k1>(
+            SEED_1,
+            &[
+                Vector {
+                    path: "m",
+                    fingerprint: "00000000",
+                    chain_code: "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508",
+                    private: "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35",
+                    public: "0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2",
+                },
+                Vector {
+                    path: "m/0'",
+                    fingerprint: "3442193e",
+                    chain_code: "47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141",
+                    private: "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea",
+                    public: "035a784662a4a20a65bf6aab9ae98a6c068a81c52e4b032c0fb5400c706cfccc56",
+                },
+                Vector {
+                    path: "m/0'/1",
+                    fingerprint: "5c1bd648",
+                    chain_code: "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19",
+                    private: "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368",
+                    public: "03501e454bf00751f24b1b489aa925215d66af2234e3891c3b21a52bedb3cd711c",
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn slip10_secp256k1_matches_bip32() {
+        let seed = hex::decode(SEED_1).unwrap();
+        let path = "m/0'/1/2'/2/1000000000";
+
+        let expected = crate::xkeys::XPriv::root_from_seed(&seed, None)
+            .unwrap()
+            .derive_path(path)
+            .unwrap();
+        let actual = Slip10XPriv::<Secp256k1>::root_from_seed(&seed, None)
+            .unwrap()
+            .derive_path(path)
+            .unwrap();
+
+        assert_eq!(
+            actual.secret_bytes(),
+            <[u8; 32]>::from(expected.key.to_bytes())
+        );
+        assert_eq!(actual.xkey_info, expected.xkey_info);
+        assert_eq!(actual.fingerprint(), expected.fingerprint());
+    }
+
+    #[test]
+    #[cfg(feature = "ed25519")]
+    fn slip10_ed25519_rejects_non_hardened() {
+        let seed = hex::decode(SEED_1).unwrap();
+        let m = Ed25519XPriv::root_from_seed(&seed, None).unwrap();
+        match m.derive_child(0) {
+            Err(Bip32Error::NonHardenedDerivationFailed) => {}
+            _ => panic!("expected non-hardened derivation error"),
+        }
+        match m.verify_key().derive_child(0) {
+            Err(Bip32Error::NonHardenedDerivationFailed) => {}
+            _ => panic!("expected non-hardened derivation error"),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use coins_core::enc::{decode_base58, encode_base58};
+use k256::ecdsa;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
+
+use crate::{
+    derived::DerivedXPriv, enc::NetworkParams, network::Network, primitives::KeyFingerprint,
+    xkeys::XPriv, Bip32Error,
+};
+
+/// The WIF suffix marking a key whose pubkey is serialized compressed
+const COMPRESSED_FLAG: u8 = 0x01;
+
+fn malformatted(msg: impl Into<String>) -> Bip32Error {
+    Bip32Error::MalformattedPrivkey(msg.into())
+}
+
+/// A single, non-extended private key, as imported from or exported to
+/// Wallet Import Format. The `compressed` flag records whether the key's
+/// pubkey is serialized compressed, and is preserved by WIF round trips.
+#[derive(Clone)]
+pub struct Privkey {
+    key: ecdsa::SigningKey,
+    compressed: bool,
+}
+
+inherit_signer!(Privkey.key);
+
+// The signing key zeroizes itself on drop
+impl ZeroizeOnDrop for Privkey {}
+
+impl PartialEq for Privkey {
+    fn eq(&self, other: &Privkey) -> bool {
+        self.compressed == other.compressed && self.key == other.key
+    }
+}
+
+impl Eq for Privkey {}
+
+impl core::fmt::Debug for Privkey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Privkey")
+            .field("key fingerprint", &self.fingerprint())
+            .field("compressed", &self.compressed)
+            .finish()
+    }
+}
+
+impl AsRef<ecdsa::SigningKey> for Privkey {
+    fn as_ref(&self) -> &ecdsa::SigningKey {
+        &self.key
+    }
+}
+
+impl From<&XPriv> for Privkey {
+    fn from(xpriv: &XPriv) -> Self {
+        Self::new(AsRef::<ecdsa::SigningKey>::as_ref(xpriv).clone(), true)
+    }
+}
+
+impl From<&DerivedXPriv> for Privkey {
+    fn from(xpriv: &DerivedXPriv) -> Self {
+        Self::from(AsRef::<XPriv>::as_ref(xpriv))
+    }
+}
+
+impl Privkey {
+    /// Instantiate a new Privkey
+    pub const fn new(key: ecdsa::SigningKey, compressed: bool) -> Self {
+        Self { key, compressed }
+    }
+
+    /// Return a reference to the underlying signing key
+    pub const fn signing_key(&self) -> &ecdsa::SigningKey {
+        &self.key
+    }
+
+    /// `true` if the pubkey is serialized compressed
+    pub const fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    /// Derive the associated pubkey
+    pub fn verify_key(&self) -> ecdsa::VerifyingKey {
+        *self.key.verifying_key()
+    }
+
+    /// The SEC1 pubkey, compressed or uncompressed as recorded by the flag
+    pub fn pubkey_bytes(&self) -> Vec<u8> {
+        self.verify_key()
+            .to_encoded_point(self.compressed)
+            .as_bytes()
+            .to_vec()
+    }
+
+    /// The fingerprint is the first 4 bytes of the HASH160 of the compressed
+    /// public key
+    pub fn fingerprint(&self) -> KeyFingerprint {
+        crate::prelude::fingerprint_of(&self.verify_key())
+    }
+
+    /// Instantiate a compressed Privkey from a 32-byte secret
+    pub fn from_bytes(secret: &[u8]) -> Result<Self, Bip32Error> {
+        if secret.len() != 32 {
+            return Err(malformatted(format!(
+                "expected a 32-byte secret, got {} bytes",
+                secret.len()
+            )));
+        }
+        Ok(Self::new(ecdsa::SigningKey::from_slice(secret)?, true))
+    }
+
+    /// The 32-byte secret
+    pub fn to_bytes(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.key.to_bytes().into())
+    }
+
+    /// Instantiate a compressed Privkey from a hex-encoded 32-byte secret
+    pub fn from_hex(secret: &str) -> Result<Self, Bip32Error> {
+        let mut buf = Zeroizing::new([0u8; 32]);
+        hex::decode_to_slice(secret, &mut buf[..]).map_err(|e| malformatted(e.to_string()))?;
+        Self::from_bytes(&buf[..])
+    }
+
+    /// The hex-encoded 32-byte secret
+    pub fn to_hex(&self) -> Zeroizing<String> {
+        Zeroizing::new(hex::encode(&self.to_bytes()[..]))
+    }
+
+    /// Serialize the key as WIF with some version byte
+    pub fn to_wif_with_version(&self, version: u8) -> Zeroizing<String> {
+        let mut payload = Zeroizing::new(Vec::with_capacity(34));
+        payload.push(version);
+        payload.extend_from_slice(&self.to_bytes()[..]);
+        if self.compressed {
+            payload.push(COMPRESSED_FLAG);
+        }
+        Zeroizing::new(encode_base58(&payload))
+    }
+
+    /// Parse a WIF key, requiring some version byte
+    pub fn from_wif_with_version(version: u8, s: &str) -> Result<Self, Bip32Error> {
+        let payload = Zeroizing::new(decode_base58(version, s)?);
+        let compressed = match payload.len() {
+            33 => false,
+            34 if payload[33] == COMPRESSED_FLAG => true,
+            34 => {
+                return Err(malformatted(format!(
+                    "expected compression flag 0x01, got 0x{:02x}",
+                    payload[33]
+                )))
+            }
+            len => {
+                return Err(malformatted(format!(
+                    "expected a 33 or 34-byte WIF payload, got {} bytes",
+                    len
+                )))
+            }
+        };
+        let key = ecdsa::SigningKey::from_slice(&payload[1..33])?;
+        Ok(Self::new(key, compressed))
+    }
+
+    /// Serialize the key as WIF with the network's version byte
+    pub fn to_wif<P: NetworkParams>(&self) -> Zeroizing<String> {
+        self.to_wif_with_version(P::WIF_VERSION)
+    }
+
+    /// Parse a WIF key, requiring the network's version byte
+    pub fn from_wif<P: NetworkParams>(s: &str) -> Result<Self, Bip32Error> {
+        Self::from_wif_with_version(P::WIF_VERSION, s)
+    }
+}
+
+impl Network {
+    /// Serialize a Privkey as WIF with this network's version byte
+    pub fn privkey_to_wif(&self, key: &Privkey) -> Zeroizing<String> {
+        key.to_wif_with_version(self.wif)
+    }
+
+    /// Parse a WIF key, requiring this network's version byte
+    pub fn privkey_from_wif(&self, s: &str) -> Result<Privkey, Bip32Error> {
+        Privkey::from_wif_with_version(self.wif, s)
+    }
+}
+
+impl XPriv {
+    /// The non-extended private key, with a compressed pubkey
+    pub fn to_privkey(&self) -> Privkey {
+        self.into()
+    }
+
+    /// Serialize the private key as compressed WIF with the network's
+    /// version byte. The chain code is not included
+    pub fn to_wif<P: NetworkParams>(&self) -> Zeroizing<String> {
+        self.to_privkey().to_wif::<P>()
+    }
+
+    /// The hex-encoded 32-byte secret. The chain code is not included
+    pub fn secret_hex(&self) -> Zeroizing<String> {
+        self.to_privkey().to_hex()
+    }
+}
+
+impl DerivedXPriv {
+    /// The non-extended private key, with a compressed pubkey
+    pub fn to_privkey(&self) -> Privkey {
+        self.into()
+    }
+
+    /// Serialize the private key as compressed WIF with the network's
+    /// version byte. The chain code and derivation are not included
+    pub fn to_wif<P: NetworkParams>(&self) -> Zeroizing<String> {
+        self.to_privkey().to_wif::<P>()
+    }
+
+    /// The hex-encoded 32-byte secret. The chain code and derivation are not
+    /// included
+    pub fn secret_hex(&self) -> Zeroizing<String> {
+        self.to_privkey().to_hex()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        enc::{Main, MainnetEncoder, Test, XKeyEncoder},
+        network::{BITCOIN, LITECOIN},
+    };
+    use coins_core::hashes::Hash256;
+    use k256::ecdsa::signature::{DigestSigner, DigestVerifier};
+
+    // https://en.bitcoin.it/wiki/Wallet_import_format
+    const SECRET: &str = "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d";
+    const WIF: &str = "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ";
+    const WIF_COMPRESSED: &str = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+
+    #[test]
+    fn it_round_trips_wif() {
+        let key = Privkey::from_hex(SECRET).unwrap();
+        assert!(key.is_compressed());
+        assert_eq!(key.to_hex().as_str(), SECRET);
+        assert_eq!(key.to_wif::<Main>().as_str(), WIF_COMPRESSED);
+        assert_eq!(Privkey::from_wif::<Main>(WIF_COMPRESSED).unwrap(), key);
+        assert_eq!(key.pubkey_bytes().len(), 33);
+
+        let uncompressed = Privkey::from_wif::<Main>(WIF).unwrap();
+        assert!(!uncompressed.is_compressed());
+        assert_eq!(uncompressed.to_hex().as_str(), SECRET);
+        assert_eq!(uncompressed.to_wif::<Main>().as_str(), WIF);
+        assert_eq!(uncompressed.pubkey_bytes().len(), 65);
+        assert_eq!(uncompressed.verify_key(), key.verify_key());
+
+        let tprv = key.to_wif::<Test>();
+        assert!(tprv.starts_with('c'));
+        assert_eq!(Privkey::from_wif::<Test>(&tprv).unwrap(), key);
+        assert!(Privkey::from_wif::<Main>(&tprv).is_err());
+
+        let ltc = LITECOIN.privkey_to_wif(&key);
+        assert!(ltc.starts_with('T'));
+        assert_eq!(LITECOIN.privkey_from_wif(&ltc).unwrap(), key);
+        assert!(BITCOIN.privkey_from_wif(&ltc).is_err());
+    }
+
+    #[test]
+    fn it_rejects_malformed_secrets() {
+        assert!(matches!(
+            Privkey::from_hex("0c28"),
+            Err(Bip32Error::MalformattedPrivkey(_))
+        ));
+        assert!(matches!(
+            Privkey::from_hex(&"zz".repeat(32)),
+            Err(Bip32Error::MalformattedPrivkey(_))
+        ));
+        assert!(Privkey::from_hex(&"00".repeat(32)).is_err());
+
+        // a bad compression flag
+        let mut payload = vec![0x80];
+        payload.extend(hex::decode(SECRET).unwrap());
+        payload.push(0x02);
+        assert!(matches!(
+            Privkey::from_wif::<Main>(&encode_base58(&payload)),
+            Err(Bip32Error::MalformattedPrivkey(_))
+        ));
+        // a truncated payload
+        assert!(matches!(
+            Privkey::from_wif::<Main>(&encode_base58(&payload[..20])),
+            Err(Bip32Error::MalformattedPrivkey(_))
+        ));
+    }
+
+    #[test]
+    fn it_exports_derived_keys() {
+        let xpriv = MainnetEncoder::xpriv_from_base58("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi").unwrap();
+        let child = xpriv.derive_path("m/0'/1").unwrap();
+
+        let wif = child.to_wif::<Main>();
+        let key = Privkey::from_wif::<Main>(&wif).unwrap();
+        assert_eq!(key.verify_key(), *child.verify_key().as_ref());
+        assert_eq!(key.fingerprint(), child.fingerprint());
+        assert_eq!(
+            Privkey::from_hex(&child.secret_hex()).unwrap(),
+            child.to_privkey()
+        );
+
+        let digest = Hash256::default();
+        let sig: ecdsa::Signature = key.sign_digest(digest.clone());
+        child.verify_key().verify_digest(digest, &sig).unwrap();
+    }
+}
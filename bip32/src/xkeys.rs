@@ -1,11 +1,18 @@
+use alloc::{borrow::ToOwned, vec, vec::Vec};
 use coins_core::hashes::{Hash160, Hash160Digest, MarkedDigest, MarkedDigestOutput};
-use hmac::{Hmac, Mac};
-use k256::{ecdsa, elliptic_curve::sec1::FromEncodedPoint};
-use sha2::Sha512;
-use std::{
+use core::{
     convert::{TryFrom, TryInto},
-    ops::{AddAssign, Mul},
+    ops::{AddAssign, Mul, Range},
+};
+use hmac::{Hmac, Mac};
+use k256::{
+    ecdsa,
+    elliptic_curve::{
+        ops::MulByGenerator, sec1::FromEncodedPoint, subtle::CtOption, BatchNormalize, PrimeField,
+    },
 };
+use sha2::Sha512;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::{
     path::DerivationPath,
@@ -16,20 +23,21 @@ use crate::{
 /// The BIP32-defined seed used for derivation of the root node.
 pub const SEED: &[u8; 12] = b"Bitcoin seed";
 
-fn hmac_and_split(
-    seed: &[u8],
-    data: &[u8],
-) -> Result<(k256::NonZeroScalar, ChainCode), Bip32Error> {
+/// HMAC-SHA512 and split the output. The left half is parsed as a scalar in
+/// constant time, and is `None` if it is zero or not below the curve order.
+fn hmac_and_split(seed: &[u8], data: &[u8]) -> (CtOption<k256::Scalar>, ChainCode) {
     let mut mac = Hmac::<Sha512>::new_from_slice(seed).expect("key length is ok");
     mac.update(data);
-    let result = mac.finalize().into_bytes();
+    let mut result = Zeroizing::new([0u8; 64]);
+    result.copy_from_slice(&mac.finalize().into_bytes());
 
-    let left = k256::NonZeroScalar::try_from(&result[..32])?;
+    let left = k256::Scalar::from_repr(*k256::FieldBytes::from_slice(&result[..32]))
+        .and_then(|left| CtOption::new(left, !left.is_zero()));
 
     let mut right = [0u8; 32];
     right.copy_from_slice(&result[32..]);
 
-    Ok((left, ChainCode(right)))
+    (left, ChainCode(right))
 }
 
 /// A Parent key can be used to derive children.
@@ -83,8 +91,19 @@ impl Clone for XPriv {
 
 inherit_signer!(XPriv.key);
 
-impl std::fmt::Debug for XPriv {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+// The signing key zeroizes itself. The chain code is `Copy` so that it may be
+// shared with `XPub`, so we wipe the private key's copy here. Copies handed
+// out through `xkey_info` or `verify_key` are not wiped.
+impl Drop for XPriv {
+    fn drop(&mut self) {
+        self.xkey_info.chain_code.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for XPriv {}
+
+impl core::fmt::Debug for XPriv {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("XPriv")
             .field("key fingerprint", &self.fingerprint())
             .field("key info", &self.xkey_info)
@@ -112,7 +131,7 @@ impl AsRef<ecdsa::SigningKey> for XPriv {
 
 impl XPriv {
     /// Instantiate a new XPriv.
-    pub fn new(key: ecdsa::SigningKey, xkey_info: XKeyInfo) -> Self {
+    pub const fn new(key: ecdsa::SigningKey, xkey_info: XKeyInfo) -> Self {
         Self { key, xkey_info }
     }
 
@@ -158,11 +177,11 @@ impl XPriv {
             return Err(Bip32Error::SeedTooShort);
         }
         let parent = KeyFingerprint([0u8; 4]);
-        let (key, chain_code) = hmac_and_split(hmac_key, data)?;
-        if bool::from(key.is_zero()) {
-            // This can only be tested by mocking hmac_and_split
-            return Err(Bip32Error::InvalidKey);
-        }
+        let (key, chain_code) = hmac_and_split(hmac_key, data);
+        // This can only be tested by mocking hmac_and_split
+        let key: k256::NonZeroScalar = Option::<k256::Scalar>::from(key)
+            .and_then(|key| Option::from(k256::NonZeroScalar::new(key)))
+            .ok_or(Bip32Error::InvalidKey)?;
 
         let key = ecdsa::SigningKey::from(key);
 
@@ -207,46 +226,62 @@ impl XPriv {
         }
         Ok(current)
     }
-}
-
-impl Parent for XPriv {
-    fn derive_child(&self, index: u32) -> Result<Self, Bip32Error> {
-        let hardened = index >= BIP32_HARDEN;
 
+    /// The child key and chain code at `index`. The key is `None` if the tweak
+    /// or the child key is invalid. Both checks run in constant time.
+    fn child_candidate(&self, index: u32) -> (CtOption<k256::Scalar>, ChainCode) {
         let key: &ecdsa::SigningKey = self.as_ref();
 
-        let mut data: Vec<u8> = vec![];
-        if hardened {
+        let mut data = Zeroizing::new(Vec::with_capacity(37));
+        if index >= BIP32_HARDEN {
             data.push(0);
             data.extend(key.to_bytes());
-            data.extend(index.to_be_bytes());
         } else {
             data.extend(key.verifying_key().to_sec1_bytes().iter());
-            data.extend(index.to_be_bytes());
-        };
-
-        let res = hmac_and_split(&self.xkey_info.chain_code.0, &data);
-        let (tweak, chain_code) = match res {
-            Ok((tweak, chain_code)) => (tweak, chain_code),
-            _ => return self.derive_child(index + 1),
         };
+        data.extend(index.to_be_bytes());
 
-        let parent_key = k256::NonZeroScalar::from_repr(key.to_bytes()).unwrap();
-        let tweaked = tweak.clone().add(&parent_key);
-
-        let tweaked: k256::NonZeroScalar =
-            Option::from(k256::NonZeroScalar::new(tweaked)).ok_or(Bip32Error::BadTweak)?;
+        let (tweak, chain_code) = hmac_and_split(&self.xkey_info.chain_code.0, &data);
+        let parent_key = *key.as_nonzero_scalar().as_ref();
+        let child = tweak.and_then(|tweak| {
+            let child = tweak + parent_key;
+            CtOption::new(child, !child.is_zero())
+        });
+        (child, chain_code)
+    }
+}
 
-        Ok(Self {
-            key: ecdsa::SigningKey::from(tweaked),
-            xkey_info: XKeyInfo {
-                depth: self.xkey_info.depth + 1,
-                parent: self.fingerprint(),
-                index,
-                chain_code,
-                hint: self.xkey_info.hint,
-            },
-        })
+impl Parent for XPriv {
+    /// Derive the child at `index`, or at the next index if that child is
+    /// invalid.
+    ///
+    /// # Side channels
+    ///
+    /// Private derivation is constant time in the parent key and chain code.
+    /// The tweak's range check, the addition and the child's zero check use
+    /// constant-time scalar operations, and yield a single validity bit. That
+    /// bit is the only value we branch on, and it is not secret: an invalid
+    /// child is skipped, which the returned key's index reveals.
+    fn derive_child(&self, index: u32) -> Result<Self, Bip32Error> {
+        let mut index = index;
+        loop {
+            let (child, chain_code) = self.child_candidate(index);
+            let child: Option<k256::Scalar> = child.into();
+            if let Some(key) = child {
+                let key = k256::NonZeroScalar::new(key).expect("checked in child_candidate");
+                return Ok(Self {
+                    key: ecdsa::SigningKey::from(key),
+                    xkey_info: XKeyInfo {
+                        depth: self.xkey_info.depth + 1,
+                        parent: self.fingerprint(),
+                        index,
+                        chain_code,
+                        hint: self.xkey_info.hint,
+                    },
+                });
+            }
+            index = index.checked_add(1).ok_or(Bip32Error::InvalidKey)?;
+        }
     }
 }
 
@@ -265,8 +300,8 @@ impl Clone for XPub {
     }
 }
 
-impl std::fmt::Debug for XPub {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for XPub {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("XPub")
             .field("public key", &self.key.to_sec1_bytes())
             .field("key fingerprint", &self.fingerprint())
@@ -314,6 +349,69 @@ impl XPub {
     }
 }
 
+impl XPub {
+    /// Derive the unhardened children at each index in `range`. Produces the
+    /// same keys as calling `derive_child` for each index, but the parent
+    /// point, serialization, fingerprint and HMAC state are computed once,
+    /// and the child points are normalized in a single batch.
+    pub fn derive_range(&self, range: Range<u32>) -> Result<Vec<XPub>, Bip32Error> {
+        if range.end > BIP32_HARDEN {
+            return Err(Bip32Error::HardenedDerivationFailed);
+        }
+        // batch normalization panics on empty input
+        if range.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let parent_point = k256::ProjectivePoint::from(*self.key.as_affine());
+        let parent = self.fingerprint();
+        let mut mac =
+            Hmac::<Sha512>::new_from_slice(&self.xkey_info.chain_code.0).expect("key length is ok");
+        mac.update(&self.key.to_sec1_bytes());
+
+        let mut points = Vec::with_capacity(range.len());
+        let mut infos = Vec::with_capacity(range.len());
+        for index in range {
+            let mut child_mac = mac.clone();
+            child_mac.update(&index.to_be_bytes());
+            let result = child_mac.finalize().into_bytes();
+
+            match k256::NonZeroScalar::try_from(&result[..32]) {
+                Ok(tweak) => {
+                    let mut chain_code = [0u8; 32];
+                    chain_code.copy_from_slice(&result[32..]);
+                    points.push(k256::ProjectivePoint::mul_by_generator(&*tweak) + parent_point);
+                    infos.push(XKeyInfo {
+                        depth: self.xkey_info.depth + 1,
+                        parent,
+                        index,
+                        chain_code: ChainCode(chain_code),
+                        hint: self.xkey_info.hint,
+                    });
+                }
+                // invalid tweaks are vanishingly rare. Fall back to the retry logic in
+                // `derive_child`
+                Err(_) => {
+                    let child = self.derive_child(index)?;
+                    points.push(k256::ProjectivePoint::from(*child.key.as_affine()));
+                    infos.push(child.xkey_info);
+                }
+            }
+        }
+
+        k256::ProjectivePoint::batch_normalize(points.as_slice())
+            .into_iter()
+            .zip(infos)
+            .map(|(point, xkey_info)| {
+                Ok(Self {
+                    key: ecdsa::VerifyingKey::from_affine(point)?,
+                    xkey_info,
+                })
+            })
+            .collect()
+    }
+}
+
 impl PartialEq for XPub {
     fn eq(&self, other: &XPub) -> bool {
         self.key == other.key
@@ -331,20 +429,15 @@ impl Parent for XPub {
         data.extend(self.key.to_sec1_bytes().iter());
         data.extend(index.to_be_bytes());
 
-        let res = hmac_and_split(&self.xkey_info.chain_code.0, &data);
-
-        let (tweak, chain_code) = match res {
-            Ok((tweak, chain_code)) => (tweak, chain_code),
-            _ => return self.derive_child(index + 1),
+        let (tweak, chain_code) = hmac_and_split(&self.xkey_info.chain_code.0, &data);
+        let tweak: k256::Scalar = match Option::from(tweak) {
+            Some(tweak) => tweak,
+            None => return self.derive_child(index + 1),
         };
 
-        if bool::from(tweak.is_zero()) {
-            return self.derive_child(index + 1);
-        }
-
         let parent_key =
             k256::ProjectivePoint::from_encoded_point(&self.key.to_encoded_point(true)).unwrap();
-        let mut tweak_point = k256::ProjectivePoint::GENERATOR.mul(*tweak);
+        let mut tweak_point = k256::ProjectivePoint::GENERATOR.mul(tweak);
         tweak_point.add_assign(parent_key);
 
         let key = ecdsa::VerifyingKey::from_affine(tweak_point.to_affine())?;
@@ -581,6 +674,22 @@ mod test {
         assert_eq!(&recovered.to_sec1_bytes(), &child_xpub.key.to_sec1_bytes());
     }
 
+    #[test]
+    fn it_marks_private_keys_zeroize_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<XPriv>();
+        assert_zeroize_on_drop::<crate::derived::DerivedXPriv>();
+    }
+
+    #[test]
+    fn it_zeroizes_chain_codes() {
+        let xpriv = MainnetEncoder::xpriv_from_base58("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi").unwrap();
+        let mut chain_code = xpriv.xkey_info.chain_code;
+        assert_ne!(chain_code, ChainCode([0u8; 32]));
+        chain_code.zeroize();
+        assert_eq!(chain_code, ChainCode([0u8; 32]));
+    }
+
     #[test]
     fn it_can_read_keys() {
         let xpriv_str = "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi".to_owned();
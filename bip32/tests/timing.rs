@@ -0,0 +1,154 @@
+//! A dudect-style timing test for private child derivation.
+//!
+//! We time `derive_child` for two classes of parent key: one fixed key, and
+//! fresh random keys. The classes are interleaved in random order, and the
+//! timings are compared with Welch's t-test after cropping the slowest
+//! measurements at a few percentiles. If derivation time depends on the
+//! parent key, |t| grows with the number of measurements.
+//!
+//! Timing is noisy on shared machines, so the derivation test is ignored by
+//! default. Run it in release mode:
+//!
+//! ```sh
+//! cargo test --release -p coins-bip32 --test timing -- --ignored
+//! ```
+
+use coins_bip32::{prelude::*, BIP32_HARDEN};
+use std::{hint::black_box, time::Instant};
+
+/// |t| above this means the classes are distinguishable
+const THRESHOLD: f64 = 10.0;
+
+/// Percentiles at which measurements are cropped
+const CROPS: [f64; 5] = [1.0, 0.99, 0.95, 0.9, 0.75];
+
+/// A small deterministic PRNG, so that failures are reproducible
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_be_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// Running mean and variance, after Welford
+#[derive(Default)]
+struct Stats {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Stats {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
+}
+
+/// Welch's t statistic between two samples
+fn welch_t(a: &Stats, b: &Stats) -> f64 {
+    (a.mean - b.mean) / (a.variance() / a.n + b.variance() / b.n).sqrt()
+}
+
+/// Measure `f` on inputs of class 0 or 1, and return the largest |t| over the
+/// cropped samples
+fn max_t<T>(
+    rounds: usize,
+    rng: &mut Lcg,
+    mut input: impl FnMut(bool, &mut Lcg) -> T,
+    mut f: impl FnMut(&T),
+) -> f64 {
+    let classes: Vec<bool> = (0..rounds).map(|_| rng.next_u64() & 1 == 1).collect();
+    let inputs: Vec<T> = classes.iter().map(|&class| input(class, rng)).collect();
+
+    let timings: Vec<f64> = inputs
+        .iter()
+        .map(|input| {
+            let start = Instant::now();
+            f(input);
+            start.elapsed().as_nanos() as f64
+        })
+        .collect();
+
+    let mut sorted = timings.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    CROPS
+        .iter()
+        .map(|crop| {
+            let cutoff = sorted[((sorted.len() - 1) as f64 * crop) as usize];
+            let mut stats = [Stats::default(), Stats::default()];
+            for (&class, &t) in classes.iter().zip(timings.iter()) {
+                if t <= cutoff {
+                    stats[class as usize].push(t);
+                }
+            }
+            welch_t(&stats[0], &stats[1]).abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+fn random_xpriv(rng: &mut Lcg) -> XPriv {
+    let mut seed = [0u8; 32];
+    rng.fill(&mut seed);
+    XPriv::root_from_seed(&seed, None).unwrap()
+}
+
+#[test]
+fn it_detects_a_leak() {
+    let mut rng = Lcg(1);
+    let t = max_t(
+        20_000,
+        &mut rng,
+        |class, _| if class { 2_000u32 } else { 0 },
+        |&n| {
+            for i in 0..black_box(n) {
+                black_box(i);
+            }
+        },
+    );
+    assert!(t > THRESHOLD, "t = {}", t);
+}
+
+#[test]
+#[ignore]
+fn it_derives_in_constant_time() {
+    let mut rng = Lcg(2);
+    let fixed = random_xpriv(&mut rng);
+
+    for index in [0, BIP32_HARDEN] {
+        let t = max_t(
+            100_000,
+            &mut rng,
+            |class, rng| {
+                if class {
+                    random_xpriv(rng)
+                } else {
+                    fixed.clone()
+                }
+            },
+            |xpriv| {
+                black_box(xpriv.derive_child(black_box(index)).unwrap());
+            },
+        );
+        assert!(t < THRESHOLD, "index {}: t = {}", index, t);
+    }
+}
@@ -0,0 +1,119 @@
+use coins_bip32::{
+    bip85::{app_entropy, APP_BIP39},
+    xkeys::XPriv,
+};
+
+use crate::{Entropy, Mnemonic, MnemonicError, Wordlist};
+
+/// A wordlist with a BIP85 language code
+pub trait Bip85Language: Wordlist {
+    /// The language index used in `m/83696968'/39'/{language}'/...`
+    const BIP85_LANGUAGE: u32;
+}
+
+macro_rules! bip85_language {
+    ($feature:literal, $wordlist:ident, $code:literal) => {
+        #[cfg(feature = $feature)]
+        impl Bip85Language for crate::$wordlist {
+            const BIP85_LANGUAGE: u32 = $code;
+        }
+    };
+}
+
+bip85_language!("english", English, 0);
+bip85_language!("japanese", Japanese, 1);
+bip85_language!("korean", Korean, 2);
+bip85_language!("spanish", Spanish, 3);
+bip85_language!("chinese-simplified", ChineseSimplified, 4);
+bip85_language!("chinese-traditional", ChineseTraditional, 5);
+bip85_language!("french", French, 6);
+bip85_language!("italian", Italian, 7);
+bip85_language!("czech", Czech, 8);
+bip85_language!("portuguese", Portuguese, 9);
+
+impl<W> Mnemonic<W>
+where
+    W: Bip85Language,
+{
+    /// Derive a child mnemonic with the BIP85 BIP39 application, at
+    /// `m/83696968'/39'/{language}'/{words}'/{index}'`. The word count must
+    /// be 12, 18 or 24.
+    pub fn from_bip85(root: &XPriv, word_count: usize, index: u32) -> Result<Self, MnemonicError> {
+        let bytes = match word_count {
+            12 => 16,
+            18 => 24,
+            24 => 32,
+            wc => return Err(MnemonicError::InvalidWordCount(wc)),
+        };
+        let entropy = app_entropy(
+            root,
+            &[APP_BIP39, W::BIP85_LANGUAGE, word_count as u32, index],
+        )?;
+        Ok(Self::new_from_entropy(Entropy::from_slice(
+            &entropy[..bytes],
+        )?))
+    }
+}
+
+#[cfg(all(test, feature = "english"))]
+mod tests {
+    use super::*;
+    use crate::English;
+    use coins_bip32::enc::{MainnetEncoder, XKeyEncoder};
+
+    // https://github.com/bitcoin/bips/blob/master/bip-0085.mediawiki
+    const ROOT: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+    #[test]
+    fn test_bip85_mnemonics() {
+        let root = MainnetEncoder::xpriv_from_base58(ROOT).unwrap();
+        let cases = [
+            (
+                12,
+                "6250b68daf746d12a24d58b4787a714b",
+                "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose",
+            ),
+            (
+                18,
+                "938033ed8b12698449d4bbca3c853c66b293ea1b1ce9d9dc",
+                "near account window bike charge season chef number sketch tomorrow excuse sniff circle vital hockey outdoor supply token",
+            ),
+            (
+                24,
+                "ae131e2312cdc61331542efe0d1077bac5ea803adf24b313a4f0e48e9c51f37f",
+                "puppy ocean match cereal symbol another shed magic wrap hammer bulb intact gadget divorce twin tonight reason outdoor destroy simple truth cigar social volcano",
+            ),
+        ];
+        for (words, entropy, phrase) in cases.iter() {
+            let mnemonic = Mnemonic::<English>::from_bip85(&root, *words, 0).unwrap();
+            assert_eq!(mnemonic.to_phrase().as_str(), *phrase);
+            assert_eq!(
+                mnemonic,
+                Mnemonic::new_from_entropy(
+                    Entropy::from_slice(hex::decode(entropy).unwrap()).unwrap()
+                )
+            );
+        }
+
+        assert!(matches!(
+            Mnemonic::<English>::from_bip85(&root, 15, 0),
+            Err(MnemonicError::InvalidWordCount(15))
+        ));
+    }
+
+    #[cfg(feature = "japanese")]
+    #[test]
+    fn test_bip85_languages() {
+        let root = MainnetEncoder::xpriv_from_base58(ROOT).unwrap();
+        let japanese = Mnemonic::<crate::Japanese>::from_bip85(&root, 12, 0).unwrap();
+
+        // the language code is part of the path
+        let entropy = app_entropy(&root, &[APP_BIP39, 1, 12, 0]).unwrap();
+        let expected = Entropy::from_slice(&entropy[..16]).unwrap();
+        assert_eq!(japanese, Mnemonic::new_from_entropy(expected));
+
+        let entropy = app_entropy(&root, &[APP_BIP39, 0, 12, 0]).unwrap();
+        let english = Entropy::from_slice(&entropy[..16]).unwrap();
+        assert_ne!(japanese, Mnemonic::new_from_entropy(english));
+    }
+}
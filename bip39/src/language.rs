@@ -0,0 +1,375 @@
+use alloc::{string::String, vec::Vec};
+
+use coins_bip32::{path::DerivationPath, xkeys::XPriv, Bip32Error};
+use core::convert::TryInto;
+use zeroize::Zeroizing;
+
+use crate::{mnemonic::nfkd, Entropy, Mnemonic, MnemonicError, Seed};
+
+macro_rules! languages {
+    ($(($feature:literal, $wordlist:ident, $doc:literal)),* $(,)?) => {
+        /// A compiled-in wordlist language
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum Language {
+            $(
+                #[doc = $doc]
+                #[cfg(feature = $feature)]
+                $wordlist,
+            )*
+        }
+
+        impl Language {
+            /// Every compiled-in language
+            pub const ALL: &'static [Language] = &[
+                $(
+                    #[cfg(feature = $feature)]
+                    Language::$wordlist,
+                )*
+            ];
+
+            /// Returns the language's name, as in its crate feature.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Language::$wordlist => $feature,
+                    )*
+                }
+            }
+
+            /// Returns the language's word list.
+            pub fn get_all(self) -> &'static [&'static str] {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Language::$wordlist => <crate::$wordlist as crate::Wordlist>::get_all(),
+                    )*
+                }
+            }
+
+            /// Returns the index of a given word from the language's word list.
+            pub fn get_index(self, word: &str) -> Result<usize, crate::WordlistError> {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Language::$wordlist => <crate::$wordlist as crate::Wordlist>::get_index(word),
+                    )*
+                }
+            }
+        }
+
+        /// A mnemonic whose language is known at runtime
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum AnyMnemonic {
+            $(
+                #[doc = $doc]
+                #[cfg(feature = $feature)]
+                $wordlist(Mnemonic<crate::$wordlist>),
+            )*
+        }
+
+        impl AnyMnemonic {
+            /// Returns a new mnemonic in `language`, encoding the given entropy.
+            pub const fn new_from_entropy(language: Language, entropy: Entropy) -> Self {
+                match language {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Language::$wordlist => {
+                            AnyMnemonic::$wordlist(Mnemonic::new_from_entropy(entropy))
+                        }
+                    )*
+                }
+            }
+
+            /// Returns the new mnemonic for `phrase` in `language`.
+            pub fn new_from_phrase_in(
+                language: Language,
+                phrase: &str,
+            ) -> Result<Self, MnemonicError> {
+                match language {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Language::$wordlist => {
+                            Ok(AnyMnemonic::$wordlist(Mnemonic::new_from_phrase(phrase)?))
+                        }
+                    )*
+                }
+            }
+
+            /// Returns the mnemonic's language.
+            pub const fn language(&self) -> Language {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        AnyMnemonic::$wordlist(_) => Language::$wordlist,
+                    )*
+                }
+            }
+
+            /// Returns the mnemonic's entropy.
+            pub const fn entropy(&self) -> &Entropy {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        AnyMnemonic::$wordlist(mnemonic) => mnemonic.entropy(),
+                    )*
+                }
+            }
+
+            /// Converts the mnemonic into phrase. The phrase is wiped from
+            /// memory when dropped.
+            pub fn to_phrase(&self) -> Zeroizing<String> {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        AnyMnemonic::$wordlist(mnemonic) => mnemonic.to_phrase(),
+                    )*
+                }
+            }
+
+            /// Convert to a bip39 seed
+            pub fn to_seed(&self, password: Option<&str>) -> Result<Seed, MnemonicError> {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        AnyMnemonic::$wordlist(mnemonic) => mnemonic.to_seed(password),
+                    )*
+                }
+            }
+        }
+
+        $(
+            #[cfg(feature = $feature)]
+            impl From<Mnemonic<crate::$wordlist>> for AnyMnemonic {
+                fn from(mnemonic: Mnemonic<crate::$wordlist>) -> Self {
+                    AnyMnemonic::$wordlist(mnemonic)
+                }
+            }
+        )*
+    };
+}
+
+languages!(
+    ("english", English, "English"),
+    (
+        "chinese-simplified",
+        ChineseSimplified,
+        "Chinese (Simplified)"
+    ),
+    (
+        "chinese-traditional",
+        ChineseTraditional,
+        "Chinese (Traditional)"
+    ),
+    ("czech", Czech, "Czech"),
+    ("french", French, "French"),
+    ("italian", Italian, "Italian"),
+    ("japanese", Japanese, "Japanese"),
+    ("korean", Korean, "Korean"),
+    ("portuguese", Portuguese, "Portuguese"),
+    ("spanish", Spanish, "Spanish"),
+);
+
+impl Language {
+    /// Returns every compiled-in language whose wordlist contains `word`.
+    /// The word is NFKD normalized.
+    pub fn containing(word: &str) -> Vec<Language> {
+        let word = nfkd(word);
+        Self::ALL
+            .iter()
+            .copied()
+            .filter(|language| language.get_index(&word).is_ok())
+            .collect()
+    }
+}
+
+/// The compiled-in languages in which a phrase is a mnemonic
+#[derive(Clone, Debug)]
+pub struct Detection {
+    mnemonics: Vec<AnyMnemonic>,
+    checksum_failures: Vec<Language>,
+    shared_words: Vec<(usize, Vec<Language>)>,
+}
+
+impl Detection {
+    /// Returns the mnemonics of every language whose wordlist contains each
+    /// word of the phrase, and whose checksum matches.
+    pub fn mnemonics(&self) -> &[AnyMnemonic] {
+        &self.mnemonics
+    }
+
+    /// Returns the languages of the matching mnemonics.
+    pub fn languages(&self) -> Vec<Language> {
+        self.mnemonics.iter().map(AnyMnemonic::language).collect()
+    }
+
+    /// Returns the languages whose wordlist contains each word of the phrase,
+    /// but whose checksum does not match.
+    pub fn checksum_failures(&self) -> &[Language] {
+        &self.checksum_failures
+    }
+
+    /// Returns the positions of words found in more than one compiled-in
+    /// wordlist, with the languages containing them.
+    pub fn shared_words(&self) -> &[(usize, Vec<Language>)] {
+        &self.shared_words
+    }
+
+    /// True if the phrase is a mnemonic in more than one language. The seed
+    /// depends only on the phrase, so every match derives the same keys, but
+    /// their entropy, and so any re-encoding of the phrase, differs.
+    pub const fn is_ambiguous(&self) -> bool {
+        self.mnemonics.len() > 1
+    }
+}
+
+impl AnyMnemonic {
+    /// Find the compiled-in languages in which `phrase` is a mnemonic. Fails
+    /// only if the phrase has an invalid word count.
+    pub fn detect(phrase: &str) -> Result<Detection, MnemonicError> {
+        let normalized = nfkd(phrase);
+        match normalized.split_whitespace().count() {
+            12 | 15 | 18 | 21 | 24 => {}
+            wc => return Err(MnemonicError::InvalidWordCount(wc)),
+        }
+
+        let mut mnemonics = Vec::new();
+        let mut checksum_failures = Vec::new();
+        for &language in Language::ALL {
+            match Self::new_from_phrase_in(language, phrase) {
+                Ok(mnemonic) => mnemonics.push(mnemonic),
+                Err(MnemonicError::InvalidPhrase(_)) => checksum_failures.push(language),
+                Err(MnemonicError::InvalidWord { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let shared_words = normalized
+            .split_whitespace()
+            .map(Language::containing)
+            .enumerate()
+            .filter(|(_, languages)| languages.len() > 1)
+            .collect();
+
+        Ok(Detection {
+            mnemonics,
+            checksum_failures,
+            shared_words,
+        })
+    }
+
+    /// Returns the mnemonic for `phrase`, detecting its language. Fails if
+    /// the phrase is a mnemonic in no compiled-in language, or in several.
+    pub fn new_from_phrase(phrase: &str) -> Result<Self, MnemonicError> {
+        let mut detection = Self::detect(phrase)?;
+        match detection.mnemonics.len() {
+            1 => Ok(detection.mnemonics.remove(0)),
+            0 if detection.checksum_failures.is_empty() => Err(MnemonicError::UnknownLanguage),
+            0 => Err(MnemonicError::InvalidPhrase(phrase.into())),
+            _ => Err(MnemonicError::AmbiguousLanguage(
+                detection
+                    .languages()
+                    .into_iter()
+                    .map(Language::name)
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Returns the master private key of the corresponding mnemonic.
+    pub fn master_key(&self, password: Option<&str>) -> Result<XPriv, MnemonicError> {
+        Ok(XPriv::root_from_seed(
+            self.to_seed(password)?.as_ref(),
+            None,
+        )?)
+    }
+
+    /// Returns the derived child private key of the corresponding mnemonic at the given index.
+    pub fn derive_key<E, P>(&self, path: P, password: Option<&str>) -> Result<XPriv, MnemonicError>
+    where
+        E: Into<Bip32Error>,
+        P: TryInto<DerivationPath, Error = E>,
+    {
+        Ok(self.master_key(password)?.derive_path(path)?)
+    }
+}
+
+impl core::str::FromStr for AnyMnemonic {
+    type Err = MnemonicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new_from_phrase(s)
+    }
+}
+
+#[cfg(all(test, feature = "english", feature = "french"))]
+mod tests {
+    use super::*;
+
+    // valid in both English and French
+    const SHARED: &str =
+        "civil festival festival palace rival concert distance panda junior unique spatial science";
+    // shared words, valid only in English
+    const ENGLISH: &str =
+        "surface ozone figure surface fatal cruel fatigue danger capable fortune minute effort";
+
+    #[test]
+    fn it_detects_a_language() {
+        let detection = AnyMnemonic::detect(ENGLISH).unwrap();
+        assert!(!detection.is_ambiguous());
+        assert_eq!(detection.languages(), [Language::English]);
+        assert_eq!(detection.checksum_failures(), [Language::French]);
+        assert_eq!(detection.shared_words().len(), 12);
+
+        let mnemonic: AnyMnemonic = ENGLISH.parse().unwrap();
+        assert_eq!(mnemonic.language(), Language::English);
+        assert_eq!(mnemonic.to_phrase().as_str(), ENGLISH);
+
+        let typed: Mnemonic<crate::English> = ENGLISH.parse().unwrap();
+        assert_eq!(mnemonic, AnyMnemonic::from(typed.clone()));
+        assert_eq!(
+            mnemonic.master_key(Some("TREZOR")).unwrap(),
+            typed.master_key(Some("TREZOR")).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_reports_ambiguity() {
+        let detection = AnyMnemonic::detect(SHARED).unwrap();
+        assert!(detection.is_ambiguous());
+        assert_eq!(detection.languages(), [Language::English, Language::French]);
+        assert!(detection
+            .shared_words()
+            .iter()
+            .all(|(_, languages)| languages == &[Language::English, Language::French]));
+
+        assert!(matches!(
+            SHARED.parse::<AnyMnemonic>(),
+            Err(MnemonicError::AmbiguousLanguage(languages))
+                if languages == ["english", "french"]
+        ));
+        // the seed depends only on the phrase, but the entropy differs
+        let mnemonics = detection.mnemonics();
+        assert_eq!(
+            mnemonics[0].to_seed(None).unwrap(),
+            mnemonics[1].to_seed(None).unwrap()
+        );
+        assert_ne!(mnemonics[0].entropy(), mnemonics[1].entropy());
+    }
+
+    #[test]
+    fn it_rejects_unknown_phrases() {
+        assert!(matches!(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo".parse::<AnyMnemonic>(),
+            Err(MnemonicError::InvalidPhrase(_))
+        ));
+        assert!(matches!(
+            "mnemonic zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo".parse::<AnyMnemonic>(),
+            Err(MnemonicError::UnknownLanguage)
+        ));
+        assert!(matches!(
+            "zoo zoo zoo".parse::<AnyMnemonic>(),
+            Err(MnemonicError::InvalidWordCount(3))
+        ));
+    }
+}
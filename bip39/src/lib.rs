@@ -1,3 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(
     missing_docs,
     missing_copy_implementations,
@@ -13,6 +14,10 @@
 //! [bip32](https://github.com/summa-tx/bitcoins-rs/tree/main/bip32) crate, that depends on
 //! [k256](https://docs.rs/k256/0.10.0/k256/index.html) instead of
 //! [libsecp256k1](https://docs.rs/libsecp256k1/0.3.5/secp256k1/).
+//!
+//! The `std` feature is on by default. Without it, the crate builds on `alloc`.
+
+extern crate alloc;
 
 /// Mnemonic phrases
 pub mod mnemonic;
@@ -21,3 +26,42 @@ pub use self::mnemonic::*;
 /// Wordlists
 pub mod wordlist;
 pub use self::wordlist::*;
+
+/// Runtime wordlist languages, and language detection
+#[cfg(any(
+    feature = "chinese-simplified",
+    feature = "chinese-traditional",
+    feature = "czech",
+    feature = "english",
+    feature = "french",
+    feature = "italian",
+    feature = "japanese",
+    feature = "korean",
+    feature = "portuguese",
+    feature = "spanish",
+))]
+pub mod language;
+#[cfg(any(
+    feature = "chinese-simplified",
+    feature = "chinese-traditional",
+    feature = "czech",
+    feature = "english",
+    feature = "french",
+    feature = "italian",
+    feature = "japanese",
+    feature = "korean",
+    feature = "portuguese",
+    feature = "spanish",
+))]
+pub use self::language::*;
+
+/// BIP85 child mnemonics
+pub mod bip85;
+
+/// SLIP-39 Shamir backups
+#[cfg(feature = "slip39")]
+pub mod slip39;
+
+/// Recovery of damaged mnemonics
+#[cfg(feature = "std")]
+pub mod recovery;
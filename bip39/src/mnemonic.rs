@@ -1,28 +1,59 @@
+use alloc::{string::String, vec::Vec};
+
 use crate::{Wordlist, WordlistError};
 use bitvec::prelude::*;
 use coins_bip32::{path::DerivationPath, xkeys::XPriv, Bip32Error};
+use core::{convert::TryInto, marker::PhantomData};
 use hmac::Hmac;
 use pbkdf2::pbkdf2;
 use rand::Rng;
 use sha2::{Digest, Sha256, Sha512};
-use std::{convert::TryInto, marker::PhantomData};
 use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 const PBKDF2_ROUNDS: u32 = 2048;
 const PBKDF2_BYTES: usize = 64;
 
+/// The NFKD normalization of `s`. The string is allocated at its exact length,
+/// so it is never reallocated, and is wiped from memory when dropped.
+pub(crate) fn nfkd(s: &str) -> Zeroizing<String> {
+    let mut normalized = Zeroizing::new(String::with_capacity(s.nfkd().map(char::len_utf8).sum()));
+    normalized.extend(s.nfkd());
+    normalized
+}
+
 #[derive(Debug, Error)]
 /// The error type returned while interacting with mnemonics.
 pub enum MnemonicError {
     /// Describes the error when the mnemonic's entropy length is invalid.
     #[error("the mnemonic's entropy length `{0}` is invalid")]
     InvalidEntropyLength(usize),
-    /// Describes the error when the given phrase is invalid.
+    /// Describes the error when the given phrase's checksum does not match.
+    /// Every word is in the wordlist, so any of them may be wrong.
     #[error("the phrase `{0}` is invalid")]
     InvalidPhrase(String),
+    /// Describes the error when the word at `position`, counting from 0, is
+    /// not in the wordlist.
+    #[error("invalid word at position {position}: {source}")]
+    InvalidWord {
+        /// The position of the word in the phrase
+        position: usize,
+        /// The wordlist error
+        #[source]
+        source: WordlistError,
+    },
     /// Describes the error when the word count provided for mnemonic generation is invalid.
     #[error("invalid word count (expected 12, 15, 18, 21, 24, found `{0}`")]
     InvalidWordCount(usize),
+    /// Describes the error when no compiled-in wordlist contains every word of
+    /// the phrase.
+    #[error("no wordlist contains every word of the phrase")]
+    UnknownLanguage,
+    /// Describes the error when the phrase is a mnemonic in several languages,
+    /// given by name.
+    #[error("the phrase is a mnemonic in several languages: {0:?}")]
+    AmbiguousLanguage(Vec<&'static str>),
     /// Describes an error propagated from the wordlist errors.
     #[error(transparent)]
     WordlistError(#[from] WordlistError),
@@ -31,8 +62,8 @@ pub enum MnemonicError {
     Bip32Error(#[from] Bip32Error),
 }
 
-/// Holds valid entropy lengths for a mnemonic
-#[derive(Copy, Clone, PartialEq, Eq)]
+/// Holds valid entropy lengths for a mnemonic. Entropy is zeroized on drop.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
 pub enum Entropy {
     /// Sixteen bytes of entropy
     Sixteen([u8; 16]),
@@ -46,8 +77,8 @@ pub enum Entropy {
     ThirtyTwo([u8; 32]),
 }
 
-impl std::fmt::Debug for Entropy {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Entropy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Sixteen(_) => f.debug_tuple("Sixteen bytes").finish(),
             Self::Twenty(_) => f.debug_tuple("Twenty bytes").finish(),
@@ -58,7 +89,7 @@ impl std::fmt::Debug for Entropy {
     }
 }
 
-impl std::convert::TryFrom<&[u8]> for Entropy {
+impl core::convert::TryFrom<&[u8]> for Entropy {
     type Error = MnemonicError;
 
     fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
@@ -165,6 +196,30 @@ impl Entropy {
     }
 }
 
+/// A BIP39 seed, derived from a mnemonic and an optional password. The seed is
+/// zeroized on drop.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct Seed([u8; PBKDF2_BYTES]);
+
+impl core::fmt::Debug for Seed {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Seed").field(&"[redacted]").finish()
+    }
+}
+
+impl AsRef<[u8]> for Seed {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Seed {
+    /// Return a reference to the seed bytes
+    pub const fn as_bytes(&self) -> &[u8; PBKDF2_BYTES] {
+        &self.0
+    }
+}
+
 /// Mnemonic represents entropy that can be represented as a phrase. A mnemonic can be used to
 /// deterministically generate an extended private key or derive its child keys.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -178,7 +233,7 @@ where
     _wordlist: PhantomData<W>,
 }
 
-impl<W> std::str::FromStr for Mnemonic<W>
+impl<W> core::str::FromStr for Mnemonic<W>
 where
     W: Wordlist,
 {
@@ -219,36 +274,100 @@ where
         })
     }
 
-    /// Returns a new mnemonic for a given phrase. The 12-24 space-separated words are used to
-    /// calculate the entropy that must have produced it.
-    pub fn new_from_phrase(phrase: &str) -> Result<Self, MnemonicError> {
-        let words = phrase.split(' ').collect::<Vec<&str>>();
+    /// Returns a new mnemonic encoding the given entropy.
+    pub const fn new_from_entropy(entropy: Entropy) -> Self {
+        Self {
+            entropy,
+            _wordlist: PhantomData,
+        }
+    }
 
-        let mut entropy: BitVec<u8, Msb0> = BitVec::new();
-        for word in words {
-            let index = W::get_index(word)?;
+    /// Returns a new mnemonic for a given phrase. The phrase is NFKD
+    /// normalized, and its 12-24 words may be separated by any Unicode
+    /// whitespace, such as the ideographic space used in Japanese.
+    pub fn new_from_phrase(phrase: &str) -> Result<Self, MnemonicError> {
+        let normalized = nfkd(phrase);
+        let mut entropy: BitVec<u8, Msb0> = BitVec::with_capacity(33 * 8);
+        for (position, word) in normalized.split_whitespace().enumerate() {
+            let index = W::get_index(word)
+                .map_err(|source| MnemonicError::InvalidWord { position, source })?;
             let index_u8: [u8; 2] = (index as u16).to_be_bytes();
 
             // 11-bits per word as per BIP-39, and max index (2047) can be represented in 11-bits.
-            let index_slice = &BitVec::from_slice(&index_u8)[5..];
-
-            entropy.append(&mut BitVec::<u8, Msb0>::from_bitslice(index_slice));
+            entropy.extend_from_bitslice(&index_u8.view_bits::<Msb0>()[5..]);
         }
 
+        let entropy = Zeroizing::new(entropy.into_vec());
         let mnemonic = Self {
-            entropy: Entropy::from_slice(entropy.as_raw_slice())?,
+            entropy: Entropy::from_slice(&*entropy)?,
             _wordlist: PhantomData,
         };
 
         // Ensures the checksum word matches the checksum word in the given phrase.
-        match phrase == mnemonic.to_phrase() {
+        match mnemonic
+            .to_phrase()
+            .split(' ')
+            .eq(normalized.split_whitespace())
+        {
             true => Ok(mnemonic),
             false => Err(MnemonicError::InvalidPhrase(phrase.into())),
         }
     }
 
-    /// Converts the mnemonic into phrase.
-    pub fn to_phrase(&self) -> String {
+    /// Returns a new mnemonic for a phrase whose words may be abbreviated, as
+    /// in `Wordlist::expand`.
+    pub fn new_from_abbreviated_phrase(phrase: &str) -> Result<Self, MnemonicError> {
+        let words = nfkd(phrase)
+            .split_whitespace()
+            .enumerate()
+            .map(|(position, word)| {
+                W::expand(word).map_err(|source| MnemonicError::InvalidWord { position, source })
+            })
+            .collect::<Result<Vec<&str>, _>>()?;
+        match Self::new_from_phrase(&words.join(" ")) {
+            Err(MnemonicError::InvalidPhrase(_)) => {
+                Err(MnemonicError::InvalidPhrase(phrase.into()))
+            }
+            result => result,
+        }
+    }
+
+    /// Returns the mnemonic whose words are at `indices` in the wordlist, if
+    /// the count is valid and the checksum matches.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_indices(indices: &[usize]) -> Option<Self> {
+        let mut bits: BitVec<u8, Msb0> = BitVec::with_capacity(33 * 8);
+        for &index in indices {
+            bits.extend_from_bitslice(&(index as u16).to_be_bytes().view_bits::<Msb0>()[5..]);
+        }
+        let checksum_bits = indices.len() / 3;
+        let entropy_bits = bits.len() - checksum_bits;
+
+        let entropy = Entropy::from_slice(
+            Zeroizing::new(bits[..entropy_bits].to_bitvec().into_vec()).as_slice(),
+        );
+        // only valid lengths have a 4 to 8 bit checksum
+        let expected = entropy
+            .is_ok()
+            .then(|| bits[entropy_bits..].load_be::<u8>());
+        bits.as_raw_mut_slice().zeroize();
+
+        let (entropy, expected) = (entropy.ok()?, expected?);
+        let hash = Sha256::digest(entropy.as_ref());
+        match hash[0] >> (8 - checksum_bits) == expected {
+            true => Some(Self::new_from_entropy(entropy)),
+            false => None,
+        }
+    }
+
+    /// Returns the mnemonic's entropy.
+    pub const fn entropy(&self) -> &Entropy {
+        &self.entropy
+    }
+
+    /// Converts the mnemonic into phrase. The phrase is wiped from memory when
+    /// dropped.
+    pub fn to_phrase(&self) -> Zeroizing<String> {
         let length = self.word_count();
 
         // Compute checksum. Checksum is the most significant (ENTROPY_BYTES/4) bits. That is also
@@ -260,20 +379,20 @@ where
         let (checksum, _) = hash_0.split_at(length / 3);
 
         // Convert the entropy bytes into bits and append the checksum.
-        let mut encoding = BitVec::<u8, Msb0>::from_slice(self.entropy.as_ref());
-        encoding.append(&mut checksum.to_bitvec());
+        let mut encoding = BitVec::<u8, Msb0>::with_capacity(33 * 8);
+        encoding.extend_from_bitslice(self.entropy.as_ref().view_bits::<Msb0>());
+        encoding.extend_from_bitslice(checksum);
 
         // Compute the phrase in 11 bit chunks which encode an index into the word list
         let wordlist = W::get_all();
-        let phrase = encoding
+        let words = encoding
             .chunks(11)
-            .map(|index| {
-                let index = index.load_be::<u16>();
-                wordlist[index as usize]
-            })
+            .map(|index| wordlist[index.load_be::<u16>() as usize])
             .collect::<Vec<&str>>();
+        encoding.as_raw_mut_slice().zeroize();
 
-        phrase.join(" ")
+        // `join` allocates the exact length, so the phrase is never reallocated
+        Zeroizing::new(words.join(" "))
     }
 
     const fn word_count(&self) -> usize {
@@ -283,7 +402,7 @@ where
     /// Returns the master private key of the corresponding mnemonic.
     pub fn master_key(&self, password: Option<&str>) -> Result<XPriv, MnemonicError> {
         Ok(XPriv::root_from_seed(
-            self.to_seed(password)?.as_slice(),
+            self.to_seed(password)?.as_ref(),
             None,
         )?)
     }
@@ -297,15 +416,18 @@ where
         Ok(self.master_key(password)?.derive_path(path)?)
     }
 
-    /// Convert to a bip23 seed
-    pub fn to_seed(&self, password: Option<&str>) -> Result<[u8; PBKDF2_BYTES], MnemonicError> {
-        let mut seed = [0u8; PBKDF2_BYTES];
-        let salt = format!("mnemonic{}", password.unwrap_or(""));
+    /// Convert to a bip39 seed. The phrase and password are NFKD normalized.
+    pub fn to_seed(&self, password: Option<&str>) -> Result<Seed, MnemonicError> {
+        let mut seed = Seed([0u8; PBKDF2_BYTES]);
+        let password = nfkd(password.unwrap_or(""));
+        let mut salt = Zeroizing::new(String::with_capacity(8 + password.len()));
+        salt.push_str("mnemonic");
+        salt.push_str(&password);
         pbkdf2::<Hmac<Sha512>>(
-            self.to_phrase().as_bytes(),
+            nfkd(&self.to_phrase()).as_bytes(),
             salt.as_bytes(),
             PBKDF2_ROUNDS,
-            &mut seed,
+            &mut seed.0,
         )
         .expect("cannot have invalid length");
 
@@ -330,7 +452,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "WordlistError(InvalidWord(\"mnemonic\"))")]
+    #[should_panic(expected = "InvalidWord { position: 0, source: InvalidWord(\"mnemonic\") }")]
     fn test_invalid_word_in_phrase() {
         let phrase = "mnemonic zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo";
         let _mnemonic: Mnemonic<English> = phrase.parse().unwrap();
@@ -516,7 +638,7 @@ mod tests {
             dbg!(&phrase);
             let mnemonic: Mnemonic<English> = phrase.parse().unwrap();
             assert_eq!(mnemonic.entropy, expected_entropy);
-            assert_eq!(mnemonic.to_phrase(), phrase.to_string());
+            assert_eq!(mnemonic.to_phrase().as_str(), *phrase);
         })
     }
 
@@ -531,11 +653,11 @@ mod tests {
                     .try_into()
                     .unwrap();
                 let mnemonic = Mnemonic::<W> {
-                    entropy,
+                    entropy: entropy.clone(),
                     _wordlist: PhantomData,
                 };
                 assert_eq!(mnemonic.entropy, entropy);
-                assert_eq!(mnemonic.to_phrase(), expected_phrase.to_string())
+                assert_eq!(mnemonic.to_phrase().as_str(), *expected_phrase)
             })
     }
 
@@ -560,6 +682,24 @@ mod tests {
             });
     }
 
+    #[test]
+    fn test_secrets_zeroize() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<Entropy>();
+        assert_zeroize_on_drop::<Seed>();
+
+        let mut entropy = Entropy::from([0xffu8; 16]);
+        entropy.zeroize();
+        assert_eq!(entropy.as_ref(), &[0u8; 16]);
+
+        let mnemonic: Mnemonic<W> = TESTCASES[0].1.parse().unwrap();
+        let mut seed = mnemonic.to_seed(Some("TREZOR")).unwrap();
+        assert_eq!(format!("{:?}", seed), "Seed(\"[redacted]\")");
+        assert_eq!(seed.as_bytes().len(), 64);
+        seed.zeroize();
+        assert_eq!(seed.as_ref(), &[0u8; 64][..]);
+    }
+
     #[test]
     fn test_master_key() {
         TESTCASES
@@ -581,4 +721,121 @@ mod tests {
         mnemonic.derive_key(0, None).unwrap();
         mnemonic.derive_key("m/44'/61'/0'/0", None).unwrap();
     }
+
+    #[test]
+    fn test_invalid_word_position() {
+        let phrase = TESTCASES[1].1.replace("thank", "thnak");
+        assert!(matches!(
+            phrase.parse::<Mnemonic<W>>(),
+            Err(MnemonicError::InvalidWord {
+                position: 2,
+                source: WordlistError::InvalidWord(_),
+            })
+        ));
+    }
+
+    #[test]
+    fn test_abbreviated_phrase() {
+        TESTCASES.iter().for_each(|(_, phrase, _, _)| {
+            let abbreviated: Vec<String> = phrase
+                .split(' ')
+                .map(|word| word.chars().take(4).collect())
+                .collect();
+            let mnemonic =
+                Mnemonic::<W>::new_from_abbreviated_phrase(&abbreviated.join(" ")).unwrap();
+            assert_eq!(mnemonic.to_phrase().as_str(), *phrase);
+        });
+
+        assert!(matches!(
+            Mnemonic::<W>::new_from_abbreviated_phrase("aban aban aban"),
+            Err(MnemonicError::InvalidEntropyLength(_))
+        ));
+        assert!(matches!(
+            Mnemonic::<W>::new_from_abbreviated_phrase(
+                "aban aban aban aban aban aban aban aban aban aban ab abou"
+            ),
+            Err(MnemonicError::InvalidWord { position: 10, .. })
+        ));
+        assert!(matches!(
+            Mnemonic::<W>::new_from_abbreviated_phrase(
+                "aban aban aban aban aban aban aban aban aban aban aban aban"
+            ),
+            Err(MnemonicError::InvalidPhrase(_))
+        ));
+    }
+
+    #[test]
+    fn test_unicode_whitespace() {
+        let phrase = TESTCASES[1].1;
+        let expected: Mnemonic<W> = phrase.parse().unwrap();
+        for sep in ["  ", "\t", "\n", "\u{3000}"] {
+            let spaced = format!(" {} ", phrase.replace(' ', sep));
+            assert_eq!(spaced.parse::<Mnemonic<W>>().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_normalized_password() {
+        let mnemonic: Mnemonic<W> = TESTCASES[0].1.parse().unwrap();
+        // "é" composed, and as "e" with a combining acute accent
+        assert_eq!(
+            mnemonic.to_seed(Some("caf\u{e9}")).unwrap(),
+            mnemonic.to_seed(Some("cafe\u{301}")).unwrap(),
+        );
+    }
+}
+
+#[cfg(all(test, feature = "japanese"))]
+mod japanese_tests {
+    use crate::Japanese;
+
+    use super::*;
+
+    const PASSWORD: &str = "㍍ガバヴァぱばぐゞちぢ十人十色";
+
+    // (entropy, phrase, seed) from the Japanese test vectors. Phrases are
+    // composed, and separated by ideographic spaces.
+    const TESTCASES: [(&str, &str, &str); 4] = [
+        (
+            "00000000000000000000000000000000",
+            "あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あいこくしん　あおぞら",
+            "a262d6fb6122ecf45be09c50492b31f92e9beb7d9a845987a02cefda57a15f9c467a17872029a9e92299b5cbdf306e3a0ee620245cbd508959b6cb7ca637bd55",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "そつう　れきだい　ほんやく　わかす　りくつ　ばいか　ろせん　やちん　そつう　れきだい　ほんやく　わかめ",
+            "aee025cbe6ca256862f889e48110a6a382365142f7d16f2b9545285b3af64e542143a577e9c144e101a6bdca18f8d97ec3366ebf5b088b1c1af9bc31346e60d9",
+        ),
+        (
+            "80808080808080808080808080808080",
+            "そとづら　あまど　おおう　あこがれる　いくぶん　けいけん　あたえる　いよく　そとづら　あまど　おおう　あかちゃん",
+            "e51736736ebdf77eda23fa17e31475fa1d9509c78f1deb6b4aacfbd760a7e2ad769c714352c95143b5c1241985bcb407df36d64e75dd5a2b78ca5d2ba82a3544",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffff",
+            "われる　われる　われる　われる　われる　われる　われる　われる　われる　われる　われる　ろんぶん",
+            "4cd2ef49b479af5e1efbbd1e0bdc117f6a29b1010211df4f78e2ed40082865793e57949236c43b9fe591ec70e5bb4298b8b71dc4b267bb96ed4ed282c8f7761c",
+        ),
+    ];
+
+    #[test]
+    fn test_japanese_vectors() {
+        TESTCASES
+            .iter()
+            .for_each(|(entropy_str, phrase, expected_seed)| {
+                let mnemonic: Mnemonic<Japanese> = phrase.parse().unwrap();
+                assert_eq!(hex::encode(mnemonic.entropy.as_ref()), *entropy_str);
+                assert_eq!(
+                    mnemonic.to_phrase().as_str(),
+                    nfkd(phrase)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+                assert_eq!(
+                    hex::encode(mnemonic.to_seed(Some(PASSWORD)).unwrap()),
+                    *expected_seed,
+                );
+            });
+    }
 }
@@ -0,0 +1,528 @@
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{mnemonic::nfkd, Mnemonic, MnemonicError, Wordlist};
+
+/// The most unknown or uncertain words a search may have
+pub const MAX_UNKNOWN_WORDS: usize = 2;
+
+/// Candidates searched between progress reports
+pub const PROGRESS_INTERVAL: u64 = 4096;
+
+/// A word of a damaged phrase. Its `Debug` output omits the word.
+#[derive(Clone, PartialEq, Eq, Zeroize)]
+pub enum DamagedWord {
+    /// A word known to be right
+    Known(String),
+    /// A word that may be wrong. Similar words are tried first.
+    Uncertain(String),
+    /// A lost word
+    Missing,
+}
+
+impl core::fmt::Debug for DamagedWord {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Known(_) => f.debug_tuple("Known").field(&"[redacted]").finish(),
+            Self::Uncertain(_) => f.debug_tuple("Uncertain").field(&"[redacted]").finish(),
+            Self::Missing => f.debug_tuple("Missing").finish(),
+        }
+    }
+}
+
+/// An error while setting up a recovery
+#[derive(Debug, Error)]
+pub enum RecoveryError {
+    /// More words are unknown or uncertain than can be searched
+    #[error("{0} unknown words, but at most {MAX_UNKNOWN_WORDS} can be recovered")]
+    TooManyUnknownWords(usize),
+    /// The damaged phrase is invalid
+    #[error(transparent)]
+    MnemonicError(#[from] MnemonicError),
+}
+
+/// Confirms candidate mnemonics, e.g. by checking whether their first address
+/// has been used. Outside wasm32, checks must be `Sync` and return `Send`
+/// futures, so that `Recovery::recover` can be spawned.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait CandidateCheck<W: Wordlist> {
+    /// The error returned by the check
+    type Error: std::error::Error;
+
+    /// Return true if `candidate` is the lost mnemonic
+    async fn confirm(&self, candidate: &Mnemonic<W>) -> Result<bool, Self::Error>;
+}
+
+/// Accepts every candidate with a valid checksum
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcceptAll;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<W: Wordlist> CandidateCheck<W> for AcceptAll {
+    type Error = std::convert::Infallible;
+
+    async fn confirm(&self, _candidate: &Mnemonic<W>) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+/// Cancels a running recovery. Clones share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Instantiate a token that has not been cancelled
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Cancel the recovery. It stops at the next candidate.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// True if the recovery has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The progress of a recovery
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of candidates searched
+    pub searched: u64,
+    /// The number of candidates in the search space
+    pub total: u64,
+    /// The number of candidates with a valid checksum
+    pub valid: u64,
+    /// The number of candidates confirmed
+    pub found: usize,
+}
+
+/// The result of a recovery
+#[derive(Clone, Debug)]
+pub struct RecoveryReport<W: Wordlist> {
+    /// The confirmed mnemonics, in search order
+    pub found: Vec<Mnemonic<W>>,
+    /// The progress when the search stopped
+    pub progress: Progress,
+    /// True if the search was cancelled before it finished
+    pub cancelled: bool,
+}
+
+/// Returns control to the executor once, so that other tasks may run
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// A search for a damaged mnemonic. Candidates fill the unknown and uncertain
+/// words, and optionally swap a pair of neighbouring words. Those with a valid
+/// checksum are confirmed by a `CandidateCheck`. Its `Debug` output omits the
+/// words.
+#[derive(Clone)]
+pub struct Recovery<W: Wordlist> {
+    /// The wordlist index of each known word
+    known: Zeroizing<Vec<usize>>,
+    /// The positions of the unknown and uncertain words, with their
+    /// candidate indices, likeliest first
+    slots: Vec<(usize, Vec<usize>)>,
+    swaps: bool,
+    max_results: Option<usize>,
+    _wordlist: PhantomData<W>,
+}
+
+impl<W: Wordlist> core::fmt::Debug for Recovery<W> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Recovery")
+            .field("word count", &self.known.len())
+            .field("unknown words", &self.slots.len())
+            .field("swaps", &self.swaps)
+            .field("max results", &self.max_results)
+            .finish()
+    }
+}
+
+impl<W: Wordlist> Recovery<W> {
+    /// Instantiate a search over a damaged phrase. Fails if the word count
+    /// is invalid, a known word is not in the wordlist, or more than 2 words
+    /// are unknown or uncertain.
+    pub fn new(words: &[DamagedWord]) -> Result<Self, RecoveryError> {
+        match words.len() {
+            12 | 15 | 18 | 21 | 24 => {}
+            wc => return Err(MnemonicError::InvalidWordCount(wc).into()),
+        }
+        let unknown = words
+            .iter()
+            .filter(|word| !matches!(word, DamagedWord::Known(_)))
+            .count();
+        if unknown > MAX_UNKNOWN_WORDS {
+            return Err(RecoveryError::TooManyUnknownWords(unknown));
+        }
+
+        let wordlist = W::get_all();
+        let mut known = Zeroizing::new(vec![0; words.len()]);
+        let mut slots = vec![];
+        for (position, word) in words.iter().enumerate() {
+            match word {
+                DamagedWord::Known(word) => {
+                    known[position] = W::get_index(&nfkd(word))
+                        .map_err(|source| MnemonicError::InvalidWord { position, source })?;
+                }
+                DamagedWord::Uncertain(word) => {
+                    // every word, nearest first
+                    let candidates = W::suggest(word, usize::MAX)
+                        .into_iter()
+                        .map(|word| W::get_index(word).expect("from the wordlist"))
+                        .collect();
+                    slots.push((position, candidates));
+                }
+                DamagedWord::Missing => slots.push((position, (0..wordlist.len()).collect())),
+            }
+        }
+
+        Ok(Self {
+            known,
+            slots,
+            swaps: false,
+            max_results: None,
+            _wordlist: PhantomData,
+        })
+    }
+
+    /// Instantiate a search over a damaged phrase, in which `?` marks a
+    /// missing word, and a trailing `?` an uncertain one, e.g.
+    /// `"abandon ? ... abandn? about"`.
+    pub fn from_phrase(phrase: &str) -> Result<Self, RecoveryError> {
+        let normalized = nfkd(phrase);
+        let words: Zeroizing<Vec<DamagedWord>> = Zeroizing::new(
+            normalized
+                .split_whitespace()
+                .map(|word| match word.strip_suffix('?') {
+                    Some("") => DamagedWord::Missing,
+                    Some(word) => DamagedWord::Uncertain(word.to_owned()),
+                    None => DamagedWord::Known(word.to_owned()),
+                })
+                .collect(),
+        );
+        Self::new(&words)
+    }
+
+    /// Also try swapping each pair of neighbouring words. This multiplies
+    /// the search space by the word count, less one.
+    pub const fn swaps(mut self, swaps: bool) -> Self {
+        self.swaps = swaps;
+        self
+    }
+
+    /// Stop after confirming `max_results` mnemonics
+    pub const fn max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// The number of fillings of the unknown and uncertain words
+    fn fillings(&self) -> u64 {
+        self.slots
+            .iter()
+            .map(|(_, candidates)| candidates.len() as u64)
+            .product()
+    }
+
+    /// The number of candidates in the search space
+    pub fn search_space(&self) -> u64 {
+        let arrangements = match self.swaps {
+            true => self.known.len() as u64,
+            false => 1,
+        };
+        self.fillings() * arrangements
+    }
+
+    /// Write the `n`th candidate into `out`. Fillings vary fastest, so every
+    /// filling is tried in place before any swap. Returns false if the
+    /// candidate swaps two equal words, and so repeats an earlier one.
+    fn candidate(&self, n: u64, out: &mut [usize]) -> bool {
+        out.copy_from_slice(&self.known);
+        let fillings = self.fillings();
+        let mut filling = n % fillings;
+        for (position, candidates) in self.slots.iter() {
+            let len = candidates.len() as u64;
+            out[*position] = candidates[(filling % len) as usize];
+            filling /= len;
+        }
+        match (n / fillings) as usize {
+            0 => true,
+            swap => {
+                out.swap(swap - 1, swap);
+                out[swap - 1] != out[swap]
+            }
+        }
+    }
+
+    /// Search for the mnemonic. Candidates with a valid checksum are passed
+    /// to `check`, and `progress` is called every `PROGRESS_INTERVAL`
+    /// candidates and when the search stops. The search yields to the
+    /// executor at each progress report, so that `cancel` may be used from
+    /// another task.
+    pub async fn recover<C, P>(
+        &self,
+        check: &C,
+        mut progress: P,
+        cancel: &CancelToken,
+    ) -> Result<RecoveryReport<W>, C::Error>
+    where
+        C: CandidateCheck<W>,
+        P: FnMut(&Progress),
+    {
+        let mut status = Progress {
+            total: self.search_space(),
+            ..Default::default()
+        };
+        let mut found: Vec<Mnemonic<W>> = vec![];
+        let mut indices = Zeroizing::new(vec![0; self.known.len()]);
+
+        let mut cancelled = false;
+        for n in 0..status.total {
+            if self.max_results.is_some_and(|max| found.len() >= max) {
+                break;
+            }
+            if n > 0 && n % PROGRESS_INTERVAL == 0 {
+                progress(&status);
+                YieldNow(false).await;
+            }
+            if cancel.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+
+            status.searched += 1;
+            if !self.candidate(n, &mut indices) {
+                continue;
+            }
+            let mnemonic = match Mnemonic::<W>::from_indices(&indices) {
+                Some(mnemonic) => mnemonic,
+                None => continue,
+            };
+            status.valid += 1;
+            if found.iter().any(|m| m.entropy() == mnemonic.entropy()) {
+                continue;
+            }
+            if check.confirm(&mnemonic).await? {
+                found.push(mnemonic);
+                status.found = found.len();
+            }
+        }
+
+        progress(&status);
+        Ok(RecoveryReport {
+            found,
+            progress: status,
+            cancelled,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "english"))]
+mod tests {
+    use super::*;
+    use crate::English;
+    use std::sync::atomic::AtomicUsize;
+
+    type W = English;
+
+    const PHRASE: &str =
+        "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+    /// Confirms the mnemonic with known entropy, counting queries. A wallet
+    /// would check e.g. the history of the first derived address instead.
+    struct KnownEntropy {
+        expected: Mnemonic<W>,
+        queries: AtomicUsize,
+    }
+
+    #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+    impl CandidateCheck<W> for KnownEntropy {
+        type Error = std::convert::Infallible;
+
+        async fn confirm(&self, candidate: &Mnemonic<W>) -> Result<bool, Self::Error> {
+            self.queries.fetch_add(1, Ordering::Relaxed);
+            Ok(candidate.entropy() == self.expected.entropy())
+        }
+    }
+
+    fn known_entropy() -> KnownEntropy {
+        KnownEntropy {
+            expected: PHRASE.parse().unwrap(),
+            queries: AtomicUsize::new(0),
+        }
+    }
+
+    fn damage(replacements: &[(usize, &str)]) -> String {
+        let mut words: Vec<&str> = PHRASE.split(' ').collect();
+        for (position, word) in replacements {
+            words[*position] = word;
+        }
+        words.join(" ")
+    }
+
+    #[tokio::test]
+    async fn it_recovers_a_missing_word() {
+        let recovery = Recovery::<W>::from_phrase(&damage(&[(3, "?")])).unwrap();
+        assert_eq!(recovery.search_space(), 2048);
+
+        // without confirmation, every candidate with a valid checksum is found
+        let report = recovery
+            .recover(&AcceptAll, |_| {}, &CancelToken::new())
+            .await
+            .unwrap();
+        assert_eq!(report.progress.searched, 2048);
+        assert_eq!(report.progress.valid as usize, report.found.len());
+        assert!(report.found.contains(&PHRASE.parse().unwrap()));
+
+        let check = known_entropy();
+        let report = recovery
+            .recover(&check, |_| {}, &CancelToken::new())
+            .await
+            .unwrap();
+        assert_eq!(report.found.len(), 1);
+        assert_eq!(report.found[0].to_phrase().as_str(), PHRASE);
+        assert_eq!(
+            check.queries.load(Ordering::Relaxed) as u64,
+            report.progress.valid
+        );
+        assert!(!report.cancelled);
+    }
+
+    #[tokio::test]
+    async fn it_recovers_two_uncertain_words() {
+        let phrase = damage(&[(1, "winer?"), (9, "wimmer?")]);
+        let recovery = Recovery::<W>::from_phrase(&phrase).unwrap().max_results(1);
+        assert_eq!(recovery.search_space(), 2048 * 2048);
+
+        let report = recovery
+            .recover(&known_entropy(), |_| {}, &CancelToken::new())
+            .await
+            .unwrap();
+        assert_eq!(report.found[0].to_phrase().as_str(), PHRASE);
+        // the nearest words are tried first
+        assert!(report.progress.searched < 2048 * 8);
+    }
+
+    #[tokio::test]
+    async fn it_recovers_swapped_neighbours() {
+        let phrase = damage(&[(4, "sausage"), (5, "wave")]);
+        let recovery = Recovery::<W>::from_phrase(&phrase).unwrap().swaps(true);
+        assert_eq!(recovery.search_space(), 12);
+
+        let report = recovery
+            .recover(&known_entropy(), |_| {}, &CancelToken::new())
+            .await
+            .unwrap();
+        assert_eq!(report.found.len(), 1);
+        assert_eq!(report.found[0].to_phrase().as_str(), PHRASE);
+    }
+
+    #[tokio::test]
+    async fn it_reports_progress_and_cancels() {
+        let recovery = Recovery::<W>::from_phrase(&damage(&[(0, "?"), (11, "?")])).unwrap();
+        let cancel = CancelToken::new();
+        let mut reports = vec![];
+        let report = recovery
+            .recover(
+                &AcceptAll,
+                |progress| {
+                    reports.push(*progress);
+                    if reports.len() == 3 {
+                        cancel.cancel();
+                    }
+                },
+                &cancel,
+            )
+            .await
+            .unwrap();
+
+        assert!(report.cancelled);
+        assert_eq!(report.progress.searched, 3 * PROGRESS_INTERVAL);
+        assert_eq!(report.progress.total, 2048 * 2048);
+        assert_eq!(reports.len(), 4);
+        assert!(reports[..3]
+            .windows(2)
+            .all(|w| w[0].searched < w[1].searched));
+        assert_eq!(reports[3], report.progress);
+    }
+
+    #[test]
+    fn it_recovers_in_send_futures() {
+        fn assert_send<T: Send>(_: &T) {}
+        let recovery = Recovery::<W>::from_phrase(&damage(&[(3, "?")])).unwrap();
+        let cancel = CancelToken::new();
+        assert_send(&recovery.recover(&AcceptAll, |_| {}, &cancel));
+        assert_send(&recovery.recover(&known_entropy(), |_| {}, &cancel));
+    }
+
+    #[test]
+    fn it_redacts_debug_output() {
+        let recovery = Recovery::<W>::from_phrase(&damage(&[(1, "winer?"), (3, "?")])).unwrap();
+        assert_eq!(
+            format!("{:?}", recovery),
+            "Recovery { word count: 12, unknown words: 2, swaps: false, max results: None }"
+        );
+
+        let words = [
+            DamagedWord::Known("legal".to_owned()),
+            DamagedWord::Uncertain("winer".to_owned()),
+            DamagedWord::Missing,
+        ];
+        assert_eq!(
+            format!("{:?}", words),
+            r#"[Known("[redacted]"), Uncertain("[redacted]"), Missing]"#
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_searches() {
+        assert!(matches!(
+            Recovery::<W>::from_phrase(&damage(&[(0, "?"), (1, "?"), (2, "winer?")])),
+            Err(RecoveryError::TooManyUnknownWords(3))
+        ));
+        assert!(matches!(
+            Recovery::<W>::from_phrase(&damage(&[(5, "sausag")])),
+            Err(RecoveryError::MnemonicError(MnemonicError::InvalidWord {
+                position: 5,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            Recovery::<W>::from_phrase("? ? legal"),
+            Err(RecoveryError::MnemonicError(
+                MnemonicError::InvalidWordCount(3)
+            ))
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+//! The SLIP-39 passphrase encryption: a 4-round Feistel network with
+//! PBKDF2-HMAC-SHA256 as the round function.
+
+use alloc::vec::Vec;
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// PBKDF2 iterations across all rounds, before scaling by the exponent
+const BASE_ITERATION_COUNT: u32 = 10_000;
+/// The number of Feistel rounds
+const ROUND_COUNT: u8 = 4;
+/// Prefix of the salt of non-extendable shares
+const CUSTOMIZATION: &[u8] = b"shamir";
+
+/// The salt prefix. Extendable backups don't bind the identifier, so that
+/// new share sets can be made for the same encrypted secret.
+fn salt(identifier: u16, extendable: bool) -> Vec<u8> {
+    if extendable {
+        return Vec::new();
+    }
+    let mut salt = CUSTOMIZATION.to_vec();
+    salt.extend(identifier.to_be_bytes());
+    salt
+}
+
+fn feistel(
+    data: &[u8],
+    passphrase: &[u8],
+    iteration_exponent: u8,
+    salt: &[u8],
+    rounds: impl Iterator<Item = u8>,
+) -> Zeroizing<Vec<u8>> {
+    let iterations = (BASE_ITERATION_COUNT << iteration_exponent) / ROUND_COUNT as u32;
+    let half = data.len() / 2;
+    let mut left = Zeroizing::new(data[..half].to_vec());
+    let mut right = Zeroizing::new(data[half..].to_vec());
+
+    for round in rounds {
+        let mut password = Zeroizing::new(Vec::with_capacity(1 + passphrase.len()));
+        password.push(round);
+        password.extend_from_slice(passphrase);
+        let mut round_salt = Zeroizing::new(salt.to_vec());
+        round_salt.extend_from_slice(&right);
+
+        let mut f = Zeroizing::new(alloc::vec![0u8; right.len()]);
+        pbkdf2::<Hmac<Sha256>>(&password, &round_salt, iterations, &mut f)
+            .expect("cannot have invalid length");
+
+        left.iter_mut().zip(f.iter()).for_each(|(l, f)| *l ^= f);
+        core::mem::swap(&mut left, &mut right);
+    }
+
+    right.extend_from_slice(&left);
+    right
+}
+
+/// Encrypt a master secret with a passphrase
+pub(crate) fn encrypt(
+    master_secret: &[u8],
+    passphrase: &[u8],
+    iteration_exponent: u8,
+    identifier: u16,
+    extendable: bool,
+) -> Zeroizing<Vec<u8>> {
+    feistel(
+        master_secret,
+        passphrase,
+        iteration_exponent,
+        &salt(identifier, extendable),
+        0..ROUND_COUNT,
+    )
+}
+
+/// Decrypt an encrypted master secret with a passphrase
+pub(crate) fn decrypt(
+    encrypted: &[u8],
+    passphrase: &[u8],
+    iteration_exponent: u8,
+    identifier: u16,
+    extendable: bool,
+) -> Zeroizing<Vec<u8>> {
+    feistel(
+        encrypted,
+        passphrase,
+        iteration_exponent,
+        &salt(identifier, extendable),
+        (0..ROUND_COUNT).rev(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_roundtrips() {
+        let secret = *b"0123456789abcdef";
+        for extendable in [false, true] {
+            let encrypted = encrypt(&secret, b"TREZOR", 0, 7, extendable);
+            assert_ne!(&encrypted[..], &secret[..]);
+            assert_eq!(
+                &decrypt(&encrypted, b"TREZOR", 0, 7, extendable)[..],
+                &secret[..]
+            );
+            assert_ne!(&decrypt(&encrypted, b"", 0, 7, extendable)[..], &secret[..]);
+        }
+    }
+}
@@ -0,0 +1,722 @@
+//! [SLIP-39](https://github.com/satoshilabs/slips/blob/master/slip-0039.md)
+//! Shamir backups.
+//!
+//! A master secret is encrypted with a passphrase, and split into groups of
+//! shares. Recovering it needs `group_threshold` groups, and each group needs
+//! its own `threshold` of member shares. The recovered master secret is the
+//! seed of the BIP32 master key.
+//!
+//! ```
+//! use coins_bip39::slip39::{GroupSpec, MasterSecret, Share};
+//!
+//! let mut rng = rand::thread_rng();
+//! let secret = MasterSecret::from_rng(16, &mut rng).unwrap();
+//!
+//! // one share alone, or 2 of 3 others
+//! let groups = [GroupSpec::new(1, 1), GroupSpec::new(2, 3)];
+//! let shares = secret.split(&mut rng, Some("TREZOR"), 1, &groups).unwrap();
+//!
+//! let phrases: Vec<_> = shares[1][1..].iter().map(Share::to_phrase).collect();
+//! let parsed = phrases
+//!     .iter()
+//!     .map(|phrase| phrase.parse())
+//!     .collect::<Result<Vec<Share>, _>>()
+//!     .unwrap();
+//! let recovered = MasterSecret::combine(&parsed, Some("TREZOR")).unwrap();
+//! assert_eq!(recovered, secret);
+//!
+//! let master_key = recovered.master_key().unwrap();
+//! ```
+
+mod cipher;
+mod shamir;
+
+/// SLIP-39 shares
+pub mod share;
+pub use self::share::*;
+
+/// The SLIP-39 wordlist
+pub mod wordlist;
+pub use self::wordlist::*;
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use coins_bip32::{xkeys::XPriv, Bip32Error};
+use rand::Rng;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::WordlistError;
+
+/// The largest number of groups, or of shares in a group
+const MAX_SHARE_COUNT: usize = 16;
+/// The shortest master secret, in bytes
+const MIN_SECRET_LENGTH: usize = 16;
+
+#[derive(Debug, Error)]
+/// The error type returned while interacting with SLIP-39 shares.
+pub enum Slip39Error {
+    /// Describes the error when a share has too few words, or a length that
+    /// does not encode whole bytes.
+    #[error("invalid share word count `{0}`")]
+    InvalidWordCount(usize),
+    /// Describes the error when a share's checksum does not match.
+    #[error("the share's checksum is invalid")]
+    InvalidChecksum,
+    /// Describes the error when a share's padding bits are not zero.
+    #[error("the share's padding is invalid")]
+    InvalidPadding,
+    /// Describes the error when a threshold is 0, exceeds its count, or is 1
+    /// for a group of several shares, or when the count exceeds 16.
+    #[error("threshold `{threshold}` is invalid for `{count}` shares")]
+    InvalidThreshold {
+        /// The threshold
+        threshold: usize,
+        /// The number of groups or shares
+        count: usize,
+    },
+    /// Describes the error when the master secret is shorter than 16 bytes or
+    /// of odd length.
+    #[error("the master secret's length `{0}` is invalid")]
+    InvalidSecretLength(usize),
+    /// Describes the error when the iteration exponent does not fit in 4 bits.
+    #[error("the iteration exponent `{0}` is invalid")]
+    InvalidIterationExponent(u8),
+    /// Describes the error when the passphrase has characters other than
+    /// printable ASCII.
+    #[error("the passphrase must be printable ASCII")]
+    InvalidPassphrase,
+    /// Describes the error when shares come from different backups, or
+    /// disagree about their parameters.
+    #[error("the shares do not belong to the same backup")]
+    MismatchedShares,
+    /// Describes the error when there are too few shares to recover the
+    /// master secret.
+    #[error("not enough shares to recover the master secret")]
+    InsufficientShares,
+    /// Describes the error when there are more groups than the group
+    /// threshold, or more shares in a group than its threshold.
+    #[error("more shares than needed to recover the master secret")]
+    TooManyShares,
+    /// Describes the error when a recovered secret does not match its digest.
+    #[error("the recovered secret's digest is invalid")]
+    InvalidDigest,
+    /// Describes an error propagated from the wordlist errors.
+    #[error(transparent)]
+    WordlistError(#[from] WordlistError),
+    /// Describes an error propagated from the BIP-32 crate.
+    #[error(transparent)]
+    Bip32Error(#[from] Bip32Error),
+}
+
+/// The thresholds of a group of shares
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupSpec {
+    /// The number of shares needed to recover the group
+    pub threshold: u8,
+    /// The number of shares in the group
+    pub count: u8,
+}
+
+impl GroupSpec {
+    /// Instantiate a group of `count` shares, any `threshold` of which
+    /// recover it
+    pub const fn new(threshold: u8, count: u8) -> Self {
+        Self { threshold, count }
+    }
+}
+
+/// Parameters of a backup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SplitOptions {
+    /// The exponent `e` of the PBKDF2 iteration count, `10000 * 2^e`. At
+    /// most 15.
+    pub iteration_exponent: u8,
+    /// Whether new share sets may later be made for the same master secret
+    pub extendable: bool,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        Self {
+            iteration_exponent: 1,
+            extendable: true,
+        }
+    }
+}
+
+const fn check_threshold(threshold: usize, count: usize) -> Result<(), Slip39Error> {
+    if threshold == 0 || threshold > count || count > MAX_SHARE_COUNT {
+        return Err(Slip39Error::InvalidThreshold { threshold, count });
+    }
+    Ok(())
+}
+
+fn passphrase_bytes(passphrase: Option<&str>) -> Result<&[u8], Slip39Error> {
+    let passphrase = passphrase.unwrap_or("").as_bytes();
+    if passphrase.iter().any(|b| !(32..=126).contains(b)) {
+        return Err(Slip39Error::InvalidPassphrase);
+    }
+    Ok(passphrase)
+}
+
+/// A SLIP-39 master secret. The secret is zeroized on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct MasterSecret(Zeroizing<Vec<u8>>);
+
+impl core::fmt::Debug for MasterSecret {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("MasterSecret").field(&"[redacted]").finish()
+    }
+}
+
+impl AsRef<[u8]> for MasterSecret {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl MasterSecret {
+    /// Instantiate a master secret from bytes. It must be at least 16 bytes
+    /// long, and of even length.
+    pub fn new(secret: impl AsRef<[u8]>) -> Result<Self, Slip39Error> {
+        let secret = secret.as_ref();
+        if secret.len() < MIN_SECRET_LENGTH || secret.len() % 2 != 0 {
+            return Err(Slip39Error::InvalidSecretLength(secret.len()));
+        }
+        Ok(Self(Zeroizing::new(secret.to_vec())))
+    }
+
+    /// Instantiate a random master secret of `bytes` bytes
+    pub fn from_rng<R: Rng>(bytes: usize, rng: &mut R) -> Result<Self, Slip39Error> {
+        let mut secret = Zeroizing::new(alloc::vec![0u8; bytes]);
+        rng.fill(&mut secret[..]);
+        Self::new(&*secret)
+    }
+
+    /// Returns the master private key, using the master secret as its seed.
+    pub fn master_key(&self) -> Result<XPriv, Slip39Error> {
+        Ok(XPriv::root_from_seed(self.as_ref(), None)?)
+    }
+
+    /// Encrypt the master secret with `passphrase`, and split it into the
+    /// shares of `groups`, any `group_threshold` of which recover it. Shares
+    /// are returned by group. The backup is extendable, with iteration
+    /// exponent 1.
+    pub fn split<R: Rng>(
+        &self,
+        rng: &mut R,
+        passphrase: Option<&str>,
+        group_threshold: u8,
+        groups: &[GroupSpec],
+    ) -> Result<Vec<Vec<Share>>, Slip39Error> {
+        self.split_with_options(
+            rng,
+            passphrase,
+            group_threshold,
+            groups,
+            SplitOptions::default(),
+        )
+    }
+
+    /// As `split`, with explicit backup parameters.
+    pub fn split_with_options<R: Rng>(
+        &self,
+        rng: &mut R,
+        passphrase: Option<&str>,
+        group_threshold: u8,
+        groups: &[GroupSpec],
+        options: SplitOptions,
+    ) -> Result<Vec<Vec<Share>>, Slip39Error> {
+        let passphrase = passphrase_bytes(passphrase)?;
+        if options.iteration_exponent > 15 {
+            return Err(Slip39Error::InvalidIterationExponent(
+                options.iteration_exponent,
+            ));
+        }
+        check_threshold(group_threshold as usize, groups.len())?;
+        for group in groups {
+            check_threshold(group.threshold as usize, group.count as usize)?;
+            if group.threshold == 1 && group.count > 1 {
+                return Err(Slip39Error::InvalidThreshold {
+                    threshold: 1,
+                    count: group.count as usize,
+                });
+            }
+        }
+
+        let identifier = rng.gen::<u16>() & 0x7fff;
+        let encrypted = cipher::encrypt(
+            &self.0,
+            passphrase,
+            options.iteration_exponent,
+            identifier,
+            options.extendable,
+        );
+        let group_secrets = shamir::split(rng, group_threshold, groups.len() as u8, &encrypted);
+
+        Ok(groups
+            .iter()
+            .zip(group_secrets.iter())
+            .enumerate()
+            .map(|(group_index, (group, group_secret))| {
+                shamir::split(rng, group.threshold, group.count, group_secret)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(member_index, value)| Share {
+                        identifier,
+                        extendable: options.extendable,
+                        iteration_exponent: options.iteration_exponent,
+                        group_index: group_index as u8,
+                        group_threshold,
+                        group_count: groups.len() as u8,
+                        member_index: member_index as u8,
+                        member_threshold: group.threshold,
+                        value,
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Recover the master secret from shares, and decrypt it with
+    /// `passphrase`. As in the reference implementation, exactly
+    /// `group_threshold` groups must be given, each with exactly its member
+    /// threshold of shares, so that a stray share is noticed rather than
+    /// silently dropped. A share given twice is counted once.
+    ///
+    /// Any passphrase decrypts to some master secret. Only the right one
+    /// decrypts to the master secret that was split.
+    pub fn combine(shares: &[Share], passphrase: Option<&str>) -> Result<Self, Slip39Error> {
+        let passphrase = passphrase_bytes(passphrase)?;
+        let first = shares.first().ok_or(Slip39Error::InsufficientShares)?;
+        let same_backup = shares.iter().all(|share| {
+            share.identifier == first.identifier
+                && share.extendable == first.extendable
+                && share.iteration_exponent == first.iteration_exponent
+                && share.group_threshold == first.group_threshold
+                && share.group_count == first.group_count
+                && share.value.len() == first.value.len()
+                && share.group_index < share.group_count
+        });
+        if !same_backup {
+            return Err(Slip39Error::MismatchedShares);
+        }
+
+        // members of each group, by member index
+        let mut groups: BTreeMap<u8, BTreeMap<u8, &Share>> = BTreeMap::new();
+        for share in shares {
+            let members = groups.entry(share.group_index).or_default();
+            let consistent = members.values().all(|member| {
+                member.member_threshold == share.member_threshold
+                    && (member.member_index != share.member_index || member.value == share.value)
+            });
+            if !consistent {
+                return Err(Slip39Error::MismatchedShares);
+            }
+            members.insert(share.member_index, share);
+        }
+
+        let check_count = |count: usize, threshold: u8| match count.cmp(&(threshold as usize)) {
+            core::cmp::Ordering::Less => Err(Slip39Error::InsufficientShares),
+            core::cmp::Ordering::Equal => Ok(()),
+            core::cmp::Ordering::Greater => Err(Slip39Error::TooManyShares),
+        };
+        check_count(groups.len(), first.group_threshold)?;
+        let complete = groups
+            .iter()
+            .map(|(&group_index, members)| {
+                let threshold = members.values().next().expect("nonempty").member_threshold;
+                check_count(members.len(), threshold)?;
+                let points: Vec<(u8, &[u8])> = members
+                    .values()
+                    .map(|share| (share.member_index, &share.value[..]))
+                    .collect();
+                Ok((group_index, shamir::recover(threshold, &points)?))
+            })
+            .collect::<Result<Vec<_>, Slip39Error>>()?;
+
+        let points: Vec<(u8, &[u8])> = complete
+            .iter()
+            .map(|(group_index, secret)| (*group_index, &secret[..]))
+            .collect();
+        let encrypted = shamir::recover(first.group_threshold, &points)?;
+        Ok(Self(cipher::decrypt(
+            &encrypted,
+            passphrase,
+            first.iteration_exponent,
+            first.identifier,
+            first.extendable,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combine(phrases: &[&str]) -> Result<MasterSecret, Slip39Error> {
+        let shares = phrases
+            .iter()
+            .map(|phrase| phrase.parse())
+            .collect::<Result<Vec<Share>, _>>()?;
+        MasterSecret::combine(&shares, Some("TREZOR"))
+    }
+
+    // (shares, master secret) from the SLIP-39 test vectors, passphrase "TREZOR"
+    const TESTCASES: [(&[&str], &str); 7] = [
+        (
+            &["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard"],
+            "bb54aac4b89dc868ba37d9cc21b2cece",
+        ),
+        (
+            &[
+                "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+                "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+            ],
+            "b43ceb7e57a0ea8766221624d01b0864",
+        ),
+        (
+            &[
+                "eraser senior beard romp adorn nuclear spill corner cradle style ancient family general leader ambition exchange unusual garlic promise voice",
+                "eraser senior ceramic snake clay various huge numb argue hesitate auction category timber browser greatest hanger petition script leaf pickup",
+                "eraser senior ceramic shaft dynamic become junior wrist silver peasant force math alto coal amazing segment yelp velvet image paces",
+                "eraser senior ceramic round column hawk trust auction smug shame alive greatest sheriff living perfect corner chest sled fumes adequate",
+            ],
+            "7c3397a292a5941682d7a4ae2d898d11",
+        ),
+        (
+            &[
+                "eraser senior decision roster beard treat identify grumpy salt index fake aviation theater cubic bike cause research dragon emphasis counter",
+                "eraser senior ceramic snake clay various huge numb argue hesitate auction category timber browser greatest hanger petition script leaf pickup",
+                "eraser senior ceramic shaft dynamic become junior wrist silver peasant force math alto coal amazing segment yelp velvet image paces",
+                "eraser senior decision smug corner ruin rescue cubic angel tackle skin skunk program roster trash rumor slush angel flea amazing",
+                "eraser senior ceramic round column hawk trust auction smug shame alive greatest sheriff living perfect corner chest sled fumes adequate",
+            ],
+            "7c3397a292a5941682d7a4ae2d898d11",
+        ),
+        (
+            &[
+                "eraser senior decision roster beard treat identify grumpy salt index fake aviation theater cubic bike cause research dragon emphasis counter",
+                "eraser senior decision smug corner ruin rescue cubic angel tackle skin skunk program roster trash rumor slush angel flea amazing",
+                "eraser senior beard romp adorn nuclear spill corner cradle style ancient family general leader ambition exchange unusual garlic promise voice",
+            ],
+            "7c3397a292a5941682d7a4ae2d898d11",
+        ),
+        (
+            &["theory painting academic academic armed sweater year military elder discuss acne wildlife boring employer fused large satoshi bundle carbon diagnose anatomy hamster leaves tracks paces beyond phantom capital marvel lips brave detect luck"],
+            "989baf9dcaad5b10ca33dfd8cc75e42477025dce88ae83e75a230086a0e00e92",
+        ),
+        (
+            &["testify swimming academic academic column loyalty smear include exotic bedroom exotic wrist lobe cover grief golden smart junior estimate learn"],
+            "1679b4516e0ee5954351d288a838f45e",
+        ),
+    ];
+
+    #[test]
+    fn it_passes_the_test_vectors() {
+        for (phrases, expected) in TESTCASES.iter() {
+            let secret = combine(phrases).unwrap();
+            assert_eq!(hex::encode(secret.as_ref()), *expected);
+        }
+    }
+
+    // invalid share sets from the SLIP-39 test vectors, passphrase "TREZOR"
+    const MISMATCHED: [[&str; 2]; 5] = [
+        // different identifiers
+        [
+            "adequate smoking academic acid debut wine petition glen cluster slow rhyme slow simple epidemic rumor junk tracks treat olympic tolerate",
+            "adequate stay academic agency agency formal party ting frequent learn upstairs remember smear leaf damage anatomy ladle market hush corner",
+        ],
+        // different iteration exponents
+        [
+            "peasant leaves academic acid desert exact olympic math alive axle trial tackle drug deny decent smear dominant desert bucket remind",
+            "peasant leader academic agency cultural blessing percent network envelope medal junk primary human pumps jacket fragment payroll ticket evoke voice",
+        ],
+        // mismatching group thresholds
+        [
+            "liberty category beard email beyond should fancy romp founder easel pink holy hairy romp loyalty material victim owner toxic custody",
+            "liberty category academic easy being hazard crush diminish oral lizard reaction cluster force dilemma deploy force club veteran expect photo",
+        ],
+        // mismatching group counts
+        [
+            "average senior academic leaf broken teacher expect surface hour capture obesity desire negative dynamic dominant pistol mineral mailman iris aide",
+            "average senior academic agency curious pants blimp spew clothes slice script dress wrap firm shaft regular slavery negative theater roster",
+        ],
+        // duplicate member indices
+        [
+            "device stay academic always dive coal antenna adult black exceed stadium herald advance soldier busy dryer daughter evaluate minister laser",
+            "device stay academic always dwarf afraid robin gravity crunch adjust soul branch walnut coastal dream costume scholar mortgage mountain pumps",
+        ],
+    ];
+
+    #[test]
+    fn it_rejects_the_invalid_test_vectors() {
+        for phrases in MISMATCHED.iter() {
+            assert!(matches!(
+                combine(phrases),
+                Err(Slip39Error::MismatchedShares)
+            ));
+        }
+
+        let digest = [
+            "guilt walnut academic acid deliver remove equip listen vampire tactics nylon rhythm failure husband fatigue alive blind enemy teaspoon rebound",
+            "guilt walnut academic agency brave hamster hobo declare herd taste alpha slim criminal mild arcade formal romp branch pink ambition",
+        ];
+        assert!(matches!(combine(&digest), Err(Slip39Error::InvalidDigest)));
+
+        // a group threshold greater than the group count
+        let greater = "music husband acrobat acid artist finance center either graduate swimming object bike medical clothes station aspect spider maiden bulb welcome";
+        assert!(matches!(
+            combine(&[greater]),
+            Err(Slip39Error::InvalidThreshold { .. })
+        ));
+
+        // insufficient groups, and a group with insufficient members
+        let eraser = TESTCASES[2].0;
+        assert!(matches!(
+            combine(&eraser[1..3]),
+            Err(Slip39Error::InsufficientShares)
+        ));
+        let members = [
+            "eraser senior decision shadow artist work morning estate greatest pipeline plan ting petition forget hormone flexible general goat admit surface",
+            eraser[0],
+        ];
+        assert!(matches!(
+            combine(&members),
+            Err(Slip39Error::InsufficientShares)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_invalid_padding() {
+        let phrase = "duckling enlarge academic academic email result length solution fridge kidney coal piece deal husband erode duke ajar music cargo fitness";
+        assert!(matches!(
+            combine(&[phrase]),
+            Err(Slip39Error::InvalidPadding)
+        ));
+    }
+
+    /// Shares of a 2-of-2 group backup with groups of 2 of 3 and 1 of 1, with
+    /// a fixed identifier so that tests are reproducible
+    fn backup() -> Vec<Vec<Share>> {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(39);
+        let options = SplitOptions {
+            iteration_exponent: 0,
+            extendable: false,
+        };
+        MasterSecret::new([0x5a; 16])
+            .unwrap()
+            .split_with_options(
+                &mut rng,
+                Some("TREZOR"),
+                2,
+                &[GroupSpec::new(2, 3), GroupSpec::new(1, 1)],
+                options,
+            )
+            .unwrap()
+    }
+
+    /// Encode shares as phrases, so that each has a valid checksum
+    fn phrases(shares: &[Share]) -> Vec<Zeroizing<alloc::string::String>> {
+        shares.iter().map(Share::to_phrase).collect()
+    }
+
+    fn combine_shares(shares: &[Share]) -> Result<MasterSecret, Slip39Error> {
+        let phrases = phrases(shares);
+        let phrases: Vec<&str> = phrases.iter().map(|phrase| phrase.as_str()).collect();
+        combine(&phrases)
+    }
+
+    #[test]
+    fn it_rejects_each_kind_of_invalid_set() {
+        let shares = backup();
+        let valid = [
+            shares[0][0].clone(),
+            shares[0][2].clone(),
+            shares[1][0].clone(),
+        ];
+        assert_eq!(combine_shares(&valid).unwrap().as_ref(), &[0x5a; 16]);
+
+        // each case alters the last share of a valid set
+        let altered = |alter: &dyn Fn(&mut Share)| {
+            let mut set = valid.to_vec();
+            alter(set.last_mut().unwrap());
+            combine_shares(&set)
+        };
+        let mismatched: [&dyn Fn(&mut Share); 6] = [
+            &|share| share.identifier ^= 1,
+            &|share| share.iteration_exponent += 1,
+            &|share| share.extendable = true,
+            &|share| share.group_threshold = 1,
+            &|share| share.group_count = 3,
+            &|share| share.value.extend([0, 0]),
+        ];
+        for alter in mismatched.iter() {
+            assert!(matches!(
+                altered(*alter),
+                Err(Slip39Error::MismatchedShares)
+            ));
+        }
+
+        // mismatching member thresholds, and duplicate member indices
+        let mut set = valid.to_vec();
+        set[1].member_threshold = 3;
+        assert!(matches!(
+            combine_shares(&set),
+            Err(Slip39Error::MismatchedShares)
+        ));
+        let mut set = valid.to_vec();
+        set[1].member_index = set[0].member_index;
+        assert!(matches!(
+            combine_shares(&set),
+            Err(Slip39Error::MismatchedShares)
+        ));
+
+        // a corrupted share value fails the digest check
+        let mut set = valid.to_vec();
+        set[1].value[0] ^= 1;
+        assert!(matches!(
+            combine_shares(&set),
+            Err(Slip39Error::InvalidDigest)
+        ));
+
+        // insufficient groups, and a group with insufficient members
+        assert!(matches!(
+            combine_shares(&valid[..2]),
+            Err(Slip39Error::InsufficientShares)
+        ));
+        assert!(matches!(
+            combine_shares(&valid[1..]),
+            Err(Slip39Error::InsufficientShares)
+        ));
+
+        // a group threshold greater than the group count
+        let mut share = valid[0].clone();
+        share.group_threshold = 3;
+        assert!(matches!(
+            share.to_phrase().parse::<Share>(),
+            Err(Slip39Error::InvalidThreshold {
+                threshold: 3,
+                count: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_extra_shares() {
+        let shares = backup();
+
+        // a third member of a 2-of-3 group
+        let mut set = shares[0].clone();
+        set.push(shares[1][0].clone());
+        assert!(matches!(
+            combine_shares(&set),
+            Err(Slip39Error::TooManyShares)
+        ));
+
+        // a repeated share is counted once
+        let set = [
+            shares[0][1].clone(),
+            shares[1][0].clone(),
+            shares[0][0].clone(),
+            shares[0][1].clone(),
+        ];
+        assert_eq!(combine_shares(&set).unwrap().as_ref(), &[0x5a; 16]);
+
+        // a group beyond the group threshold
+        let single = MasterSecret::new([0x5a; 16])
+            .unwrap()
+            .split(
+                &mut rand::thread_rng(),
+                None,
+                1,
+                &[GroupSpec::new(1, 1), GroupSpec::new(1, 1)],
+            )
+            .unwrap();
+        assert!(matches!(
+            MasterSecret::combine(&[single[0][0].clone(), single[1][0].clone()], None),
+            Err(Slip39Error::TooManyShares)
+        ));
+        assert_eq!(
+            MasterSecret::combine(&single[1], None).unwrap().as_ref(),
+            &[0x5a; 16]
+        );
+    }
+
+    #[test]
+    fn it_rejects_insufficient_and_mismatched_shares() {
+        let (phrases, _) = TESTCASES[1];
+        assert!(matches!(
+            combine(&phrases[..1]),
+            Err(Slip39Error::InsufficientShares)
+        ));
+        assert!(matches!(
+            combine(&[phrases[0], TESTCASES[2].0[1]]),
+            Err(Slip39Error::MismatchedShares)
+        ));
+    }
+
+    #[test]
+    fn it_splits_and_combines() {
+        let mut rng = rand::thread_rng();
+        let secret = MasterSecret::from_rng(32, &mut rng).unwrap();
+        let groups = [
+            GroupSpec::new(1, 1),
+            GroupSpec::new(2, 3),
+            GroupSpec::new(3, 5),
+        ];
+        let options = SplitOptions {
+            iteration_exponent: 0,
+            extendable: false,
+        };
+        let shares = secret
+            .split_with_options(&mut rng, Some("TREZOR"), 2, &groups, options)
+            .unwrap();
+        assert_eq!(shares.iter().map(Vec::len).collect::<Vec<_>>(), [1, 3, 5]);
+
+        // group 0, plus 2 of group 1, roundtripped through phrases
+        let selected: Vec<Share> = [&shares[0][0], &shares[1][2], &shares[1][0]]
+            .iter()
+            .map(|share| share.to_phrase().parse().unwrap())
+            .collect();
+        let recovered = MasterSecret::combine(&selected, Some("TREZOR")).unwrap();
+        assert_eq!(recovered, secret);
+        assert_eq!(
+            recovered.master_key().unwrap(),
+            XPriv::root_from_seed(secret.as_ref(), None).unwrap()
+        );
+
+        // a wrong passphrase decrypts to a different secret
+        let other = MasterSecret::combine(&selected, None).unwrap();
+        assert_ne!(other, secret);
+
+        // one group is not enough
+        assert!(matches!(
+            MasterSecret::combine(&shares[2][..3], Some("TREZOR")),
+            Err(Slip39Error::InsufficientShares)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_invalid_parameters() {
+        let mut rng = rand::thread_rng();
+        let secret = MasterSecret::new([7u8; 16]).unwrap();
+        assert!(matches!(
+            MasterSecret::new([7u8; 15]),
+            Err(Slip39Error::InvalidSecretLength(15))
+        ));
+        assert!(matches!(
+            secret.split(&mut rng, None, 2, &[GroupSpec::new(1, 1)]),
+            Err(Slip39Error::InvalidThreshold { .. })
+        ));
+        assert!(matches!(
+            secret.split(&mut rng, None, 1, &[GroupSpec::new(1, 2)]),
+            Err(Slip39Error::InvalidThreshold { .. })
+        ));
+        assert!(matches!(
+            secret.split(&mut rng, Some("naïve"), 1, &[GroupSpec::new(1, 1)]),
+            Err(Slip39Error::InvalidPassphrase)
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+//! Shamir's secret sharing over GF(256), with the SLIP-39 digest share.
+
+use alloc::vec::Vec;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use super::Slip39Error;
+
+/// The x coordinate of the share holding the secret
+const SECRET_INDEX: u8 = 255;
+/// The x coordinate of the share holding the digest
+const DIGEST_INDEX: u8 = 254;
+/// The length of the digest prefix of the digest share
+const DIGEST_LENGTH: usize = 4;
+
+/// Exponent and logarithm tables of GF(256) with the Rijndael polynomial
+/// `x^8 + x^4 + x^3 + x + 1`, and generator `x + 1`
+const TABLES: ([u8; 255], [u8; 256]) = tables();
+
+const fn tables() -> ([u8; 255], [u8; 256]) {
+    let mut exp = [0u8; 255];
+    let mut log = [0u8; 256];
+    let mut poly: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = poly as u8;
+        log[poly as usize] = i as u8;
+        // multiply by the generator, x + 1
+        poly ^= poly << 1;
+        if poly & 0x100 != 0 {
+            poly ^= 0x11b;
+        }
+        i += 1;
+    }
+    (exp, log)
+}
+
+const EXP: [u8; 255] = TABLES.0;
+const LOG: [u8; 256] = TABLES.1;
+
+/// Evaluate the polynomial through `shares` at `x`. The shares must have
+/// distinct x coordinates and values of equal length.
+fn interpolate(shares: &[(u8, &[u8])], x: u8) -> Zeroizing<Vec<u8>> {
+    if let Some((_, value)) = shares.iter().find(|(xi, _)| *xi == x) {
+        return Zeroizing::new(value.to_vec());
+    }
+
+    let log_product: usize = shares
+        .iter()
+        .map(|(xi, _)| LOG[(xi ^ x) as usize] as usize)
+        .sum();
+
+    let mut result = Zeroizing::new(alloc::vec![0u8; shares[0].1.len()]);
+    for (xi, value) in shares.iter() {
+        let log_denominator: usize = shares
+            .iter()
+            .filter(|(xj, _)| xj != xi)
+            .map(|(xj, _)| LOG[(xi ^ xj) as usize] as usize)
+            .sum();
+        let log_basis =
+            (log_product + 255 * shares.len() - LOG[(xi ^ x) as usize] as usize - log_denominator)
+                % 255;
+        for (out, &byte) in result.iter_mut().zip(value.iter()) {
+            if byte != 0 {
+                *out ^= EXP[(LOG[byte as usize] as usize + log_basis) % 255];
+            }
+        }
+    }
+    result
+}
+
+/// The digest share's prefix, `HMAC-SHA256(random_part, secret)[..4]`
+fn digest(random_part: &[u8], secret: &[u8]) -> [u8; DIGEST_LENGTH] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(random_part).expect("any key length is valid");
+    mac.update(secret);
+    let mut digest = [0u8; DIGEST_LENGTH];
+    digest.copy_from_slice(&mac.finalize().into_bytes()[..DIGEST_LENGTH]);
+    digest
+}
+
+/// Split `secret` into `count` shares, any `threshold` of which recover it.
+/// The share at position `i` has x coordinate `i`.
+pub(crate) fn split<R: Rng>(
+    rng: &mut R,
+    threshold: u8,
+    count: u8,
+    secret: &[u8],
+) -> Vec<Zeroizing<Vec<u8>>> {
+    if threshold == 1 {
+        return (0..count)
+            .map(|_| Zeroizing::new(secret.to_vec()))
+            .collect();
+    }
+
+    let random_count = threshold - 2;
+    let mut shares: Vec<Zeroizing<Vec<u8>>> = (0..random_count)
+        .map(|_| {
+            let mut share = Zeroizing::new(alloc::vec![0u8; secret.len()]);
+            rng.fill(&mut share[..]);
+            share
+        })
+        .collect();
+
+    let mut digest_share = Zeroizing::new(alloc::vec![0u8; secret.len()]);
+    rng.fill(&mut digest_share[DIGEST_LENGTH..]);
+    let prefix = digest(&digest_share[DIGEST_LENGTH..], secret);
+    digest_share[..DIGEST_LENGTH].copy_from_slice(&prefix);
+
+    let mut base: Vec<(u8, &[u8])> = shares
+        .iter()
+        .enumerate()
+        .map(|(i, share)| (i as u8, &share[..]))
+        .collect();
+    base.push((DIGEST_INDEX, &digest_share));
+    base.push((SECRET_INDEX, secret));
+
+    let rest: Vec<_> = (random_count..count)
+        .map(|x| interpolate(&base, x))
+        .collect();
+    shares.extend(rest);
+    shares
+}
+
+/// Recover a secret from `threshold` shares with distinct x coordinates, and
+/// check it against the digest share.
+pub(crate) fn recover(
+    threshold: u8,
+    shares: &[(u8, &[u8])],
+) -> Result<Zeroizing<Vec<u8>>, Slip39Error> {
+    if threshold == 1 {
+        return Ok(Zeroizing::new(shares[0].1.to_vec()));
+    }
+
+    let secret = interpolate(shares, SECRET_INDEX);
+    let digest_share = interpolate(shares, DIGEST_INDEX);
+    if digest(&digest_share[DIGEST_LENGTH..], &secret) != digest_share[..DIGEST_LENGTH] {
+        return Err(Slip39Error::InvalidDigest);
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_builds_the_field_tables() {
+        assert_eq!(EXP[0], 1);
+        assert_eq!(EXP[1], 3);
+        assert_eq!(EXP[254], 0xf6);
+        assert!((1..=255).all(|x| EXP[LOG[x] as usize] as usize == x));
+    }
+
+    #[test]
+    fn it_splits_and_recovers() {
+        let mut rng = rand::thread_rng();
+        let secret = [0xa5u8; 16];
+        let shares = split(&mut rng, 3, 5, &secret);
+        assert_eq!(shares.len(), 5);
+
+        let subset: Vec<(u8, &[u8])> = [4, 0, 2]
+            .iter()
+            .map(|&i| (i as u8, &shares[i][..]))
+            .collect();
+        assert_eq!(&recover(3, &subset).unwrap()[..], &secret[..]);
+
+        let mut tampered = shares[1].to_vec();
+        tampered[0] ^= 1;
+        let subset = [(0, &shares[0][..]), (1, &tampered[..]), (3, &shares[3][..])];
+        assert!(matches!(
+            recover(3, &subset),
+            Err(Slip39Error::InvalidDigest)
+        ));
+    }
+}
@@ -0,0 +1,313 @@
+//! SLIP-39 shares and their mnemonic encoding.
+
+use alloc::{string::String, vec::Vec};
+use bitvec::prelude::*;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{Slip39Error, Slip39Wordlist};
+
+/// The bits encoded by each word
+const RADIX_BITS: usize = 10;
+/// The number of words in the header: identifier, extendable flag, iteration
+/// exponent, group and member parameters
+const HEADER_WORDS: usize = 4;
+/// The number of checksum words
+const CHECKSUM_WORDS: usize = 3;
+/// The shortest share, encoding a 128-bit secret
+pub const MIN_MNEMONIC_WORDS: usize = HEADER_WORDS + 13 + CHECKSUM_WORDS;
+
+/// RS1024 generator coefficients
+const GEN: [u32; 10] = [
+    0xe0e040, 0x1c1c080, 0x3838100, 0x7070200, 0xe0e0009, 0x1c0c2412, 0x38086c24, 0x3090fc48,
+    0x21b1f890, 0x3f3f120,
+];
+
+const fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        b"shamir_extendable"
+    } else {
+        b"shamir"
+    }
+}
+
+fn polymod(values: impl Iterator<Item = u16>) -> u32 {
+    values.fold(1, |chk, value| {
+        let top = chk >> 20;
+        let mut chk = ((chk & 0xfffff) << 10) ^ value as u32;
+        for (i, gen) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= gen;
+            }
+        }
+        chk
+    })
+}
+
+/// Check the RS1024 checksum over `words`, which include the checksum words
+fn verify_checksum(words: &[u16], extendable: bool) -> bool {
+    let custom = customization(extendable).iter().map(|&b| b as u16);
+    polymod(custom.chain(words.iter().copied())) == 1
+}
+
+/// The RS1024 checksum words for `words`
+fn create_checksum(words: &[u16], extendable: bool) -> [u16; CHECKSUM_WORDS] {
+    let custom = customization(extendable).iter().map(|&b| b as u16);
+    let values = custom
+        .chain(words.iter().copied())
+        .chain([0; CHECKSUM_WORDS]);
+    let chk = polymod(values) ^ 1;
+    [
+        (chk >> 20) as u16 & 0x3ff,
+        (chk >> 10) as u16 & 0x3ff,
+        chk as u16 & 0x3ff,
+    ]
+}
+
+/// One share of a SLIP-39 backup. The share value is zeroized on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share {
+    pub(crate) identifier: u16,
+    pub(crate) extendable: bool,
+    pub(crate) iteration_exponent: u8,
+    pub(crate) group_index: u8,
+    pub(crate) group_threshold: u8,
+    pub(crate) group_count: u8,
+    pub(crate) member_index: u8,
+    pub(crate) member_threshold: u8,
+    pub(crate) value: Zeroizing<Vec<u8>>,
+}
+
+impl core::fmt::Debug for Share {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Share")
+            .field("identifier", &self.identifier)
+            .field("extendable", &self.extendable)
+            .field("iteration_exponent", &self.iteration_exponent)
+            .field("group_index", &self.group_index)
+            .field("group_threshold", &self.group_threshold)
+            .field("group_count", &self.group_count)
+            .field("member_index", &self.member_index)
+            .field("member_threshold", &self.member_threshold)
+            .field("value", &"[redacted]")
+            .finish()
+    }
+}
+
+impl core::str::FromStr for Share {
+    type Err = Slip39Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_phrase(s)
+    }
+}
+
+impl Share {
+    /// The random identifier shared by all shares of a backup
+    pub const fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// Whether the backup is extendable. New share sets of an extendable
+    /// backup can be made for the same master secret and passphrase.
+    pub const fn extendable(&self) -> bool {
+        self.extendable
+    }
+
+    /// The exponent `e` of the PBKDF2 iteration count, `10000 * 2^e`
+    pub const fn iteration_exponent(&self) -> u8 {
+        self.iteration_exponent
+    }
+
+    /// The index of this share's group
+    pub const fn group_index(&self) -> u8 {
+        self.group_index
+    }
+
+    /// The number of groups needed to recover the master secret
+    pub const fn group_threshold(&self) -> u8 {
+        self.group_threshold
+    }
+
+    /// The total number of groups
+    pub const fn group_count(&self) -> u8 {
+        self.group_count
+    }
+
+    /// The index of this share within its group
+    pub const fn member_index(&self) -> u8 {
+        self.member_index
+    }
+
+    /// The number of shares needed to recover this share's group
+    pub const fn member_threshold(&self) -> u8 {
+        self.member_threshold
+    }
+
+    /// Parse a share from its space-separated words, and check its checksum
+    /// and padding.
+    pub fn from_phrase(phrase: &str) -> Result<Self, Slip39Error> {
+        let words = Zeroizing::new(
+            phrase
+                .split_whitespace()
+                .map(|word| Slip39Wordlist::get_index(word).map(|i| i as u16))
+                .collect::<Result<Vec<u16>, _>>()?,
+        );
+        if words.len() < MIN_MNEMONIC_WORDS {
+            return Err(Slip39Error::InvalidWordCount(words.len()));
+        }
+
+        // the extendable flag selects the checksum customization string
+        let extendable = (words[1] >> 4) & 1 == 1;
+        if !verify_checksum(&words, extendable) {
+            return Err(Slip39Error::InvalidChecksum);
+        }
+
+        let header = words[..HEADER_WORDS]
+            .iter()
+            .fold(0u64, |acc, &w| (acc << RADIX_BITS) | w as u64);
+        let field = |shift: u32, bits: u32| ((header >> shift) & ((1 << bits) - 1)) as u8;
+
+        let value_words = &words[HEADER_WORDS..words.len() - CHECKSUM_WORDS];
+        let padding = value_words.len() * RADIX_BITS % 16;
+        if padding > 8 {
+            return Err(Slip39Error::InvalidWordCount(words.len()));
+        }
+        let mut bits = BitVec::<u16, Msb0>::with_capacity(value_words.len() * RADIX_BITS);
+        for word in value_words.iter() {
+            bits.extend_from_bitslice(&word.view_bits::<Msb0>()[16 - RADIX_BITS..]);
+        }
+        if bits[..padding].any() {
+            bits.as_raw_mut_slice().zeroize();
+            return Err(Slip39Error::InvalidPadding);
+        }
+        let value = Zeroizing::new(
+            bits[padding..]
+                .chunks(8)
+                .map(|byte| byte.load_be::<u8>())
+                .collect::<Vec<u8>>(),
+        );
+        bits.as_raw_mut_slice().zeroize();
+
+        let share = Self {
+            identifier: (header >> 25) as u16,
+            extendable,
+            iteration_exponent: field(20, 4),
+            group_index: field(16, 4),
+            group_threshold: field(12, 4) + 1,
+            group_count: field(8, 4) + 1,
+            member_index: field(4, 4),
+            member_threshold: field(0, 4) + 1,
+            value,
+        };
+        if share.group_threshold > share.group_count {
+            return Err(Slip39Error::InvalidThreshold {
+                threshold: share.group_threshold as usize,
+                count: share.group_count as usize,
+            });
+        }
+        Ok(share)
+    }
+
+    /// Encode the share as words. The phrase is wiped from memory when
+    /// dropped.
+    pub fn to_phrase(&self) -> Zeroizing<String> {
+        let header = (self.identifier as u64) << 25
+            | (self.extendable as u64) << 24
+            | (self.iteration_exponent as u64) << 20
+            | (self.group_index as u64) << 16
+            | (self.group_threshold as u64 - 1) << 12
+            | (self.group_count as u64 - 1) << 8
+            | (self.member_index as u64) << 4
+            | (self.member_threshold as u64 - 1);
+
+        // the value is left-padded with zeros to a multiple of 10 bits
+        let value_bits = self.value.len() * 8;
+        let padding = (RADIX_BITS - value_bits % RADIX_BITS) % RADIX_BITS;
+        let mut bits = BitVec::<u8, Msb0>::repeat(false, padding);
+        bits.extend_from_bitslice(self.value.view_bits::<Msb0>());
+
+        let mut words: Zeroizing<Vec<u16>> = Zeroizing::new(
+            (0..HEADER_WORDS)
+                .rev()
+                .map(|i| ((header >> (i * RADIX_BITS)) & 0x3ff) as u16)
+                .chain(bits.chunks(RADIX_BITS).map(|word| word.load_be::<u16>()))
+                .collect(),
+        );
+        bits.as_raw_mut_slice().zeroize();
+        let checksum = create_checksum(&words, self.extendable);
+        words.extend(checksum);
+
+        let wordlist = Slip39Wordlist::get_all();
+        let phrase = words
+            .iter()
+            .map(|&i| wordlist[i as usize])
+            .collect::<Vec<&str>>();
+        Zeroizing::new(phrase.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard";
+
+    #[test]
+    fn it_roundtrips_phrases() {
+        let share: Share = PHRASE.parse().unwrap();
+        assert_eq!(share.identifier(), 7945);
+        assert!(!share.extendable());
+        assert_eq!(share.iteration_exponent(), 0);
+        assert_eq!(share.group_threshold(), 1);
+        assert_eq!(share.group_count(), 1);
+        assert_eq!(share.member_threshold(), 1);
+        assert_eq!(share.value.len(), 16);
+        assert_eq!(share.to_phrase().as_str(), PHRASE);
+    }
+
+    #[test]
+    fn it_rejects_invalid_checksums() {
+        let phrase = PHRASE.replace("keyboard", "kidney");
+        assert!(matches!(
+            phrase.parse::<Share>(),
+            Err(Slip39Error::InvalidChecksum)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_short_phrases() {
+        let (_, phrase) = PHRASE.split_once(' ').unwrap();
+        assert!(matches!(
+            phrase.parse::<Share>(),
+            Err(Slip39Error::InvalidWordCount(19))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_lengths_that_are_not_whole_bytes() {
+        // 14 value words carry 12 bits of padding, with a valid checksum
+        let mut words: Vec<u16> = PHRASE
+            .split(' ')
+            .take(PHRASE.split(' ').count() - CHECKSUM_WORDS)
+            .map(|word| Slip39Wordlist::get_index(word).unwrap() as u16)
+            .collect();
+        words.push(0);
+        let checksum = create_checksum(&words, false);
+        words.extend(checksum);
+        let wordlist = Slip39Wordlist::get_all();
+        let phrase: Vec<&str> = words.iter().map(|&i| wordlist[i as usize]).collect();
+        assert!(matches!(
+            phrase.join(" ").parse::<Share>(),
+            Err(Slip39Error::InvalidWordCount(21))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_unknown_words() {
+        let phrase = PHRASE.replace("duckling", "duck");
+        assert!(matches!(
+            phrase.parse::<Share>(),
+            Err(Slip39Error::WordlistError(_))
+        ));
+    }
+}
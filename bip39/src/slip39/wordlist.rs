@@ -0,0 +1,72 @@
+use crate::WordlistError;
+use alloc::{string::ToString, vec::Vec};
+use spin::Lazy;
+
+/// The SLIP-39 list of words.
+pub const RAW_SLIP39: &str = include_str!("./words/slip39.txt");
+
+/// SLIP-39 word list, split into words
+pub static PARSED: Lazy<Vec<&'static str>> = Lazy::new(|| RAW_SLIP39.lines().collect());
+
+#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+/// The 1024-word SLIP-39 wordlist. Each word encodes 10 bits, and is
+/// identified by its first 4 letters.
+///
+/// This is deliberately not a BIP39 `Wordlist`, as BIP39 words encode 11 bits.
+pub struct Slip39Wordlist;
+
+impl Slip39Wordlist {
+    /// Returns the word list.
+    pub fn get_all() -> &'static [&'static str] {
+        PARSED.as_slice()
+    }
+
+    /// Returns the word of a given index from the word list.
+    pub fn get(index: usize) -> Result<&'static str, WordlistError> {
+        Self::get_all()
+            .get(index)
+            .copied()
+            .ok_or(WordlistError::InvalidIndex(index))
+    }
+
+    /// Returns the index of a given word from the word list.
+    pub fn get_index(word: &str) -> Result<usize, WordlistError> {
+        Self::get_all()
+            .binary_search(&word)
+            .map_err(|_| WordlistError::InvalidWord(word.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        assert_eq!(Slip39Wordlist::get(0), Ok("academic"));
+        assert_eq!(Slip39Wordlist::get(1023), Ok("zero"));
+        assert_eq!(
+            Slip39Wordlist::get(1024),
+            Err(WordlistError::InvalidIndex(1024))
+        );
+    }
+
+    #[test]
+    fn test_get_index() {
+        assert_eq!(Slip39Wordlist::get_index("academic"), Ok(0));
+        assert_eq!(Slip39Wordlist::get_index("satoshi"), Ok(781));
+        assert_eq!(
+            Slip39Wordlist::get_index("abandon"),
+            Err(WordlistError::InvalidWord("abandon".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_get_all() {
+        let words = Slip39Wordlist::get_all();
+        assert_eq!(words.len(), 1024);
+        assert!(words.windows(2).all(|w| w[0] < w[1]));
+        // the first 4 letters identify each word
+        assert!(words.windows(2).all(|w| w[0].get(..4) != w[1].get(..4)));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::Wordlist;
-use once_cell::sync::Lazy;
+use alloc::vec::Vec;
+use spin::Lazy;
 
 /// The list of words as supported in the Chinese (Traditional) language.
 pub const RAW_CHINESE_TRADITIONAL: &str = include_str!("./words/chinese_traditional.txt");
@@ -1,5 +1,6 @@
 use crate::{Wordlist, WordlistError};
-use once_cell::sync::Lazy;
+use alloc::{string::ToString, vec::Vec};
+use spin::Lazy;
 
 /// The list of words as supported in the English language.
 pub const RAW_ENGLISH: &str = include_str!("./words/english.txt");
@@ -1,5 +1,6 @@
 use crate::Wordlist;
-use once_cell::sync::Lazy;
+use alloc::vec::Vec;
+use spin::Lazy;
 
 /// The list of words as supported in the French language.
 pub const RAW_FRENCH: &str = include_str!("./words/french.txt");
@@ -1,5 +1,6 @@
 use crate::Wordlist;
-use once_cell::sync::Lazy;
+use alloc::vec::Vec;
+use spin::Lazy;
 
 /// The list of words as supported in the Japanese language.
 pub const RAW_JAPANESE: &str = include_str!("./words/japanese.txt");
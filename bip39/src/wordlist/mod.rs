@@ -58,7 +58,19 @@ pub mod spanish;
 #[cfg(feature = "spanish")]
 pub use super::spanish::Spanish;
 
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::mnemonic::nfkd;
+
+/// The shortest prefix accepted as an abbreviation, in letters. Letters are
+/// counted in composed form, so an accented letter or a Hangul syllable counts
+/// once.
+pub const MIN_ABBREVIATION_LENGTH: usize = 4;
 
 #[derive(Debug, Error, PartialEq, Eq)]
 /// The error type returned while interacting with wordists.
@@ -69,6 +81,25 @@ pub enum WordlistError {
     /// Describes the error when the wordlist does not contain the queried word.
     #[error("the word `{0}` is invalid")]
     InvalidWord(String),
+    /// Describes the error when an abbreviation is the prefix of several words.
+    #[error("the abbreviation `{0}` is ambiguous")]
+    AmbiguousAbbreviation(String),
+}
+
+/// The Levenshtein distance between `a` and `b`, counted in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != *cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
 }
 
 /// The Wordlist trait that every language's wordlist must implement.
@@ -82,7 +113,7 @@ pub trait Wordlist {
     fn get(index: usize) -> Result<&'static str, WordlistError> {
         Self::get_all()
             .get(index)
-            .map(std::ops::Deref::deref)
+            .map(core::ops::Deref::deref)
             .ok_or(crate::WordlistError::InvalidIndex(index))
     }
 
@@ -93,4 +124,125 @@ pub trait Wordlist {
             .position(|&x| x == word)
             .ok_or(crate::WordlistError::InvalidWord(word.to_string()))
     }
+
+    /// Returns the words starting with `prefix`, which is NFKD normalized.
+    fn complete(prefix: &str) -> Vec<&'static str> {
+        let prefix = nfkd(prefix);
+        Self::get_all()
+            .iter()
+            .copied()
+            .filter(|word| word.starts_with(prefix.as_str()))
+            .collect()
+    }
+
+    /// Returns the words within `max_distance` edits of `word`, nearest
+    /// first. The word is NFKD normalized, and edits are counted in chars.
+    fn suggest(word: &str, max_distance: usize) -> Vec<&'static str> {
+        let word = nfkd(word);
+        let mut suggestions: Vec<(usize, &'static str)> = Self::get_all()
+            .iter()
+            .map(|&candidate| (edit_distance(&word, candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect();
+        // stable, so ties keep wordlist order
+        suggestions.sort_by_key(|(distance, _)| *distance);
+        suggestions.into_iter().map(|(_, word)| word).collect()
+    }
+
+    /// Returns the word abbreviated by `abbreviation`: either the word
+    /// itself, or a prefix of at least 4 letters that starts no other word.
+    /// The abbreviation is NFKD normalized.
+    ///
+    /// In every compiled-in list, the first 4 letters identify a word.
+    fn expand(abbreviation: &str) -> Result<&'static str, WordlistError> {
+        let abbreviation = nfkd(abbreviation);
+        if let Ok(index) = Self::get_index(&abbreviation) {
+            return Self::get(index);
+        }
+        if abbreviation.nfc().count() < MIN_ABBREVIATION_LENGTH {
+            return Err(WordlistError::InvalidWord(abbreviation.to_string()));
+        }
+        match Self::complete(&abbreviation).as_slice() {
+            [word] => Ok(word),
+            [] => Err(WordlistError::InvalidWord(abbreviation.to_string())),
+            _ => Err(WordlistError::AmbiguousAbbreviation(
+                abbreviation.to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "english", feature = "french"))]
+mod tests {
+    use super::*;
+
+    /// A list whose words share a 4 letter prefix
+    struct Accented;
+
+    impl Wordlist for Accented {
+        fn get_all() -> &'static [&'static str] {
+            &["e\u{301}cart", "e\u{301}carter", "e\u{301}chelle"]
+        }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("zoo", "zoo"), 0);
+        assert_eq!(edit_distance("ab\u{e9}", "abe"), 1);
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(English::complete("zo"), ["zone", "zoo"]);
+        assert_eq!(English::complete("abando"), ["abandon"]);
+        assert!(English::complete("xyz").is_empty());
+        // "é" completes accented words, whose lists are stored decomposed
+        assert_eq!(
+            French::complete("\u{e9}ch"),
+            ["e\u{301}charpe", "e\u{301}chelle"]
+        );
+    }
+
+    #[test]
+    fn test_suggest() {
+        assert_eq!(English::suggest("abandn", 1), ["abandon"]);
+        assert_eq!(English::suggest("zoo", 0), ["zoo"]);
+        let suggestions = English::suggest("wurld", 2);
+        assert_eq!(suggestions.first(), Some(&"world"));
+        assert!(suggestions.len() > 1);
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(English::expand("aban"), Ok("abandon"));
+        assert_eq!(English::expand("abandon"), Ok("abandon"));
+        // a whole word, though it also starts "action"
+        assert_eq!(English::expand("act"), Ok("act"));
+        assert_eq!(
+            English::expand("aba"),
+            Err(WordlistError::InvalidWord("aba".to_string()))
+        );
+        assert_eq!(
+            English::expand("xyzw"),
+            Err(WordlistError::InvalidWord("xyzw".to_string()))
+        );
+        // the accent of a decomposed "é" is not a letter
+        assert_eq!(
+            French::expand("\u{e9}ch"),
+            Err(WordlistError::InvalidWord("e\u{301}ch".to_string()))
+        );
+        assert_eq!(
+            French::expand("e\u{301}ch"),
+            Err(WordlistError::InvalidWord("e\u{301}ch".to_string()))
+        );
+        assert_eq!(French::expand("\u{e9}cha"), Ok("e\u{301}charpe"));
+        assert_eq!(French::expand("e\u{301}che"), Ok("e\u{301}chelle"));
+        assert!(matches!(
+            Accented::expand("\u{e9}car"),
+            Err(WordlistError::AmbiguousAbbreviation(_))
+        ));
+        assert_eq!(Accented::expand("\u{e9}cart"), Ok("e\u{301}cart"));
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{Wordlist, WordlistError};
-use once_cell::sync::Lazy;
+use alloc::{string::ToString, vec::Vec};
+use spin::Lazy;
 
 /// The list of words as supported in the Portuguese language.
 pub const RAW_PORTUGUESE: &str = include_str!("./words/portuguese.txt");
@@ -10,6 +10,7 @@ use bs58::{
     encode::Error as Bs58EncodeError,
 };
 
+use alloc::{borrow::ToOwned, string::String, vec, vec::Vec};
 use thiserror::Error;
 
 /// Errors that can be returned by the Bitcoin `AddressEncoder`.
@@ -39,15 +40,15 @@ pub enum EncodingError {
 
     /// Bubbled up error from base58check library
     #[error("{0}")]
-    Bs58Decode(#[from] Bs58DecodeError),
+    Bs58Decode(Bs58DecodeError),
 
     /// Bubbled up error from base58check library
     #[error("{0}")]
-    Bs58Encode(#[from] Bs58EncodeError),
+    Bs58Encode(Bs58EncodeError),
 
     /// Bubbled up error from bech32 library
-    #[error(transparent)]
-    BechError(#[from] BechError),
+    #[error("{0}")]
+    BechError(BechError),
 
     /// Op Return ScriptPubkey was passed to encoder
     #[error("Can't encode op return scripts as addresses")]
@@ -62,6 +63,26 @@ pub enum EncodingError {
     InvalidSizeError,
 }
 
+// These errors implement `std::error::Error` only with their `std` feature, so
+// they are not sources
+impl From<Bs58DecodeError> for EncodingError {
+    fn from(e: Bs58DecodeError) -> Self {
+        Self::Bs58Decode(e)
+    }
+}
+
+impl From<Bs58EncodeError> for EncodingError {
+    fn from(e: Bs58EncodeError) -> Self {
+        Self::Bs58Encode(e)
+    }
+}
+
+impl From<BechError> for EncodingError {
+    fn from(e: BechError) -> Self {
+        Self::BechError(e)
+    }
+}
+
 /// A simple result type alias
 pub type EncodingResult<T> = Result<T, EncodingError>;
 
@@ -5,11 +5,13 @@
 //! type-confusion between TXIDs, sighashes, and other digests with the same
 //! length.
 
+use alloc::string::String;
 use digest::{
     core_api::{BlockSizeUser, OutputSizeUser},
     HashMarker, Output,
 };
-use std::io::Write;
+
+use crate::io::Write;
 
 use crate::ser::{ByteFormat, SerError, SerResult};
 
@@ -78,12 +80,12 @@ where
 /// A `Digest` implementation that performs Bitcoin style double-sha256
 pub struct Hash256(sha2::Sha256);
 
-impl std::io::Write for Hash256 {
-    fn flush(&mut self) -> std::io::Result<()> {
+impl Write for Hash256 {
+    fn flush(&mut self) -> crate::io::Result<()> {
         Ok(())
     }
 
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    fn write(&mut self, buf: &[u8]) -> crate::io::Result<usize> {
         self.update(buf);
         Ok(buf.len())
     }
@@ -131,12 +133,12 @@ impl digest::Update for Hash256 {
 /// A `Digest` implementation that performs Bitcoin style double-sha256
 pub struct Hash160(sha2::Sha256);
 
-impl std::io::Write for Hash160 {
-    fn flush(&mut self) -> std::io::Result<()> {
+impl Write for Hash160 {
+    fn flush(&mut self) -> crate::io::Result<()> {
         Ok(())
     }
 
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    fn write(&mut self, buf: &[u8]) -> crate::io::Result<usize> {
         self.update(buf);
         Ok(buf.len())
     }
@@ -0,0 +1,291 @@
+//! Byte-oriented `Read` and `Write` used by `ByteFormat`.
+//!
+//! With the `std` feature these are re-exports from `std::io`. Without it, this
+//! module provides a minimal subset over byte slices, vectors and cursors, so
+//! that serialization works on `alloc` alone.
+
+#[cfg(feature = "std")]
+pub use std::io::{Cursor, Error, ErrorKind, Read, Result, Take, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::alloc_io::*;
+
+#[cfg(not(feature = "std"))]
+mod alloc_io {
+    use alloc::vec::Vec;
+    use core::{cmp, fmt};
+
+    /// The kind of an I/O error
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ErrorKind {
+        /// The reader ran out of bytes before filling a buffer
+        UnexpectedEof,
+        /// The writer stopped accepting bytes
+        WriteZero,
+        /// Any other error
+        Other,
+    }
+
+    /// An I/O error
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: &'static str,
+    }
+
+    impl Error {
+        /// Instantiate an error with a static message
+        pub const fn new(kind: ErrorKind, message: &'static str) -> Self {
+            Self { kind, message }
+        }
+
+        /// The kind of this error
+        pub const fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Self::new(kind, "I/O error")
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    /// A specialized `Result` for I/O operations
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A source of bytes. Mirrors the subset of `std::io::Read` we use.
+    pub trait Read {
+        /// Read some bytes into `buf`, returning how many were read. 0 means
+        /// the reader is exhausted.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        /// Read exactly enough bytes to fill `buf`
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        ))
+                    }
+                    n => buf = &mut core::mem::take(&mut buf)[n..],
+                }
+            }
+            Ok(())
+        }
+
+        /// Read all remaining bytes, appending them to `buf`
+        fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+            let start = buf.len();
+            let mut chunk = [0u8; 64];
+            loop {
+                match self.read(&mut chunk)? {
+                    0 => return Ok(buf.len() - start),
+                    n => buf.extend_from_slice(&chunk[..n]),
+                }
+            }
+        }
+
+        /// Adapt this reader to read at most `limit` bytes
+        fn take(self, limit: u64) -> Take<Self>
+        where
+            Self: Sized,
+        {
+            Take { inner: self, limit }
+        }
+    }
+
+    /// A sink for bytes. Mirrors the subset of `std::io::Write` we use.
+    pub trait Write {
+        /// Write some bytes from `buf`, returning how many were written
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        /// Flush any buffered bytes
+        fn flush(&mut self) -> Result<()>;
+
+        /// Write all of `buf`
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let n = cmp::min(buf.len(), self.len());
+            let (head, tail) = self.split_at(n);
+            buf[..n].copy_from_slice(head);
+            *self = tail;
+            Ok(n)
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Write for &mut [u8] {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            let n = cmp::min(buf.len(), self.len());
+            let (head, tail) = core::mem::take(self).split_at_mut(n);
+            head.copy_from_slice(&buf[..n]);
+            *self = tail;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+
+    /// A reader limited to a number of bytes. See `Read::take`.
+    #[derive(Debug)]
+    pub struct Take<R> {
+        inner: R,
+        limit: u64,
+    }
+
+    impl<R> Take<R> {
+        /// The number of bytes that may still be read
+        pub const fn limit(&self) -> u64 {
+            self.limit
+        }
+
+        /// Consume the adapter, returning the underlying reader
+        pub fn into_inner(self) -> R {
+            self.inner
+        }
+    }
+
+    impl<R: Read> Read for Take<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let max = cmp::min(buf.len() as u64, self.limit) as usize;
+            let n = self.inner.read(&mut buf[..max])?;
+            self.limit -= n as u64;
+            Ok(n)
+        }
+    }
+
+    /// Wraps an in-memory buffer and tracks a read position
+    #[derive(Debug, Clone, Default)]
+    pub struct Cursor<T> {
+        inner: T,
+        pos: u64,
+    }
+
+    impl<T> Cursor<T> {
+        /// Wrap a buffer, starting at position 0
+        pub const fn new(inner: T) -> Self {
+            Self { inner, pos: 0 }
+        }
+
+        /// Consume the cursor, returning the underlying buffer
+        pub fn into_inner(self) -> T {
+            self.inner
+        }
+
+        /// A reference to the underlying buffer
+        pub const fn get_ref(&self) -> &T {
+            &self.inner
+        }
+
+        /// The current position
+        pub const fn position(&self) -> u64 {
+            self.pos
+        }
+
+        /// Set the current position
+        pub fn set_position(&mut self, pos: u64) {
+            self.pos = pos;
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Read for Cursor<T> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let data = self.inner.as_ref();
+            let start = cmp::min(self.pos, data.len() as u64) as usize;
+            let n = (&data[start..]).read(buf)?;
+            self.pos += n as u64;
+            Ok(n)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_reads_and_writes() {
+        let mut buf = vec![];
+        buf.write_all(&[1, 2, 3, 4, 5]).unwrap();
+
+        let mut cursor = Cursor::new(buf);
+        let mut two = [0u8; 2];
+        cursor.read_exact(&mut two).unwrap();
+        assert_eq!(two, [1, 2]);
+
+        let mut rest = vec![];
+        (&mut cursor).take(2).read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, vec![3, 4]);
+
+        let mut three = [0u8; 3];
+        let err = cursor.read_exact(&mut three).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn it_writes_to_slices() {
+        let mut out = [0u8; 3];
+        let mut writer = &mut out[..];
+        assert_eq!(writer.write(&[9, 9, 9, 9]).unwrap(), 3);
+        assert_eq!(
+            writer.write_all(&[1]).unwrap_err().kind(),
+            ErrorKind::WriteZero
+        );
+        assert_eq!(out, [9, 9, 9]);
+    }
+}
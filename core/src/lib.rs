@@ -12,11 +12,18 @@
 //! Support for other chains may be added by implementing these traits. We have provided an
 //! implementation suitable for Bitcoin chains (mainnet, testnet, and signet) in the
 //! `bitcoins` crate.
+//!
+//! The `std` feature is on by default. Without it, the crate builds on `alloc`, and
+//! serialization uses the minimal `Read` and `Write` traits in the `io` module.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
 
+#[doc(hidden)]
+pub extern crate alloc;
+
 #[cfg(not(tarpaulin_include))]
 #[macro_use]
 pub mod macros;
@@ -24,6 +31,7 @@ pub mod macros;
 // pub mod builder;
 pub mod enc;
 pub mod hashes;
+pub mod io;
 // pub mod nets;
 pub mod prelude;
 pub mod ser;
@@ -21,7 +21,7 @@ macro_rules! impl_hex_serde {
             {
                 let s: &str = serde::Deserialize::deserialize(deserializer)?;
                 <$item as $crate::ser::ByteFormat>::deserialize_hex(s)
-                    .map_err(|e| serde::de::Error::custom(e.to_string()))
+                    .map_err(serde::de::Error::custom)
             }
         }
     };
@@ -37,7 +37,7 @@ macro_rules! wrap_prefixed_byte_vector {
     ) => {
         $(#[$outer])*
         #[derive(Clone, Debug, Eq, PartialEq, Default, Hash, PartialOrd, Ord)]
-        pub struct $wrapper_name(Vec<u8>);
+        pub struct $wrapper_name($crate::alloc::vec::Vec<u8>);
 
         impl $crate::ser::ByteFormat for $wrapper_name {
             type Error = $crate::ser::SerError;
@@ -50,22 +50,22 @@ macro_rules! wrap_prefixed_byte_vector {
 
             fn read_from<R>(reader: &mut R) -> Result<Self, Self::Error>
             where
-                R: std::io::Read
+                R: $crate::io::Read
             {
-                Ok(coins_core::ser::read_prefix_vec(reader)?.into())
+                Ok($crate::ser::read_prefix_vec(reader)?.into())
             }
 
             fn write_to<W>(&self, writer: &mut W) -> Result<usize, Self::Error>
             where
-                W: std::io::Write
+                W: $crate::io::Write
             {
-                coins_core::ser::write_prefix_vec(writer, &self.0)
+                $crate::ser::write_prefix_vec(writer, &self.0)
             }
         }
 
         impl_hex_serde!($wrapper_name);
 
-        impl std::convert::AsRef<[u8]> for $wrapper_name {
+        impl core::convert::AsRef<[u8]> for $wrapper_name {
             fn as_ref(&self) -> &[u8] {
                 &self.0[..]
             }
@@ -73,13 +73,13 @@ macro_rules! wrap_prefixed_byte_vector {
 
         impl $wrapper_name {
             /// Instantate a new wrapped vector
-            pub fn new(v: Vec<u8>) -> Self {
+            pub fn new(v: $crate::alloc::vec::Vec<u8>) -> Self {
                 Self(v)
             }
 
             /// Construct an empty wrapped vector instance.
             pub fn null() -> Self {
-                Self(vec![])
+                Self($crate::alloc::vec::Vec::new())
             }
 
             /// Return a reference to the underlying bytes
@@ -88,7 +88,7 @@ macro_rules! wrap_prefixed_byte_vector {
             }
 
             /// Set the underlying items vector.
-            pub fn set_items(&mut self, v: Vec<u8>) {
+            pub fn set_items(&mut self, v: $crate::alloc::vec::Vec<u8>) {
                 self.0 = v
             }
 
@@ -124,13 +124,13 @@ macro_rules! wrap_prefixed_byte_vector {
             }
         }
 
-        impl From<Vec<u8>> for $wrapper_name {
-            fn from(v: Vec<u8>) -> Self {
+        impl From<$crate::alloc::vec::Vec<u8>> for $wrapper_name {
+            fn from(v: $crate::alloc::vec::Vec<u8>) -> Self {
                 Self(v)
             }
         }
 
-        impl std::ops::Index<usize> for $wrapper_name {
+        impl core::ops::Index<usize> for $wrapper_name {
             type Output = u8;
 
             fn index(&self, index: usize) -> &Self::Output {
@@ -138,29 +138,29 @@ macro_rules! wrap_prefixed_byte_vector {
             }
         }
 
-        impl std::ops::Index<std::ops::Range<usize>> for $wrapper_name {
+        impl core::ops::Index<core::ops::Range<usize>> for $wrapper_name {
             type Output = [u8];
 
-            fn index(&self, range: std::ops::Range<usize>) -> &[u8] {
+            fn index(&self, range: core::ops::Range<usize>) -> &[u8] {
                 &self.0[range]
             }
         }
 
-        impl std::ops::IndexMut<usize> for $wrapper_name {
+        impl core::ops::IndexMut<usize> for $wrapper_name {
             fn index_mut(&mut self, index: usize) -> &mut Self::Output {
                 &mut self.0[index]
             }
         }
 
-        impl std::iter::Extend<u8> for $wrapper_name {
-            fn extend<I: std::iter::IntoIterator<Item=u8>>(&mut self, iter: I) {
+        impl core::iter::Extend<u8> for $wrapper_name {
+            fn extend<I: core::iter::IntoIterator<Item=u8>>(&mut self, iter: I) {
                 self.0.extend(iter)
             }
         }
 
-        impl std::iter::IntoIterator for $wrapper_name {
+        impl core::iter::IntoIterator for $wrapper_name {
             type Item = u8;
-            type IntoIter = std::vec::IntoIter<u8>;
+            type IntoIter = $crate::alloc::vec::IntoIter<u8>;
 
             fn into_iter(self) -> Self::IntoIter {
                 self.0.into_iter()
@@ -262,8 +262,8 @@ macro_rules! marked_digest {
 
             fn read_from<R>(reader: &mut R) -> $crate::ser::SerResult<Self>
             where
-                R: std::io::Read,
-                Self: std::marker::Sized,
+                R: $crate::io::Read,
+                Self: core::marker::Sized,
             {
                 let mut buf = Self::default();
                 reader.read_exact(buf.as_mut())?;
@@ -272,7 +272,7 @@ macro_rules! marked_digest {
 
             fn write_to<W>(&self, writer: &mut W) -> $crate::ser::SerResult<usize>
             where
-                W: std::io::Write,
+                W: $crate::io::Write,
             {
                 Ok(writer.write(self.as_ref())?)
             }
@@ -1,14 +1,13 @@
-//! A simple trait for binary (de)Serialization using std `Read` and `Write` traits.
+//! A simple trait for binary (de)Serialization using the `Read` and `Write` traits in `io`.
 
+use alloc::{string::String, vec, vec::Vec};
 use base64::{prelude::*, DecodeError};
+use core::convert::TryInto;
 use hex::FromHexError;
-use std::{
-    convert::TryInto,
-    fmt::Debug,
-    io::{Cursor, Error as IOError, Read, Write},
-};
 use thiserror::Error;
 
+use crate::io::{Cursor, Error as IOError, Read, Write};
+
 /// Erros related to serialization of types.
 #[derive(Debug, Error)]
 pub enum SerError {
@@ -21,12 +20,12 @@ pub enum SerError {
     IoError(#[from] IOError),
 
     /// `deserialize_hex` encountered an error on its input.
-    #[error(transparent)]
-    FromHexError(#[from] FromHexError),
+    #[error("{0}")]
+    FromHexError(FromHexError),
 
     /// `deserialize_base64` encountered an error on its input.
-    #[error(transparent)]
-    DecodeError(#[from] DecodeError),
+    #[error("{0}")]
+    DecodeError(DecodeError),
 
     /// An error by a component call in data structure (de)serialization
     #[error("Error in component (de)serialization: {0}")]
@@ -42,6 +41,20 @@ pub enum SerError {
     },
 }
 
+// These errors implement `core::error::Error` only with their `std` feature, so
+// they are not sources
+impl From<FromHexError> for SerError {
+    fn from(e: FromHexError) -> Self {
+        Self::FromHexError(e)
+    }
+}
+
+impl From<DecodeError> for SerError {
+    fn from(e: DecodeError) -> Self {
+        Self::DecodeError(e)
+    }
+}
+
 /// Operation mode for `read_seq_from`.
 pub enum ReadSeqMode {
     /// Specify `Exactly` to deserialize an exact number, or return an error
@@ -170,18 +183,18 @@ where
 pub fn read_prefix_vec<R, E, I>(reader: &mut R) -> Result<Vec<I>, E>
 where
     R: Read,
-    E: From<SerError> + From<IOError> + std::error::Error,
+    E: From<SerError> + From<IOError> + core::error::Error,
     I: ByteFormat<Error = E>,
 {
     let items = read_compact_int(reader)?;
-    I::read_seq_from(reader, ReadSeqMode::Exactly(items.try_into().unwrap())).map_err(Into::into)
+    I::read_seq_from(reader, ReadSeqMode::Exactly(items.try_into().unwrap()))
 }
 
 /// Convenience function to write a Bitcoin-style length-prefixed vector.
 pub fn write_prefix_vec<W, E, I>(writer: &mut W, vector: &[I]) -> Result<usize, E>
 where
     W: Write,
-    E: From<SerError> + From<IOError> + std::error::Error,
+    E: From<SerError> + From<IOError> + core::error::Error,
     I: ByteFormat<Error = E>,
 {
     let mut written = write_compact_int(writer, vector.len() as u64)?;
@@ -189,18 +202,18 @@ where
     Ok(written)
 }
 
-/// A simple trait for deserializing from `std::io::Read` and serializing to `std::io::Write`.
+/// A simple trait for deserializing from `io::Read` and serializing to `io::Write`.
 ///
 /// `ByteFormat` is used extensively in Sighash calculation, txid calculations, and transaction
 /// serialization and deserialization.
 pub trait ByteFormat {
     /// An associated error type
-    type Error: From<SerError> + From<IOError> + std::error::Error;
+    type Error: From<SerError> + From<IOError> + core::error::Error;
 
     /// Returns the byte-length of the serialized data structure.
     fn serialized_length(&self) -> usize;
 
-    /// Deserializes an instance of `Self` from a `std::io::Read`.
+    /// Deserializes an instance of `Self` from an `io::Read`.
     /// The `limit` argument is used only when deserializing collections, and  specifies a maximum
     /// number of instances of the underlying type to read.
     ///
@@ -216,9 +229,9 @@ pub trait ByteFormat {
     fn read_from<R>(reader: &mut R) -> Result<Self, Self::Error>
     where
         R: Read,
-        Self: std::marker::Sized;
+        Self: core::marker::Sized;
 
-    /// Serializes `self` to a `std::io::Write`. Following `Write` trait conventions, its `Ok`
+    /// Serializes `self` to an `io::Write`. Following `Write` trait conventions, its `Ok`
     /// type must be a `usize` denoting the number of bytes written.
     ///
     /// ```
@@ -242,7 +255,7 @@ pub trait ByteFormat {
     fn read_seq_from<R>(reader: &mut R, mode: ReadSeqMode) -> Result<Vec<Self>, Self::Error>
     where
         R: Read,
-        Self: std::marker::Sized,
+        Self: core::marker::Sized,
     {
         let mut v = vec![];
         match mode {
@@ -307,7 +320,7 @@ pub trait ByteFormat {
     ) -> Result<usize, <Self as ByteFormat>::Error>
     where
         W: Write,
-        E: Into<Self::Error> + From<SerError> + From<IOError> + std::error::Error,
+        E: Into<Self::Error> + From<SerError> + From<IOError> + core::error::Error,
         Item: 'a + ByteFormat<Error = E>,
         Iter: IntoIterator<Item = &'a Item>,
     {
@@ -321,7 +334,7 @@ pub trait ByteFormat {
     /// Decodes a hex string to a `Vec<u8>`, deserializes an instance of `Self` from that vector.
     fn deserialize_hex(s: &str) -> Result<Self, Self::Error>
     where
-        Self: std::marker::Sized,
+        Self: core::marker::Sized,
     {
         let v: Vec<u8> = hex::decode(s).map_err(SerError::from)?;
         let mut cursor = Cursor::new(v);
@@ -331,7 +344,7 @@ pub trait ByteFormat {
     /// Serialize `self` to a base64 string, using standard RFC4648 non-url safe characters
     fn deserialize_base64(s: &str) -> Result<Self, Self::Error>
     where
-        Self: std::marker::Sized,
+        Self: core::marker::Sized,
     {
         let v: Vec<u8> = BASE64_STANDARD.decode(s).map_err(SerError::from)?;
         let mut cursor = Cursor::new(v);
@@ -363,7 +376,7 @@ impl ByteFormat for u8 {
     fn read_seq_from<R>(reader: &mut R, mode: ReadSeqMode) -> SerResult<Vec<u8>>
     where
         R: Read,
-        Self: std::marker::Sized,
+        Self: core::marker::Sized,
     {
         match mode {
             ReadSeqMode::Exactly(number) => {
@@ -377,14 +390,18 @@ impl ByteFormat for u8 {
                 v.truncate(n);
                 Ok(v)
             }
-            ReadSeqMode::UntilEnd => Ok(reader.bytes().collect::<Result<Vec<u8>, _>>()?),
+            ReadSeqMode::UntilEnd => {
+                let mut v = vec![];
+                reader.read_to_end(&mut v)?;
+                Ok(v)
+            }
         }
     }
 
     fn read_from<R>(reader: &mut R) -> SerResult<Self>
     where
         R: Read,
-        Self: std::marker::Sized,
+        Self: core::marker::Sized,
     {
         let mut buf = [0u8; 1];
         reader.read_exact(&mut buf)?;
@@ -0,0 +1,104 @@
+//! Checks that `coins-core`, `coins-bip32` and `coins-bip39` work without `std`.
+//!
+//! This crate is `no_std`, and depends on the other crates with their `std`
+//! features off. Test it on its own, so that features are not unified with the
+//! rest of the workspace:
+//!
+//! ```sh
+//! cargo test -p coins-no-std-test
+//! ```
+
+#![no_std]
+#![forbid(unsafe_code)]
+#![warn(missing_docs, unreachable_pub)]
+
+extern crate alloc;
+
+#[cfg(test)]
+extern crate std;
+
+use alloc::{string::String, vec::Vec};
+
+use coins_bip32::prelude::*;
+use coins_bip39::{English, Mnemonic, MnemonicError};
+use coins_core::ser::ByteFormat;
+
+/// Derive the xpub at `path` from a BIP39 phrase, and encode it for mainnet
+/// with the version bytes for `hint`
+pub fn xpub_from_phrase(
+    phrase: &str,
+    password: Option<&str>,
+    hint: Hint,
+    path: &str,
+) -> Result<String, MnemonicError> {
+    let seed = Mnemonic::<English>::new_from_phrase(phrase)?.to_seed(password)?;
+    let root = DerivedXPriv::root_from_seed(seed.as_ref(), Some(hint))?;
+    let xpub = root.derive_path(path)?.verify_key();
+    Ok(MainnetEncoder::xpub_to_base58(&xpub)?)
+}
+
+/// Serialize a key derivation with `ByteFormat`, and read it back
+pub fn roundtrip_derivation(derivation: &KeyDerivation) -> Result<KeyDerivation, Bip32Error> {
+    let mut buf = Vec::new();
+    derivation.write_to(&mut buf)?;
+    KeyDerivation::read_from(&mut buf.as_slice())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use coins_core::hashes::Hash256;
+
+    const PHRASE: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn it_derives_from_a_mnemonic() {
+        // BIP84 test vector
+        assert_eq!(
+            xpub_from_phrase(PHRASE, None, Hint::SegWit, "m/84'/0'/0'").unwrap(),
+            "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs"
+        );
+
+        // BIP39 test vector
+        let seed = Mnemonic::<English>::new_from_phrase(PHRASE)
+            .unwrap()
+            .to_seed(Some("TREZOR"))
+            .unwrap();
+        let master = XPriv::root_from_seed(seed.as_ref(), Some(Hint::Legacy)).unwrap();
+        assert_eq!(
+            MainnetEncoder::xpriv_to_base58(&master).unwrap(),
+            "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF"
+        );
+    }
+
+    #[test]
+    fn it_signs_and_encodes() {
+        let xpriv = Mnemonic::<English>::new_from_phrase(PHRASE)
+            .unwrap()
+            .derive_key("m/44'/0'/0'/0/0", None)
+            .unwrap();
+        let digest = Hash256::default();
+        let (sig, _): (Signature, RecoveryId) = xpriv.sign_digest(digest.clone());
+        xpriv.verify_key().verify_digest(digest, &sig).unwrap();
+
+        let encoded = MainnetEncoder::xpriv_to_base58(&xpriv).unwrap();
+        assert_eq!(MainnetEncoder::xpriv_from_base58(&encoded).unwrap(), xpriv);
+    }
+
+    #[test]
+    fn it_roundtrips_derivations() {
+        let root = DerivedXPriv::root_from_seed(&[7u8; 32], None).unwrap();
+        let child = root.derive_path("m/0'/1/2'").unwrap();
+        let derivation = child.derivation().clone();
+        assert_eq!(roundtrip_derivation(&derivation).unwrap(), derivation);
+
+        // a container claiming more bytes than are available surfaces an io error
+        let mut buf = Vec::new();
+        derivation.write_to(&mut buf).unwrap();
+        assert!(matches!(
+            KeyDerivation::read_with_length(&mut &buf[..], buf.len() + 4),
+            Err(Bip32Error::IoError(_))
+        ));
+    }
+}